@@ -1,3 +1,121 @@
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Frontend item data the Rust item catalogue is generated from
+const ITEMS_SOURCE: &str = "../src/assets/items.ts";
+
 fn main() {
+    generate_item_catalogue();
     tauri_build::build()
 }
+
+/// One top-level entry of `uniqueItems`, `setItems` or `runeWords`
+#[derive(Default)]
+struct CatalogueEntry {
+    kind: String,
+    key: String,
+    name: String,
+    base: Option<String>,
+    base_code: Option<String>,
+    set_name: Option<String>,
+}
+
+/// Write `$OUT_DIR/item_catalogue.rs`, a static table of every unique, set item and runeword
+///
+/// items.ts is plain object literals with one property per line, so the top-level string
+/// properties of each entry (4-space indent) are all we need to pick out.
+fn generate_item_catalogue() {
+    println!("cargo:rerun-if-changed={}", ITEMS_SOURCE);
+
+    let source = fs::read_to_string(ITEMS_SOURCE)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", ITEMS_SOURCE, e));
+
+    let mut entries = Vec::new();
+    let mut current: Option<CatalogueEntry> = None;
+    for line in source.lines() {
+        if line == "  {" {
+            current = Some(CatalogueEntry::default());
+            continue;
+        }
+        if line == "  }," || line == "  }" {
+            if let Some(entry) = current.take() {
+                if !entry.name.is_empty() {
+                    entries.push(entry);
+                }
+            }
+            continue;
+        }
+
+        let entry = match current.as_mut() {
+            Some(entry) => entry,
+            None => continue,
+        };
+        let property = match line.strip_prefix("    ") {
+            Some(rest) if !rest.starts_with(' ') => rest,
+            _ => continue,
+        };
+        let (name, value) = match property.split_once(": ") {
+            Some(pair) => pair,
+            None => continue,
+        };
+        let value = match parse_string_literal(value.trim_end_matches(',')) {
+            Some(value) => value,
+            None => continue,
+        };
+        match name {
+            "kind" => entry.kind = value,
+            "key" => entry.key = value,
+            "name" => entry.name = value,
+            "base" => entry.base = Some(value),
+            "base_code" => entry.base_code = Some(value),
+            "set_code" => entry.set_name = Some(value),
+            _ => {}
+        }
+    }
+
+    let mut out = String::from("pub static ITEM_CATALOGUE: &[CatalogueItem] = &[\n");
+    for entry in &entries {
+        let quality = match entry.kind.as_str() {
+            "item.unique" => "Unique",
+            "item.set-item" => "Set",
+            "item.runeword" => "Runeword",
+            _ => continue,
+        };
+        let _ = writeln!(
+            out,
+            "    CatalogueItem {{ key: {:?}, name: {:?}, quality: ItemQuality::{}, base: {:?}, base_code: {:?}, set_name: {:?} }},",
+            entry.key, entry.name, quality, entry.base, entry.base_code, entry.set_name
+        );
+    }
+    out.push_str("];\n");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    write_if_changed(&out_dir.join("item_catalogue.rs"), &out);
+}
+
+/// Parse a single- or double-quoted JS string literal
+fn parse_string_literal(text: &str) -> Option<String> {
+    let quote = text.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let inner = text.strip_prefix(quote)?.strip_suffix(quote)?;
+
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            value.push(chars.next()?);
+        } else {
+            value.push(c);
+        }
+    }
+    Some(value)
+}
+
+fn write_if_changed(path: &Path, contents: &str) {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return;
+    }
+    fs::write(path, contents)
+        .unwrap_or_else(|e| panic!("Failed to write {}: {}", path.display(), e));
+}
@@ -0,0 +1,8 @@
+//! Headless entry point: `pd2-trader-cli watch --d2-dir <path>` or `pd2-trader-cli parse <file>`
+
+fn main() {
+    if let Err(e) = dmg_meter_widget_lib::cli::run(std::env::args().skip(1)) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
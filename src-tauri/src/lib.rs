@@ -9,7 +9,10 @@ use windows_sys::Win32::UI::WindowsAndMessaging::{SystemParametersInfoW, SPI_GET
 pub mod modules;
 
 // Re-export modules for easier access
-pub use modules::{chat_watcher, commands, keyboard, system, webview, window};
+pub use modules::{
+    chat_watcher, cli, commands, game_chat, hotkeys, input_capture, keyboard, player_registry,
+    system, trade_history, webview, window,
+};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -25,7 +28,11 @@ pub fn run() {
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
-        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(hotkeys::global_shortcut::handle_shortcut)
+                .build(),
+        )
         .plugin(tauri_plugin_shell::init())
         .plugin(
             tauri_plugin_window_state::Builder::default()
@@ -42,9 +49,103 @@ pub fn run() {
         }))
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_app_exit::init())
+        .manage(chat_watcher::ChatWatcher::default())
+        .manage(input_capture::InputCapture::default())
         .setup(|app| {
             let _handle = app.app_handle();
 
+            app.manage(hotkeys::HotkeyRegistry::new(
+                hotkeys::global_shortcut::GlobalShortcutBackend::new(app.app_handle().clone()),
+            ));
+
+            // Open the persistent trade history; fall back to memory so its commands keep working
+            let history = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| e.to_string())
+                .and_then(|dir| {
+                    trade_history::TradeHistory::open(&dir.join("trade-history.sqlite"))
+                })
+                .or_else(|e| {
+                    eprintln!("[trade_history] {}, keeping history in memory", e);
+                    trade_history::TradeHistory::in_memory()
+                });
+            match history {
+                Ok(history) => {
+                    app.manage(history);
+                }
+                Err(e) => eprintln!("[trade_history] {}", e),
+            }
+
+            // Same fallback for the account/character registry
+            let registry = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| e.to_string())
+                .and_then(|dir| {
+                    player_registry::PlayerRegistry::open(&dir.join("player-registry.sqlite"))
+                })
+                .or_else(|e| {
+                    eprintln!("[player_registry] {}, keeping registry in memory", e);
+                    player_registry::PlayerRegistry::in_memory()
+                });
+            match registry {
+                Ok(registry) => {
+                    app.manage(registry);
+                }
+                Err(e) => eprintln!("[player_registry] {}", e),
+            }
+
+            // Load user-edited whisper templates; the shipped ones stay active otherwise
+            let templates = chat_watcher::whisper_templates_path(app.app_handle())
+                .and_then(|path| chat_watcher::WhisperTemplates::load(&path));
+            match templates {
+                Ok(templates) => {
+                    let _ = app
+                        .state::<chat_watcher::ChatWatcher>()
+                        .set_whisper_templates(templates);
+                }
+                Err(e) => eprintln!("[chat_watcher] {}", e),
+            }
+
+            // Same for the whisper ignore list and spam rules
+            let filter = chat_watcher::whisper_filter_path(app.app_handle())
+                .and_then(|path| chat_watcher::WhisperFilter::load_config(&path))
+                .and_then(chat_watcher::WhisperFilter::new);
+            match filter {
+                Ok(filter) => {
+                    let _ = app
+                        .state::<chat_watcher::ChatWatcher>()
+                        .set_whisper_filter(filter);
+                }
+                Err(e) => eprintln!("[chat_watcher] {}", e),
+            }
+
+            // Code page of logs written by a non-UTF-8 Windows client
+            let encoding = chat_watcher::log_encoding_path(app.app_handle())
+                .and_then(|path| chat_watcher::LogEncoding::load_config(&path));
+            match encoding {
+                Ok(encoding) => {
+                    let _ = app
+                        .state::<chat_watcher::ChatWatcher>()
+                        .set_log_encoding(encoding);
+                }
+                Err(e) => eprintln!("[chat_watcher] {}", e),
+            }
+
+            // Webhook, file and stdout sinks that parsed chat events are forwarded to
+            let sinks = chat_watcher::event_sinks_path(app.app_handle())
+                .and_then(|path| chat_watcher::EventSinks::load_config(&path))
+                .and_then(chat_watcher::EventSinks::new);
+            match sinks {
+                Ok(sinks) => {
+                    let _ = app
+                        .state::<chat_watcher::ChatWatcher>()
+                        .set_event_sinks(sinks);
+                }
+                Err(e) => eprintln!("[chat_watcher] {}", e),
+            }
+
             let (x, y, width, height) =
                 match window::get_appropriate_window_bounds(app.app_handle()) {
                     Some(rect) => (
@@ -104,9 +205,14 @@ pub fn run() {
             // Initialize Diablo focus monitoring (hotkeys & window repositioning)
             let app_handle_bounds = app.app_handle().clone();
             let app_handle_focus = app.app_handle().clone();
+            let hotkey_focus =
+                hotkeys::global_shortcut::spawn_focus_worker(app.app_handle().clone());
             window::initialize_diablo_focus_monitoring(
                 app_handle_focus,
-                Some(Box::new(move |_is_focused| {
+                Some(Box::new(move |is_focused| {
+                    // Hotkeys are only registered while Diablo is focused
+                    let _ = hotkey_focus.send(is_focused);
+
                     // Reposition windows when Diablo focus changes
                     let _ = commands::update_window_bounds(app_handle_bounds.clone());
                     let _ = commands::reposition_toast_window(app_handle_bounds.clone());
@@ -126,6 +232,13 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             commands::get_diablo_rect,
             commands::press_key,
+            commands::press_key_sequence,
+            commands::send_game_chat,
+            commands::set_input_capture_enabled,
+            commands::get_input_capture_status,
+            commands::set_hotkeys,
+            commands::check_hotkeys,
+            commands::get_hotkey_status,
             commands::is_diablo_focused,
             commands::open_project_diablo2_webview,
             commands::update_window_bounds,
@@ -134,6 +247,22 @@ pub fn run() {
             commands::reposition_toast_window,
             commands::start_chat_watcher,
             commands::stop_chat_watcher,
+            commands::get_chat_watcher_status,
+            commands::get_game_session,
+            commands::get_trade_threads,
+            commands::analyse_chat_log,
+            commands::get_whisper_templates,
+            commands::set_whisper_templates,
+            commands::get_whisper_filter,
+            commands::set_whisper_filter,
+            commands::get_log_encoding,
+            commands::set_log_encoding,
+            commands::get_suppressed_whisper_counts,
+            commands::get_event_sinks,
+            commands::set_event_sinks,
+            commands::query_trade_history,
+            commands::get_account_characters,
+            commands::get_character_accounts,
             commands::get_diablo2_directory,
             commands::auto_detect_diablo2_directory,
         ])
@@ -0,0 +1,239 @@
+use super::encoding::{LineDecoder, LogEncoding};
+use super::parser::{
+    parse_chat_line, trade_message_from_chat_line, whisper_from_chat_line, ChatLine,
+    TradeMessageEvent, WhisperEvent,
+};
+use super::templates::WhisperTemplates;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// "12:34" inside a leading "[...]" stamp, e.g. "[12:34:56]" or "[2024-03-01 12:34:56]"
+static STAMP_HOUR: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|[^\d])(\d{1,2}):\d{2}").unwrap());
+
+/// One log line that parsed into a chat event, with everything the watcher derives from it
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysedLine {
+    /// 1-based line number in the input
+    pub line: usize,
+    /// Hour of day from a leading "[hh:mm]" stamp; the game itself writes none
+    pub hour: Option<u8>,
+    pub chat_line: ChatLine,
+    pub whisper: Option<WhisperEvent>,
+    pub trade: Option<TradeMessageEvent>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct NameCount {
+    pub name: String,
+    pub count: u64,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HourCount {
+    pub hour: u8,
+    pub count: u64,
+}
+
+/// Spread of the amounts asked in one currency
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceStats {
+    /// Canonical currency key, as in `PriceComponent::currency`
+    pub currency: String,
+    /// Trade messages whose price includes this currency
+    pub count: u64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+}
+
+/// Aggregates over the trade messages of a log
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LogStats {
+    pub total_lines: u64,
+    /// Lines that parsed into a chat event
+    pub parsed_lines: u64,
+    pub whispers_in: u64,
+    pub whispers_out: u64,
+    /// Trade messages in either direction
+    pub trades: u64,
+    pub incoming_trades: u64,
+    /// Catalogue name when the item resolved, else the name as written; most traded first
+    pub trades_per_item: Vec<NameCount>,
+    /// Incoming trades per account (or character), most active first
+    pub trades_per_buyer: Vec<NameCount>,
+    /// Trades per hour of day, for lines carrying a "[hh:mm]" stamp
+    pub trades_per_hour: Vec<HourCount>,
+    /// Trades on lines without a stamp, which have no hour
+    pub untimed_trades: u64,
+    /// One entry per currency seen in trade prices, most used first
+    pub price_distribution: Vec<PriceStats>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogAnalysis {
+    pub events: Vec<AnalysedLine>,
+    pub stats: LogStats,
+}
+
+/// Drop a leading "[...]" stamp, returning the hour it names, if any
+fn strip_stamp(line: &str) -> (Option<u8>, &str) {
+    let rest = match line.trim_start().strip_prefix('[') {
+        Some(rest) => rest,
+        None => return (None, line),
+    };
+    let (stamp, text) = match rest.split_once(']') {
+        Some(split) => split,
+        None => return (None, line),
+    };
+    let hour = STAMP_HOUR
+        .captures(stamp)
+        .and_then(|caps| caps[1].parse::<u8>().ok())
+        .filter(|hour| *hour < 24);
+    (hour, text.trim_start())
+}
+
+/// Sorted by count (highest first), then name
+fn sorted_counts(counts: HashMap<String, u64>) -> Vec<NameCount> {
+    let mut counts: Vec<NameCount> = counts
+        .into_iter()
+        .map(|(name, count)| NameCount { name, count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    counts
+}
+
+fn price_stats(currency: String, mut amounts: Vec<f64>) -> PriceStats {
+    amounts.sort_by(|a, b| a.total_cmp(b));
+    let count = amounts.len();
+    // The middle amount, or the mean of the two middle amounts for an even count
+    let median = (amounts[(count - 1) / 2] + amounts[count / 2]) / 2.0;
+    PriceStats {
+        currency,
+        count: count as u64,
+        min: amounts[0],
+        max: amounts[count - 1],
+        mean: amounts.iter().sum::<f64>() / count as f64,
+        median,
+    }
+}
+
+/// Run every line of a chat log through the parsers and aggregate the trades
+pub fn analyse_log(text: &str, templates: &WhisperTemplates) -> LogAnalysis {
+    let mut events = Vec::new();
+    let mut stats = LogStats::default();
+    let mut per_item: HashMap<String, u64> = HashMap::new();
+    let mut per_buyer: HashMap<String, u64> = HashMap::new();
+    let mut per_hour: BTreeMap<u8, u64> = BTreeMap::new();
+    let mut prices: HashMap<String, Vec<f64>> = HashMap::new();
+
+    for (index, line) in text.lines().enumerate() {
+        stats.total_lines += 1;
+        let (hour, line) = strip_stamp(line);
+        let chat_line = match parse_chat_line(line) {
+            Some(chat_line) => chat_line,
+            None => continue,
+        };
+        stats.parsed_lines += 1;
+        match chat_line {
+            ChatLine::WhisperIn { .. } => stats.whispers_in += 1,
+            ChatLine::WhisperOut { .. } => stats.whispers_out += 1,
+            _ => {}
+        }
+
+        let whisper = whisper_from_chat_line(&chat_line, templates);
+        let trade = trade_message_from_chat_line(&chat_line, templates);
+        if let Some(trade) = &trade {
+            stats.trades += 1;
+            if trade.is_incoming {
+                stats.incoming_trades += 1;
+                *per_buyer.entry(trade.player_name.clone()).or_default() += 1;
+            }
+            let item = trade
+                .item
+                .as_ref()
+                .map(|item| item.name.clone())
+                .or_else(|| trade.item_name.clone());
+            if let Some(item) = item {
+                *per_item.entry(item).or_default() += 1;
+            }
+            match hour {
+                Some(hour) => *per_hour.entry(hour).or_default() += 1,
+                None => stats.untimed_trades += 1,
+            }
+            if let Some(price) = &trade.parsed_price {
+                let mut currencies: Vec<&str> = price
+                    .components
+                    .iter()
+                    .map(|component| component.currency.as_str())
+                    .collect();
+                currencies.sort_unstable();
+                currencies.dedup();
+                for currency in currencies {
+                    prices
+                        .entry(currency.to_string())
+                        .or_default()
+                        .push(price.amount_of(currency));
+                }
+            }
+        }
+
+        events.push(AnalysedLine {
+            line: index + 1,
+            hour,
+            chat_line,
+            whisper,
+            trade,
+        });
+    }
+
+    stats.trades_per_item = sorted_counts(per_item);
+    stats.trades_per_buyer = sorted_counts(per_buyer);
+    stats.trades_per_hour = per_hour
+        .into_iter()
+        .map(|(hour, count)| HourCount { hour, count })
+        .collect();
+    let mut price_distribution: Vec<PriceStats> = prices
+        .into_iter()
+        .map(|(currency, amounts)| price_stats(currency, amounts))
+        .collect();
+    price_distribution.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.currency.cmp(&b.currency))
+    });
+    stats.price_distribution = price_distribution;
+
+    LogAnalysis { events, stats }
+}
+
+/// Analyse a log file, decoding it like the watcher does
+pub fn analyse_log_file(
+    path: &Path,
+    templates: &WhisperTemplates,
+    encoding: LogEncoding,
+) -> Result<LogAnalysis, String> {
+    let bytes =
+        fs::read(path).map_err(|e| format!("Failed to read chat log {}: {}", path.display(), e))?;
+    Ok(analyse_log_bytes(&bytes, templates, encoding))
+}
+
+/// Analyse a raw log, decoding each line like the watcher does
+pub fn analyse_log_bytes(
+    bytes: &[u8],
+    templates: &WhisperTemplates,
+    encoding: LogEncoding,
+) -> LogAnalysis {
+    let text = LineDecoder::new(encoding).decode_lines(bytes);
+    analyse_log(&text, templates)
+}
@@ -0,0 +1,92 @@
+use super::{emit_chat_line, parse_chat_line, unix_millis, WatchShared};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::time::{Duration, SystemTime};
+
+/// Cap on replayed lines when only a time window is given
+const BACKFILL_MAX_LINES: usize = 500;
+/// How far back de-duplication looks when the log's creation time is unknown
+const DEDUP_FALLBACK_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Which part of the existing chat log to replay when the watcher starts
+///
+/// Chat log lines carry no timestamps, so `minutes` only checks that the log was written
+/// to within that window; the replayed lines are then the last `lines` (or up to 500).
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BackfillOptions {
+    pub lines: Option<usize>,
+    pub minutes: Option<u64>,
+}
+
+/// Re-parse the tail of the chat log and emit lines the trade history hasn't seen yet
+pub(super) fn replay(
+    shared: &WatchShared,
+    app_handle: Option<&tauri::AppHandle>,
+    options: &BackfillOptions,
+) {
+    let max_lines = match (options.lines, options.minutes) {
+        (Some(lines), _) => lines,
+        (None, Some(_)) => BACKFILL_MAX_LINES,
+        (None, None) => return,
+    };
+
+    let metadata = match fs::metadata(&shared.chat_log_path) {
+        Ok(metadata) => metadata,
+        Err(_e) => return,
+    };
+    let now = SystemTime::now();
+    if let (Some(minutes), Ok(modified)) = (options.minutes, metadata.modified()) {
+        let window = Duration::from_secs(minutes.saturating_mul(60));
+        if now.duration_since(modified).unwrap_or_default() > window {
+            return; // Nothing written recently enough
+        }
+    }
+
+    let lines = match shared.chat_tail.lock() {
+        Ok(tail) => tail.read_last_lines(max_lines),
+        Err(_e) => return,
+    };
+    let lines = match lines {
+        Ok(lines) => lines,
+        Err(e) => {
+            eprintln!("[chat_watcher] Failed to read chat log for backfill: {}", e);
+            return;
+        }
+    };
+
+    // Only rows stored during this log file's lifetime can be the same lines
+    let since = metadata
+        .created()
+        .ok()
+        .or_else(|| now.checked_sub(DEDUP_FALLBACK_WINDOW))
+        .and_then(unix_millis)
+        .unwrap_or(0);
+
+    // Identical messages can legitimately repeat, so skip only as many as were recorded
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for line in lines {
+        let line_str = shared.decode(&line);
+        let chat_line = match parse_chat_line(&line_str) {
+            Some(chat_line) => chat_line,
+            None => continue,
+        };
+
+        if let Some(history) = &shared.history {
+            let occurrence = seen.entry(line_str.trim_end().to_string()).or_insert(0);
+            *occurrence += 1;
+            match history.count_recorded(&chat_line, since) {
+                Ok(recorded) if recorded >= *occurrence => continue,
+                Ok(_) => {}
+                Err(e) => eprintln!("[chat_watcher] {}", e),
+            }
+        }
+
+        if !shared.active.load(std::sync::atomic::Ordering::SeqCst) {
+            return;
+        }
+        emit_chat_line(shared, app_handle, chat_line, true);
+    }
+}
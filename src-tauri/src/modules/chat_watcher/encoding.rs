@@ -0,0 +1,149 @@
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1250, WINDOWS_1251, WINDOWS_1252};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Character encoding of the chat and game logs
+///
+/// The Windows client writes names and messages in the system's ANSI code page, so a log
+/// from a Russian or Polish install isn't UTF-8.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LogEncoding {
+    /// A code page guessed from the first line that isn't valid UTF-8
+    #[default]
+    Auto,
+    Utf8,
+    /// Central European
+    Windows1250,
+    /// Cyrillic
+    Windows1251,
+    /// Western European
+    Windows1252,
+}
+
+impl LogEncoding {
+    fn encoding(self) -> Option<&'static Encoding> {
+        match self {
+            LogEncoding::Auto => None,
+            LogEncoding::Utf8 => Some(UTF_8),
+            LogEncoding::Windows1250 => Some(WINDOWS_1250),
+            LogEncoding::Windows1251 => Some(WINDOWS_1251),
+            LogEncoding::Windows1252 => Some(WINDOWS_1252),
+        }
+    }
+
+    /// Encoding saved at `path`, or auto-detection if nothing has been saved yet
+    pub fn load_config(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("Failed to parse log encoding: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read log encoding: {}", e)),
+        }
+    }
+
+    pub fn save_config(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize log encoding: {}", e))?;
+        fs::write(path, contents).map_err(|e| format!("Failed to save log encoding: {}", e))
+    }
+}
+
+/// Bytes that are Polish, Czech or Slovak letters in Windows-1250 but symbols or rare
+/// letters in Windows-1252 (e.g. 0xB3 is "ł" there and "³" here)
+const CENTRAL_EUROPEAN_LETTERS: [u8; 12] = [
+    0x8C, 0x8D, 0x8F, 0x9C, 0x9D, 0x9F, 0xA3, 0xA5, 0xB3, 0xB9, 0xBC, 0xBE,
+];
+
+/// Guess the code page of a line that isn't UTF-8
+///
+/// A Russian word in Windows-1251 is high bytes from start to end, while accented Latin
+/// letters sit inside otherwise ASCII words. Among Latin code pages, letters only
+/// Windows-1250 has make it Central European; anything else is taken as Western European.
+fn guess_code_page(bytes: &[u8]) -> LogEncoding {
+    let mut cyrillic_words = 0;
+    let mut latin_words = 0;
+    for word in bytes.split(|byte| byte.is_ascii() && !byte.is_ascii_alphabetic()) {
+        if word.is_ascii() {
+            continue;
+        }
+        if word.iter().all(|byte| !byte.is_ascii()) {
+            cyrillic_words += 1;
+        } else {
+            latin_words += 1;
+        }
+    }
+
+    if cyrillic_words > latin_words {
+        LogEncoding::Windows1251
+    } else if bytes
+        .iter()
+        .any(|byte| CENTRAL_EUROPEAN_LETTERS.contains(byte))
+    {
+        LogEncoding::Windows1250
+    } else {
+        LogEncoding::Windows1252
+    }
+}
+
+/// Turns raw log lines into text, remembering the code page detected for the log
+#[derive(Clone, Debug, Default)]
+pub struct LineDecoder {
+    encoding: LogEncoding,
+    detected: Option<LogEncoding>,
+}
+
+impl LineDecoder {
+    pub fn new(encoding: LogEncoding) -> Self {
+        Self {
+            encoding,
+            detected: None,
+        }
+    }
+
+    /// The configured encoding
+    pub fn encoding(&self) -> LogEncoding {
+        self.encoding
+    }
+
+    /// Code page auto-detection settled on, once a line that isn't UTF-8 has been seen
+    pub fn detected(&self) -> Option<LogEncoding> {
+        self.detected
+    }
+
+    /// Decode one line; bytes invalid in the chosen encoding become U+FFFD
+    ///
+    /// Valid UTF-8 is kept as is whatever the setting, since text in a legacy code page
+    /// practically never is.
+    pub fn decode(&mut self, bytes: &[u8]) -> String {
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            return text.to_string();
+        }
+        let encoding = match self.encoding.encoding() {
+            Some(encoding) => encoding,
+            // A log is written by one client, so the first guess holds for the rest of it
+            None => self
+                .detected
+                .get_or_insert_with(|| guess_code_page(bytes))
+                .encoding()
+                .unwrap_or(UTF_8),
+        };
+        encoding.decode_without_bom_handling(bytes).0.into_owned()
+    }
+
+    /// Decode a whole log line by line, as the watcher sees it, keeping the line endings
+    ///
+    /// A UTF-8 line next to legacy code page lines stays readable, which decoding the
+    /// buffer in one go would not allow.
+    pub fn decode_lines(&mut self, bytes: &[u8]) -> String {
+        bytes
+            .split_inclusive(|&byte| byte == b'\n')
+            .map(|line| self.decode(line))
+            .collect()
+    }
+}
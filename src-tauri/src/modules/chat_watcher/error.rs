@@ -0,0 +1,119 @@
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Why the chat watcher couldn't start or stopped delivering lines
+#[derive(Debug)]
+pub enum ChatWatcherError {
+    /// No Diablo II directory was found, or the log file couldn't be created in it
+    LogNotFound,
+    /// A log file exists but couldn't be read
+    Read { path: PathBuf, source: io::Error },
+    /// The OS file watcher couldn't be created or reported a failure
+    Watcher(notify::Error),
+    /// A thread panicked while holding this lock
+    LockPoisoned(&'static str),
+    /// The reader thread couldn't be spawned
+    Thread(io::Error),
+}
+
+impl ChatWatcherError {
+    /// Stable camelCase name for events and logs
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ChatWatcherError::LogNotFound => "logNotFound",
+            ChatWatcherError::Read { .. } => "read",
+            ChatWatcherError::Watcher(_) => "watcher",
+            ChatWatcherError::LockPoisoned(_) => "lockPoisoned",
+            ChatWatcherError::Thread(_) => "thread",
+        }
+    }
+}
+
+impl fmt::Display for ChatWatcherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChatWatcherError::LogNotFound => write!(
+                f,
+                "Could not find or create chat log file. Please check your Diablo II Directory settings."
+            ),
+            ChatWatcherError::Read { path, source } => {
+                write!(f, "Failed to read {}: {}", path.display(), source)
+            }
+            ChatWatcherError::Watcher(e) => write!(f, "File watcher error: {}", e),
+            ChatWatcherError::LockPoisoned(name) => write!(f, "Failed to lock {}", name),
+            ChatWatcherError::Thread(e) => write!(f, "Failed to start chat log reader: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ChatWatcherError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ChatWatcherError::Read { source, .. } => Some(source),
+            ChatWatcherError::Watcher(e) => Some(e),
+            ChatWatcherError::Thread(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Commands report errors as strings
+impl From<ChatWatcherError> for String {
+    fn from(error: ChatWatcherError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Overall state carried by `chat-watcher-status` events
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WatchHealth {
+    /// A watch began; lines will follow as the logs grow
+    Started,
+    /// Reads work again after a failure
+    Healthy,
+    /// At least one log or the file watcher is failing; some lines may be missed
+    Degraded,
+    /// The watch ended; `reason` says why
+    Stopped,
+}
+
+/// A failure that is still affecting the watch
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchErrorInfo {
+    /// What failed: "chatLog", "gameLog" or "watcher"
+    pub source: String,
+    /// `ChatWatcherError::kind`
+    pub kind: String,
+    pub message: String,
+    /// Unix time in milliseconds when the failure was first seen
+    pub since: u64,
+}
+
+impl WatchErrorInfo {
+    pub fn new(source: &str, error: &ChatWatcherError, since: u64) -> Self {
+        Self {
+            source: source.to_string(),
+            kind: error.kind().to_string(),
+            message: error.to_string(),
+            since,
+        }
+    }
+}
+
+/// Payload of the `chat-watcher-status` event
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchHealthEvent {
+    pub health: WatchHealth,
+    /// Why the watch degraded or stopped, or how it started when notable
+    pub reason: Option<String>,
+    /// Failures still in effect
+    pub errors: Vec<WatchErrorInfo>,
+    pub backend: Option<super::WatchBackend>,
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+}
@@ -0,0 +1,254 @@
+use super::parser::{ChatLine, ChatPlayer};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+
+/// Senders tracked for rate limiting and duplicates before idle ones are forgotten
+const MAX_TRACKED_SENDERS: usize = 1000;
+
+/// Which incoming whispers are dropped before they reach the frontend
+///
+/// Only the ignore list applies by default; the rate limit and duplicate check are opt-in.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WhisperFilterConfig {
+    /// Account or character names, case-insensitive; "*" matches any run of characters
+    pub ignore: Vec<String>,
+    /// Whispers accepted from one sender per `rate_limit_window_secs`; 0 disables the limit
+    pub rate_limit: u32,
+    pub rate_limit_window_secs: u64,
+    /// The same text from the same sender within this window is dropped; 0 disables the check
+    pub duplicate_window_secs: u64,
+    /// Regexes matched against the whisper text, case-insensitive
+    pub spam_patterns: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SuppressReason {
+    Ignored,
+    RateLimited,
+    Duplicate,
+    Spam,
+}
+
+/// How many lines each rule has dropped since the app started
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SuppressedCounts {
+    pub ignored: u64,
+    pub rate_limited: u64,
+    pub duplicate: u64,
+    pub spam: u64,
+    pub total: u64,
+}
+
+impl SuppressedCounts {
+    fn add(&mut self, reason: SuppressReason) {
+        match reason {
+            SuppressReason::Ignored => self.ignored += 1,
+            SuppressReason::RateLimited => self.rate_limited += 1,
+            SuppressReason::Duplicate => self.duplicate += 1,
+            SuppressReason::Spam => self.spam += 1,
+        }
+        self.total += 1;
+    }
+}
+
+/// Ignore list and spam rules applied to incoming whispers and join lines
+///
+/// Outgoing whispers are never filtered.
+pub struct WhisperFilter {
+    config: WhisperFilterConfig,
+    ignore: Vec<Regex>,
+    spam: Vec<Regex>,
+    /// Recent whispers per lowercased sender: (unix millis, text)
+    recent: HashMap<String, VecDeque<(u64, String)>>,
+    counts: SuppressedCounts,
+}
+
+impl Default for WhisperFilter {
+    fn default() -> Self {
+        Self::new(WhisperFilterConfig::default()).expect("default whisper filter compiles")
+    }
+}
+
+/// "*shrack*" -> ^.*shrack.*$ with everything else literal
+fn wildcard_regex(pattern: &str) -> Result<Regex, String> {
+    let body = pattern
+        .trim()
+        .split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(".*");
+    RegexBuilder::new(&format!("^{}$", body))
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| format!("Invalid ignore pattern {}: {}", pattern, e))
+}
+
+impl WhisperFilter {
+    /// Compile a configuration, rejecting it if any spam pattern is invalid
+    pub fn new(config: WhisperFilterConfig) -> Result<Self, String> {
+        let ignore = config
+            .ignore
+            .iter()
+            .filter(|pattern| !pattern.trim().is_empty())
+            .map(|pattern| wildcard_regex(pattern))
+            .collect::<Result<Vec<_>, String>>()?;
+        let spam = config
+            .spam_patterns
+            .iter()
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| format!("Invalid spam pattern {}: {}", pattern, e))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Self {
+            config,
+            ignore,
+            spam,
+            recent: HashMap::new(),
+            counts: SuppressedCounts::default(),
+        })
+    }
+
+    /// Configuration saved at `path`, or the defaults if nothing has been saved yet
+    pub fn load_config(path: &Path) -> Result<WhisperFilterConfig, String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("Failed to parse whisper filter: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(WhisperFilterConfig::default())
+            }
+            Err(e) => Err(format!("Failed to read whisper filter: {}", e)),
+        }
+    }
+
+    pub fn save_config(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let contents = serde_json::to_string_pretty(&self.config)
+            .map_err(|e| format!("Failed to serialize whisper filter: {}", e))?;
+        fs::write(path, contents).map_err(|e| format!("Failed to save whisper filter: {}", e))
+    }
+
+    pub fn config(&self) -> &WhisperFilterConfig {
+        &self.config
+    }
+
+    pub fn counts(&self) -> SuppressedCounts {
+        self.counts.clone()
+    }
+
+    /// Swap in a new configuration, keeping the counts and recent-whisper tracking
+    pub fn replace(&mut self, other: WhisperFilter) {
+        self.config = other.config;
+        self.ignore = other.ignore;
+        self.spam = other.spam;
+    }
+
+    /// Run every rule against a live line; returns why it should be dropped, if it should
+    pub fn check(&mut self, chat_line: &ChatLine, now: u64) -> Option<SuppressReason> {
+        let reason = self.check_rules(chat_line).or_else(|| match chat_line {
+            ChatLine::WhisperIn { player, message } => self.check_rate(player, message, now),
+            _ => None,
+        });
+        if let Some(reason) = reason {
+            self.counts.add(reason);
+        }
+        reason
+    }
+
+    /// Only the ignore list and spam patterns, for replayed lines whose timing is unknown
+    pub fn check_replayed(&mut self, chat_line: &ChatLine) -> Option<SuppressReason> {
+        let reason = self.check_rules(chat_line);
+        if let Some(reason) = reason {
+            self.counts.add(reason);
+        }
+        reason
+    }
+
+    fn check_rules(&self, chat_line: &ChatLine) -> Option<SuppressReason> {
+        let (player, message) = match chat_line {
+            ChatLine::WhisperIn { player, message } => (player, Some(message)),
+            ChatLine::Join { player, .. } => (player, None),
+            _ => return None,
+        };
+
+        if self.is_ignored(player) {
+            return Some(SuppressReason::Ignored);
+        }
+        if message.is_some_and(|message| self.spam.iter().any(|spam| spam.is_match(message))) {
+            return Some(SuppressReason::Spam);
+        }
+        None
+    }
+
+    fn is_ignored(&self, player: &ChatPlayer) -> bool {
+        let names = [Some(player.character.as_str()), player.account.as_deref()];
+        self.ignore
+            .iter()
+            .any(|pattern| names.iter().flatten().any(|name| pattern.is_match(name)))
+    }
+
+    /// Duplicate and rate checks; every whisper counts towards the rate, even dropped ones
+    fn check_rate(
+        &mut self,
+        player: &ChatPlayer,
+        message: &str,
+        now: u64,
+    ) -> Option<SuppressReason> {
+        let rate_window = self.config.rate_limit_window_secs * 1000;
+        let duplicate_window = self.config.duplicate_window_secs * 1000;
+        let keep_for = rate_window.max(duplicate_window);
+        if keep_for == 0 {
+            return None;
+        }
+
+        if self.recent.len() >= MAX_TRACKED_SENDERS {
+            self.recent.retain(|_, whispers| {
+                whispers
+                    .back()
+                    .is_some_and(|(at, _)| now.saturating_sub(*at) < keep_for)
+            });
+        }
+
+        let whispers = self.recent.entry(player.name().to_lowercase()).or_default();
+        while whispers
+            .front()
+            .is_some_and(|(at, _)| now.saturating_sub(*at) >= keep_for)
+        {
+            whispers.pop_front();
+        }
+
+        let is_duplicate = duplicate_window > 0
+            && whispers
+                .iter()
+                .any(|(at, text)| now.saturating_sub(*at) < duplicate_window && text == message);
+        let in_window = whispers
+            .iter()
+            .filter(|(at, _)| now.saturating_sub(*at) < rate_window)
+            .count();
+        let is_limited = self.config.rate_limit > 0
+            && rate_window > 0
+            && in_window >= self.config.rate_limit as usize;
+
+        whispers.push_back((now, message.to_string()));
+
+        if is_duplicate {
+            Some(SuppressReason::Duplicate)
+        } else if is_limited {
+            Some(SuppressReason::RateLimited)
+        } else {
+            None
+        }
+    }
+}
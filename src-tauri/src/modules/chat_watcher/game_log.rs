@@ -0,0 +1,170 @@
+use serde::Serialize;
+
+/// A structured pd2_game.log line
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum GameLogEvent {
+    GameJoined {
+        name: String,
+        password: Option<String>,
+    },
+    GameLeft,
+    CharacterChanged {
+        name: String,
+    },
+    ActChanged {
+        act: u8,
+    },
+}
+
+/// What we currently know about the player's game session
+#[derive(Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GameSession {
+    pub in_game: bool,
+    pub game_name: Option<String>,
+    pub password: Option<String>,
+    pub character: Option<String>,
+    pub act: Option<u8>,
+}
+
+impl GameSession {
+    /// Fold an event into the session, returning whether anything changed
+    pub fn apply(&mut self, event: &GameLogEvent) -> bool {
+        let before = self.clone();
+        match event {
+            GameLogEvent::GameJoined { name, password } => {
+                self.in_game = true;
+                self.game_name = Some(name.clone());
+                self.password = password.clone();
+                self.act = Some(1);
+            }
+            GameLogEvent::GameLeft => {
+                self.in_game = false;
+                self.game_name = None;
+                self.password = None;
+                self.act = None;
+            }
+            GameLogEvent::CharacterChanged { name } => {
+                self.character = Some(name.clone());
+            }
+            GameLogEvent::ActChanged { act } => {
+                self.act = Some(*act);
+            }
+        }
+        *self != before
+    }
+
+    /// Game name and password in the "{gameInfo}" form used by the offer templates
+    pub fn game_info(&self) -> Option<String> {
+        let name = self.game_name.as_ref()?;
+        Some(match &self.password {
+            Some(password) => format!("{}////{}", name, password),
+            None => name.clone(),
+        })
+    }
+}
+
+/// Parse a game log line
+/// Formats: "Joined game: <name> (password: <pw>)", "Created game <name>//<pw>", "Left game",
+/// "Character: <name>", "Entered Act 2". An optional "<n>," channel prefix and "[time]" stamp are skipped.
+/// Markers must start the line, so chat quoted into the log can't fake an event.
+pub fn parse_game_line(line: &str) -> Option<GameLogEvent> {
+    let text = strip_line_prefix(line.trim_end_matches(['\r', '\n']));
+    if text.is_empty() {
+        return None;
+    }
+
+    for marker in ["joined game", "created game"] {
+        if let Some(rest) = strip_marker(text, marker).filter(|rest| rest.starts_with([':', ' '])) {
+            return parse_game_joined(rest);
+        }
+    }
+
+    if let Some(rest) = strip_marker(text, "left game") {
+        return rest
+            .trim_end_matches('.')
+            .trim()
+            .is_empty()
+            .then_some(GameLogEvent::GameLeft);
+    }
+
+    if let Some(rest) = strip_marker(text, "character:") {
+        let name = rest.split_whitespace().next()?;
+        return Some(GameLogEvent::CharacterChanged {
+            name: name.to_string(),
+        });
+    }
+
+    if let Some(rest) = strip_marker(text, "entered act ") {
+        let act = rest
+            .trim()
+            .trim_end_matches('.')
+            .parse::<u8>()
+            .ok()
+            .filter(|act| (1..=5).contains(act))?;
+        return Some(GameLogEvent::ActChanged { act });
+    }
+
+    None
+}
+
+/// The text after `marker` when the line starts with it, ignoring case
+fn strip_marker<'a>(text: &'a str, marker: &str) -> Option<&'a str> {
+    let head = text.get(..marker.len())?;
+    head.eq_ignore_ascii_case(marker)
+        .then(|| &text[marker.len()..])
+}
+
+/// Skip "<n>," channel prefixes and leading "[timestamp]" blocks
+fn strip_line_prefix(line: &str) -> &str {
+    let mut text = line.trim();
+    if let Some((prefix, rest)) = text.split_once(',') {
+        if !prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_digit()) {
+            text = rest.trim_start();
+        }
+    }
+    while let Some(rest) = text.strip_prefix('[') {
+        match rest.find(']') {
+            Some(end) => text = rest[end + 1..].trim_start(),
+            None => break,
+        }
+    }
+    text
+}
+
+/// Parse "<name>", "<name> (password: <pw>)" or "<name>//<pw>" after a join marker
+fn parse_game_joined(rest: &str) -> Option<GameLogEvent> {
+    let rest = rest
+        .trim_start_matches([':', ' '])
+        .trim()
+        .trim_end_matches('.');
+    if rest.is_empty() {
+        return None;
+    }
+
+    let lower = rest.to_ascii_lowercase();
+    let (name, password) = if let Some(pos) = lower.find("password") {
+        let password = rest[pos + 8..]
+            .trim_start_matches([':', ' '])
+            .trim_end_matches(')')
+            .trim();
+        let name = rest[..pos].trim().trim_end_matches(['(', ',', '-']).trim();
+        (name, Some(password))
+    } else if let Some((name, password)) = rest.split_once("//") {
+        (name.trim(), Some(password.trim_start_matches('/').trim()))
+    } else {
+        (rest, None)
+    };
+
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(GameLogEvent::GameJoined {
+        name: name.to_string(),
+        password: password
+            .filter(|password| !password.is_empty())
+            .map(str::to_string),
+    })
+}
@@ -0,0 +1,221 @@
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemQuality {
+    Unique,
+    Set,
+    Runeword,
+}
+
+/// An item from the catalogue generated at build time from src/assets/items.ts
+#[derive(Debug)]
+pub struct CatalogueItem {
+    pub key: &'static str,
+    pub name: &'static str,
+    pub quality: ItemQuality,
+    /// Base item name ("Shako"); runewords have none
+    pub base: Option<&'static str>,
+    pub base_code: Option<&'static str>,
+    /// Full set name for set items
+    pub set_name: Option<&'static str>,
+}
+
+include!(concat!(env!("OUT_DIR"), "/item_catalogue.rs"));
+
+/// The catalogue item a whisper refers to
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemMatch {
+    /// Catalogue key, e.g. "Mindrend" for Skull Splitter or "Runeword1" for Ancients' Pledge
+    pub key: String,
+    pub name: String,
+    pub quality: ItemQuality,
+    /// Catalogue base for uniques and sets, or the base named in the whisper for runewords
+    pub base: Option<String>,
+    pub is_ethereal: bool,
+    /// 1.0 for an exact name match, lower for base-only and fuzzy matches
+    pub confidence: f64,
+}
+
+/// Confidence when the text names a runeword followed by its base ("Spirit Monarch")
+const RUNEWORD_BASE_CONFIDENCE: f64 = 0.95;
+/// Confidence when a known item name is followed by text that isn't its base
+const EXTRA_TEXT_CONFIDENCE: f64 = 0.7;
+/// Confidence when the text is only a base that a single unique or set item uses ("Shako")
+const BASE_ONLY_CONFIDENCE: f64 = 0.8;
+/// Minimum similarity for a fuzzy (typo) match
+const FUZZY_MIN_SIMILARITY: f64 = 0.8;
+/// Fuzzy matches never score above this, even for a one-letter typo
+const FUZZY_CONFIDENCE_SCALE: f64 = 0.9;
+
+/// Catalogue names and bases, normalized once for matching
+struct IndexedItem {
+    item: &'static CatalogueItem,
+    name: Vec<String>,
+    base: Option<Vec<String>>,
+}
+
+static INDEX: Lazy<Vec<IndexedItem>> = Lazy::new(|| {
+    ITEM_CATALOGUE
+        .iter()
+        .map(|item| IndexedItem {
+            item,
+            name: tokenize(item.name).into_iter().map(|t| t.lower).collect(),
+            base: item
+                .base
+                .map(|base| tokenize(base).into_iter().map(|t| t.lower).collect()),
+        })
+        .collect()
+});
+
+/// A word of the item text, in original case and normalized for comparison
+struct Token {
+    original: String,
+    lower: String,
+}
+
+/// Split into words, dropping apostrophes ("Titan's" -> "Titans") and other punctuation
+fn tokenize(text: &str) -> Vec<Token> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '\u{2019}'))
+        .map(|word| word.replace(['\'', '\u{2019}'], ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| Token {
+            lower: word.to_lowercase(),
+            original: word,
+        })
+        .collect()
+}
+
+/// Whisper words that describe the item rather than name it
+fn is_descriptor(word: &str) -> bool {
+    if matches!(
+        word,
+        "eth" | "ethereal" | "superior" | "sup" | "perfect" | "perf" | "unid" | "socketed" | "os"
+    ) {
+        return true;
+    }
+    // "4", "4os", "3soc"
+    let suffix = word.trim_start_matches(|c: char| c.is_ascii_digit());
+    suffix.len() < word.len() && matches!(suffix, "" | "os" | "s" | "soc" | "sock" | "socket")
+}
+
+/// Resolve the item text of a trade whisper ("ethereal Titan's Revenge", "Spirit Monarch",
+/// "Harlequin Crest") to a catalogue item
+pub fn resolve_item(text: &str) -> Option<ItemMatch> {
+    let mut tokens = tokenize(text);
+    let is_ethereal = tokens
+        .iter()
+        .any(|t| t.lower == "eth" || t.lower == "ethereal");
+
+    // Descriptors lead or trail the name: "eth 4os Titan's Revenge", "Shako (superior)"
+    while tokens.first().is_some_and(|t| is_descriptor(&t.lower)) {
+        tokens.remove(0);
+    }
+    while tokens.last().is_some_and(|t| is_descriptor(&t.lower)) {
+        tokens.pop();
+    }
+    if tokens.is_empty() {
+        return None;
+    }
+    let words: Vec<&str> = tokens.iter().map(|t| t.lower.as_str()).collect();
+
+    let to_match = |indexed: &IndexedItem, base: Option<String>, confidence: f64| ItemMatch {
+        key: indexed.item.key.to_string(),
+        name: indexed.item.name.to_string(),
+        quality: indexed.item.quality,
+        base: base.or_else(|| indexed.item.base.map(str::to_string)),
+        is_ethereal,
+        confidence,
+    };
+
+    // Exact name; catalogue order puts uniques before sets before runewords
+    if let Some(indexed) = INDEX.iter().find(|indexed| indexed.name == words) {
+        return Some(to_match(indexed, None, 1.0));
+    }
+
+    // Name followed by its base ("Harlequin Crest Shako") or a runeword base ("Spirit Monarch")
+    let mut best: Option<ItemMatch> = None;
+    let mut best_len = 0;
+    for indexed in INDEX.iter() {
+        let name_len = indexed.name.len();
+        if name_len < best_len || name_len >= words.len() || words[..name_len] != indexed.name {
+            continue;
+        }
+        let rest = &words[name_len..];
+        let candidate = if indexed.base.as_deref().is_some_and(|base| base == rest) {
+            to_match(indexed, None, 1.0)
+        } else if indexed.item.quality == ItemQuality::Runeword {
+            let base = tokens[name_len..]
+                .iter()
+                .map(|t| t.original.as_str())
+                .collect::<Vec<_>>()
+                .join(" ");
+            to_match(indexed, Some(base), RUNEWORD_BASE_CONFIDENCE)
+        } else {
+            to_match(indexed, None, EXTRA_TEXT_CONFIDENCE)
+        };
+
+        let better = match &best {
+            None => true,
+            Some(best) => name_len > best_len || candidate.confidence > best.confidence,
+        };
+        if better {
+            best = Some(candidate);
+            best_len = name_len;
+        }
+    }
+    if best.is_some() {
+        return best;
+    }
+
+    // A base only one unique or set item uses
+    let mut with_base = INDEX
+        .iter()
+        .filter(|indexed| indexed.base.as_deref().is_some_and(|base| base == words));
+    if let (Some(indexed), None) = (with_base.next(), with_base.next()) {
+        return Some(to_match(indexed, None, BASE_ONLY_CONFIDENCE));
+    }
+
+    // Typos
+    let joined = words.join(" ");
+    INDEX
+        .iter()
+        .map(|indexed| (indexed, similarity(&joined, &indexed.name.join(" "))))
+        .filter(|(_, score)| *score >= FUZZY_MIN_SIMILARITY)
+        .fold(
+            None,
+            |best: Option<(&IndexedItem, f64)>, (indexed, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((indexed, score)),
+            },
+        )
+        .map(|(indexed, score)| {
+            let confidence = (score * FUZZY_CONFIDENCE_SCALE * 100.0).round() / 100.0;
+            to_match(indexed, None, confidence)
+        })
+}
+
+/// 1.0 for equal strings, down to 0.0 when every character differs (normalized Levenshtein)
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
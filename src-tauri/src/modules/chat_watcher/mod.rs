@@ -0,0 +1,1120 @@
+use crate::modules::player_registry::PlayerRegistry;
+use crate::modules::trade_history::TradeHistory;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, Manager};
+
+pub mod analysis;
+mod backfill;
+pub mod encoding;
+pub mod error;
+pub mod filter;
+pub mod game_log;
+pub mod items;
+pub mod parser;
+pub mod price;
+pub mod sinks;
+mod tail;
+pub mod templates;
+pub mod threads;
+
+pub use analysis::{analyse_log, analyse_log_bytes, analyse_log_file, LogAnalysis, LogStats};
+
+pub use backfill::BackfillOptions;
+
+pub use encoding::{LineDecoder, LogEncoding};
+
+pub use error::{ChatWatcherError, WatchErrorInfo, WatchHealth, WatchHealthEvent};
+
+pub use filter::{SuppressReason, SuppressedCounts, WhisperFilter, WhisperFilterConfig};
+
+pub use game_log::{parse_game_line, GameLogEvent, GameSession};
+
+pub use items::{resolve_item, ItemMatch, ItemQuality};
+
+pub use price::{parse_price, CurrencyKind, Price, PriceComponent};
+
+pub use sinks::{EventSink, EventSinks, RetryPolicy, SinkConfig, SinkEvent, SinkKind};
+
+pub use parser::{
+    parse_chat_line, parse_trade_message, parse_whisper, ChatLine, ChatPlayer, TradeMessageEvent,
+    WhisperEvent,
+};
+use tail::LogTail;
+
+pub use templates::{WhisperTemplate, WhisperTemplates};
+
+pub use threads::{ThreadState, TradeThread, TradeThreads};
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GameSessionEvent {
+    pub event: GameLogEvent,
+    pub session: GameSession,
+    pub game_info: Option<String>,
+}
+
+/// How the watcher learns about log changes
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WatchMode {
+    /// OS file notifications only
+    Native,
+    /// Stat the logs on a fixed interval
+    Poll,
+    /// Native notifications, switching to polling if changes show up without events
+    #[default]
+    Auto,
+}
+
+/// The mechanism currently delivering log changes
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WatchBackend {
+    Native,
+    Poll,
+}
+
+#[derive(Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatWatcherStatus {
+    pub running: bool,
+    pub mode: Option<WatchMode>,
+    pub backend: Option<WatchBackend>,
+    pub chat_log_path: Option<String>,
+    pub chat_log_offset: u64,
+    pub game_log_path: Option<String>,
+    pub game_log_offset: u64,
+    /// Unix time in milliseconds of the last emitted event
+    pub last_event_at: Option<u64>,
+    /// Healthy or degraded while running
+    pub health: Option<WatchHealth>,
+    /// Failures currently affecting the watch
+    pub errors: Vec<WatchErrorInfo>,
+    /// Code page picked by auto-detection for the current logs
+    pub detected_encoding: Option<LogEncoding>,
+}
+
+/// Failure sources tracked in `WatchErrorInfo::source`
+const SOURCE_CHAT_LOG: &str = "chatLog";
+const SOURCE_GAME_LOG: &str = "gameLog";
+const SOURCE_WATCHER: &str = "watcher";
+
+/// Chat log watcher registered as Tauri state
+/// Owns the file watcher and the read offsets of the currently watched logs
+#[derive(Default)]
+pub struct ChatWatcher {
+    session: Mutex<Option<WatchSession>>,
+    game_session: Arc<Mutex<GameSession>>,
+    templates: Arc<RwLock<WhisperTemplates>>,
+    threads: Arc<Mutex<TradeThreads>>,
+    filter: Arc<Mutex<WhisperFilter>>,
+    sinks: Arc<Mutex<EventSinks>>,
+    decoder: Arc<Mutex<LineDecoder>>,
+}
+
+/// Quiet period after the last file event before the reader processes a batch
+const READ_DEBOUNCE: Duration = Duration::from_millis(100);
+/// Upper bound on how long a steady stream of events can delay a read
+const READ_MAX_DELAY: Duration = Duration::from_millis(500);
+/// Stat interval in poll mode, and how often auto mode checks for missed events
+const POLL_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// A running watch: the file watcher (absent when polling) and the reader thread it wakes
+struct WatchSession {
+    app_handle: Option<tauri::AppHandle>,
+    watcher: Option<RecommendedWatcher>,
+    wake: SyncSender<()>,
+    reader: JoinHandle<()>,
+    shared: Arc<WatchShared>,
+}
+
+/// State shared between the watcher callback and the reader thread
+struct WatchShared {
+    /// Cleared on stop so a read already in progress emits nothing
+    active: AtomicBool,
+    mode: WatchMode,
+    /// Set once the reader stats the logs itself instead of waiting for events
+    polling: AtomicBool,
+    /// Set by the watcher callback, cleared by the reader before reading
+    chat_dirty: AtomicBool,
+    game_dirty: AtomicBool,
+    chat_log_path: PathBuf,
+    chat_tail: Mutex<LogTail>,
+    game_log_path: Option<PathBuf>,
+    game_tail: Option<Mutex<LogTail>>,
+    game_session: Arc<Mutex<GameSession>>,
+    templates: Arc<RwLock<WhisperTemplates>>,
+    threads: Arc<Mutex<TradeThreads>>,
+    filter: Arc<Mutex<WhisperFilter>>,
+    /// Where parsed events are forwarded besides the frontend
+    sinks: Arc<Mutex<EventSinks>>,
+    /// Shared by both logs since the same client writes them
+    decoder: Arc<Mutex<LineDecoder>>,
+    /// Where whispers are persisted, if the history store is available
+    history: Option<TradeHistory>,
+    /// Where account/character pairs are remembered, if the registry is available
+    registry: Option<PlayerRegistry>,
+    last_event_at: Mutex<Option<SystemTime>>,
+    /// Failures not yet cleared by a successful read, at most one per source
+    errors: Mutex<Vec<WatchErrorInfo>>,
+}
+
+/// Find the Diablo II installation directory
+/// If custom_path is provided and exists, use it. Otherwise, try auto-detection.
+pub fn find_diablo2_directory(custom_path: Option<&str>) -> Option<PathBuf> {
+    // If custom path is provided and exists, use it
+    if let Some(custom) = custom_path {
+        if !custom.is_empty() {
+            let path = PathBuf::from(custom);
+            if path.exists() {
+                return Some(path);
+            }
+        }
+    }
+
+    crate::modules::system::find_diablo2_install_path()
+}
+
+/// Auto-detect the Diablo II installation directory (without using custom path)
+pub fn auto_detect_diablo2_directory() -> Option<PathBuf> {
+    find_diablo2_directory(None)
+}
+
+// find_diablo2_in_registry removed
+
+/// Get the chat log file path, creating directories if needed
+pub fn get_chat_log_path(custom_d2_dir: Option<&str>) -> Option<PathBuf> {
+    let d2_dir = find_diablo2_directory(custom_d2_dir)?;
+    let logs_dir = d2_dir.join("ProjectD2").join("pd2logs");
+    // Create directories if they don't exist
+    if let Err(_e) = fs::create_dir_all(&logs_dir) {
+        return None;
+    }
+
+    let log_file = logs_dir.join("pd2_chat.log");
+
+    // Create file if it doesn't exist
+    if !log_file.exists() {
+        if let Err(_e) = fs::File::create(&log_file) {
+            return None;
+        }
+    }
+
+    Some(log_file)
+}
+
+/// Get the game log file path, creating directories if needed
+pub fn get_game_log_path(custom_d2_dir: Option<&str>) -> Option<PathBuf> {
+    let d2_dir = find_diablo2_directory(custom_d2_dir)?;
+    let logs_dir = d2_dir.join("ProjectD2").join("pd2logs");
+
+    // Create directories if they don't exist
+    if let Err(_e) = fs::create_dir_all(&logs_dir) {
+        return None;
+    }
+
+    let log_file = logs_dir.join("pd2_game.log");
+
+    // Create file if it doesn't exist
+    if !log_file.exists() {
+        if let Err(_e) = fs::File::create(&log_file) {
+            return None;
+        }
+    }
+
+    Some(log_file)
+}
+
+/// Where user-edited whisper templates are saved
+pub fn whisper_templates_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_config_dir()
+        .map(|dir| dir.join("whisper-templates.json"))
+        .map_err(|e| format!("Failed to resolve config directory: {}", e))
+}
+
+/// Where the whisper ignore list and spam rules are saved
+pub fn whisper_filter_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_config_dir()
+        .map(|dir| dir.join("whisper-filter.json"))
+        .map_err(|e| format!("Failed to resolve config directory: {}", e))
+}
+
+/// Where the chosen log encoding is saved
+pub fn log_encoding_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_config_dir()
+        .map(|dir| dir.join("log-encoding.json"))
+        .map_err(|e| format!("Failed to resolve config directory: {}", e))
+}
+
+/// Where the event sink configurations are saved
+pub fn event_sinks_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_config_dir()
+        .map(|dir| dir.join("event-sinks.json"))
+        .map_err(|e| format!("Failed to resolve config directory: {}", e))
+}
+
+/// Read new lines from the chat log file
+fn read_new_lines(
+    shared: &WatchShared,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<(), ChatWatcherError> {
+    let mut tail = shared
+        .chat_tail
+        .lock()
+        .map_err(|_| ChatWatcherError::LockPoisoned("chat log reader"))?;
+    // Checked under the lock so a stopped session never emits
+    if !shared.active.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let lines = tail.read_lines().map_err(|source| ChatWatcherError::Read {
+        path: shared.chat_log_path.clone(),
+        source,
+    })?;
+    for line in lines {
+        let line_str = shared.decode(&line);
+
+        if let Some(chat_line) = parse_chat_line(&line_str) {
+            emit_chat_line(shared, app_handle, chat_line, false);
+        }
+    }
+
+    Ok(())
+}
+
+/// Record a parsed line and emit it along with the legacy trade/whisper events derived from it
+fn emit_chat_line(
+    shared: &WatchShared,
+    app_handle: Option<&tauri::AppHandle>,
+    chat_line: ChatLine,
+    backfilled: bool,
+) {
+    let now = unix_millis(SystemTime::now()).unwrap_or(0);
+
+    // Ignored senders and spam never reach the frontend, the history or the threads
+    let suppressed = match shared.filter.lock() {
+        Ok(mut filter) if backfilled => filter.check_replayed(&chat_line),
+        Ok(mut filter) => filter.check(&chat_line, now),
+        Err(_e) => None,
+    };
+    if suppressed.is_some() {
+        return;
+    }
+
+    let templates = shared
+        .templates
+        .read()
+        .map(|templates| templates.clone())
+        .unwrap_or_default();
+
+    // Parse trade message first (both incoming and outgoing)
+    let trade_message = parser::trade_message_from_chat_line(&chat_line, &templates);
+
+    if let Some(history) = &shared.history {
+        if let Err(e) = history.record(&chat_line, trade_message.as_ref(), now) {
+            eprintln!("[chat_watcher] {}", e);
+        }
+    }
+
+    if let Some(registry) = &shared.registry {
+        let recorded = if backfilled {
+            registry.record_replayed(&chat_line, now)
+        } else {
+            registry.record(&chat_line, now)
+        };
+        if let Err(e) = recorded {
+            eprintln!("[chat_watcher] {}", e);
+        }
+    }
+
+    let updated_threads = match shared.threads.lock() {
+        Ok(mut threads) => threads.apply(&chat_line, trade_message.as_ref(), now),
+        Err(_e) => Vec::new(),
+    };
+    for thread in updated_threads {
+        publish(
+            &shared.sinks,
+            app_handle,
+            "trade-thread-updated",
+            thread,
+            now,
+        );
+    }
+
+    if let Some(mut trade_message) = trade_message {
+        trade_message.is_backfilled = backfilled;
+        // Emit trade message event to frontend
+        publish(
+            &shared.sinks,
+            app_handle,
+            "trade-message",
+            trade_message,
+            now,
+        );
+    }
+
+    // Not every line is a whisper we care about
+    if let Some(mut whisper) = parser::whisper_from_chat_line(&chat_line, &templates) {
+        whisper.is_backfilled = backfilled;
+        // Emit whisper event to frontend
+        publish(&shared.sinks, app_handle, "whisper-received", whisper, now);
+    }
+
+    let event = if backfilled {
+        "chat-line-backfilled"
+    } else {
+        "chat-line"
+    };
+    publish(&shared.sinks, app_handle, event, chat_line, now);
+    shared.touch();
+}
+
+/// Emit an event to the frontend, when there is one, and hand it to the configured sinks
+fn publish<S: Serialize + Clone>(
+    sinks: &Mutex<EventSinks>,
+    app_handle: Option<&tauri::AppHandle>,
+    event: &str,
+    payload: S,
+    timestamp: u64,
+) {
+    if let Ok(sinks) = sinks.lock() {
+        sinks.dispatch(event, &payload, timestamp);
+    }
+    if let Some(app_handle) = app_handle {
+        let _ = app_handle.emit(event, payload);
+    }
+}
+
+/// Read new lines from the game log file and update the current session
+fn read_new_game_lines(
+    shared: &WatchShared,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<(), ChatWatcherError> {
+    let (path, tail) = match (&shared.game_log_path, &shared.game_tail) {
+        (Some(path), Some(tail)) => (path, tail),
+        _ => return Ok(()),
+    };
+    let mut tail = tail
+        .lock()
+        .map_err(|_| ChatWatcherError::LockPoisoned("game log reader"))?;
+    if !shared.active.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let lines = tail.read_lines().map_err(|source| ChatWatcherError::Read {
+        path: path.clone(),
+        source,
+    })?;
+    for line in lines {
+        let line_str = shared.decode(&line);
+        let event = match parse_game_line(&line_str) {
+            Some(event) => event,
+            None => continue,
+        };
+
+        let session = match shared.game_session.lock() {
+            Ok(mut session) => {
+                session.apply(&event);
+                session.clone()
+            }
+            Err(_e) => continue,
+        };
+        let game_info = session.game_info();
+        publish(
+            &shared.sinks,
+            app_handle,
+            "game-session-event",
+            GameSessionEvent {
+                event,
+                session,
+                game_info,
+            },
+            unix_millis(SystemTime::now()).unwrap_or(0),
+        );
+        shared.touch();
+    }
+
+    Ok(())
+}
+
+/// Whether a watcher event touches the given log file
+/// The parent directory is watched so delete+recreate and renames are seen too
+fn is_log_event(event: &Event, log_path: &Path) -> bool {
+    let relevant_kind = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    );
+    relevant_kind
+        && event
+            .paths
+            .iter()
+            .any(|path| path.file_name() == log_path.file_name())
+}
+
+fn unix_millis(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_millis() as u64)
+}
+
+/// Create the OS file watcher; it only flags which log changed and wakes the reader
+fn create_native_watcher(
+    shared: &Arc<WatchShared>,
+    wake: SyncSender<()>,
+    app_handle: Option<tauri::AppHandle>,
+) -> Result<RecommendedWatcher, ChatWatcherError> {
+    let shared_for_watcher = shared.clone();
+
+    let mut watcher: RecommendedWatcher =
+        notify::recommended_watcher(move |result: Result<Event, notify::Error>| {
+            let shared = &shared_for_watcher;
+            match result {
+                Ok(event) => {
+                    shared.recover(app_handle.as_ref(), SOURCE_WATCHER);
+                    if is_log_event(&event, &shared.chat_log_path) {
+                        shared.mark_dirty(&shared.chat_dirty, &wake);
+                    } else if shared
+                        .game_log_path
+                        .as_deref()
+                        .is_some_and(|path| is_log_event(&event, path))
+                    {
+                        shared.mark_dirty(&shared.game_dirty, &wake);
+                    }
+                }
+                Err(e) => {
+                    // Changes may go unnoticed until the next successful event
+                    shared.fail(
+                        app_handle.as_ref(),
+                        SOURCE_WATCHER,
+                        ChatWatcherError::Watcher(e),
+                    );
+                    shared.mark_dirty(&shared.chat_dirty, &wake);
+                }
+            }
+        })
+        .map_err(ChatWatcherError::Watcher)?;
+
+    // Watch the logs directory so the file can be deleted and recreated by the game
+    let logs_dir = shared
+        .chat_log_path
+        .parent()
+        .ok_or(ChatWatcherError::LogNotFound)?;
+    watcher
+        .watch(logs_dir, RecursiveMode::NonRecursive)
+        .map_err(ChatWatcherError::Watcher)?;
+
+    Ok(watcher)
+}
+
+/// Reader thread body: wait for a wake-up, let the burst settle, then read dirty logs in order
+/// Exits once the watcher and the session (the only senders) are dropped
+fn run_reader(
+    shared: Arc<WatchShared>,
+    app_handle: Option<tauri::AppHandle>,
+    wake: Receiver<()>,
+    backfill: Option<BackfillOptions>,
+) {
+    // Replay before following so backfilled lines come first, in file order
+    if let Some(options) = backfill {
+        backfill::replay(&shared, app_handle.as_ref(), &options);
+    }
+
+    // Auto mode only falls back after unread data survives two checks without an event
+    let mut suspect_missed_events = false;
+
+    loop {
+        if shared.polling.load(Ordering::SeqCst) {
+            match wake.recv_timeout(POLL_INTERVAL) {
+                Ok(()) => {}
+                Err(RecvTimeoutError::Timeout) => {
+                    shared.chat_dirty.store(true, Ordering::SeqCst);
+                    shared.game_dirty.store(true, Ordering::SeqCst);
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        } else if shared.mode == WatchMode::Auto {
+            match wake.recv_timeout(POLL_INTERVAL) {
+                Ok(()) => suspect_missed_events = false,
+                Err(RecvTimeoutError::Timeout) => {
+                    if !shared.has_unread_data() {
+                        suspect_missed_events = false;
+                        continue;
+                    }
+                    if !suspect_missed_events {
+                        suspect_missed_events = true;
+                        continue;
+                    }
+                    // Data keeps arriving without events; notifications don't work here
+                    shared.polling.store(true, Ordering::SeqCst);
+                    shared.chat_dirty.store(true, Ordering::SeqCst);
+                    shared.game_dirty.store(true, Ordering::SeqCst);
+                    let now = unix_millis(SystemTime::now()).unwrap_or(0);
+                    publish(
+                        &shared.sinks,
+                        app_handle.as_ref(),
+                        "chat-watcher-backend",
+                        WatchBackend::Poll,
+                        now,
+                    );
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        } else if wake.recv().is_err() {
+            break;
+        }
+
+        let deadline = Instant::now() + READ_MAX_DELAY;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            match wake.recv_timeout(READ_DEBOUNCE.min(remaining)) {
+                Ok(()) => continue,
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        let app_handle = app_handle.as_ref();
+        if shared.chat_dirty.swap(false, Ordering::SeqCst) {
+            match read_new_lines(&shared, app_handle) {
+                Ok(()) => shared.recover(app_handle, SOURCE_CHAT_LOG),
+                Err(e) => shared.fail(app_handle, SOURCE_CHAT_LOG, e),
+            }
+        }
+        if shared.game_dirty.swap(false, Ordering::SeqCst) {
+            match read_new_game_lines(&shared, app_handle) {
+                Ok(()) => shared.recover(app_handle, SOURCE_GAME_LOG),
+                Err(e) => shared.fail(app_handle, SOURCE_GAME_LOG, e),
+            }
+        }
+    }
+}
+
+impl WatchSession {
+    /// Stop emitting, drop the senders and wait for the reader to finish its current batch
+    fn shutdown(self, reason: &str) {
+        self.shared.active.store(false, Ordering::SeqCst);
+        drop(self.watcher);
+        drop(self.wake);
+        let _ = self.reader.join();
+        self.shared
+            .report(self.app_handle.as_ref(), WatchHealth::Stopped, Some(reason));
+    }
+}
+
+impl WatchShared {
+    /// Mark a log as changed and wake the reader; a full queue already has a wake-up pending
+    fn mark_dirty(&self, dirty: &AtomicBool, wake: &SyncSender<()>) {
+        dirty.store(true, Ordering::SeqCst);
+        let _ = wake.try_send(());
+    }
+
+    /// Whether any log has grown or been replaced since it was last read
+    fn has_unread_data(&self) -> bool {
+        let chat = self
+            .chat_tail
+            .lock()
+            .map(|tail| tail.has_unread_data())
+            .unwrap_or(false);
+        let game = self
+            .game_tail
+            .as_ref()
+            .and_then(|tail| tail.lock().ok().map(|tail| tail.has_unread_data()))
+            .unwrap_or(false);
+        chat || game
+    }
+
+    fn backend(&self) -> WatchBackend {
+        if self.polling.load(Ordering::SeqCst) {
+            WatchBackend::Poll
+        } else {
+            WatchBackend::Native
+        }
+    }
+
+    fn touch(&self) {
+        if let Ok(mut last) = self.last_event_at.lock() {
+            *last = Some(SystemTime::now());
+        }
+    }
+
+    /// Text of a raw log line in the configured or detected encoding
+    fn decode(&self, line: &[u8]) -> String {
+        match self.decoder.lock() {
+            Ok(mut decoder) => decoder.decode(line),
+            Err(_e) => String::from_utf8_lossy(line).into_owned(),
+        }
+    }
+
+    /// Record a failure of `source`; a new or changed failure is logged and reported as degraded
+    fn fail(&self, app_handle: Option<&tauri::AppHandle>, source: &str, error: ChatWatcherError) {
+        let now = unix_millis(SystemTime::now()).unwrap_or(0);
+        {
+            // Poisoning here would hide every later failure, so recover the list instead
+            let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+            let message = error.to_string();
+            if errors
+                .iter()
+                .any(|known| known.source == source && known.message == message)
+            {
+                return;
+            }
+            errors.retain(|known| known.source != source);
+            errors.push(WatchErrorInfo::new(source, &error, now));
+        }
+        eprintln!(
+            "[chat_watcher] {} failed ({}): {}",
+            source,
+            error.kind(),
+            error
+        );
+        self.report(app_handle, WatchHealth::Degraded, Some(&error.to_string()));
+    }
+
+    /// `source` works again; once nothing is failing the watch is reported healthy
+    fn recover(&self, app_handle: Option<&tauri::AppHandle>, source: &str) {
+        let degraded = {
+            let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+            let before = errors.len();
+            errors.retain(|known| known.source != source);
+            if errors.len() == before {
+                return;
+            }
+            !errors.is_empty()
+        };
+        eprintln!("[chat_watcher] {} recovered", source);
+        let health = if degraded {
+            WatchHealth::Degraded
+        } else {
+            WatchHealth::Healthy
+        };
+        self.report(app_handle, health, None);
+    }
+
+    fn health(&self) -> WatchHealth {
+        let degraded = self
+            .errors
+            .lock()
+            .map(|errors| !errors.is_empty())
+            .unwrap_or(true);
+        if degraded {
+            WatchHealth::Degraded
+        } else {
+            WatchHealth::Healthy
+        }
+    }
+
+    /// Emit a `chat-watcher-status` event with the failures still in effect
+    fn report(
+        &self,
+        app_handle: Option<&tauri::AppHandle>,
+        health: WatchHealth,
+        reason: Option<&str>,
+    ) {
+        let errors = self
+            .errors
+            .lock()
+            .map(|errors| errors.clone())
+            .unwrap_or_default();
+        let timestamp = unix_millis(SystemTime::now()).unwrap_or(0);
+        let event = WatchHealthEvent {
+            health,
+            reason: reason.map(str::to_string),
+            errors,
+            backend: (health != WatchHealth::Stopped).then(|| self.backend()),
+            timestamp,
+        };
+        publish(
+            &self.sinks,
+            app_handle,
+            "chat-watcher-status",
+            event,
+            timestamp,
+        );
+    }
+}
+
+impl ChatWatcher {
+    /// Start watching the chat log file, replacing any running watch
+    pub fn start(
+        &self,
+        app_handle: tauri::AppHandle,
+        custom_d2_dir: Option<String>,
+        mode: WatchMode,
+        backfill: Option<BackfillOptions>,
+    ) -> Result<WatchBackend, ChatWatcherError> {
+        let history = app_handle
+            .try_state::<TradeHistory>()
+            .map(|history| history.inner().clone());
+        let registry = app_handle
+            .try_state::<PlayerRegistry>()
+            .map(|registry| registry.inner().clone());
+        let result = self.start_session(
+            Some(app_handle.clone()),
+            custom_d2_dir,
+            mode,
+            backfill,
+            history,
+            registry,
+        );
+        if let Err(e) = &result {
+            let _ = app_handle.emit("error", e.to_string());
+        }
+        result
+    }
+
+    /// Start watching without a frontend; events only go to the configured sinks
+    ///
+    /// Used by the command line, which has no Tauri app to emit to or keep stores in.
+    pub fn start_headless(
+        &self,
+        custom_d2_dir: Option<String>,
+        mode: WatchMode,
+        backfill: Option<BackfillOptions>,
+    ) -> Result<WatchBackend, ChatWatcherError> {
+        self.start_session(None, custom_d2_dir, mode, backfill, None, None)
+    }
+
+    fn start_session(
+        &self,
+        app_handle: Option<tauri::AppHandle>,
+        custom_d2_dir: Option<String>,
+        mode: WatchMode,
+        backfill: Option<BackfillOptions>,
+        history: Option<TradeHistory>,
+        registry: Option<PlayerRegistry>,
+    ) -> Result<WatchBackend, ChatWatcherError> {
+        let result = self.open_session(
+            app_handle.clone(),
+            custom_d2_dir,
+            mode,
+            backfill,
+            history,
+            registry,
+        );
+        if let Err(e) = &result {
+            eprintln!("[chat_watcher] start failed ({}): {}", e.kind(), e);
+            let timestamp = unix_millis(SystemTime::now()).unwrap_or(0);
+            let event = WatchHealthEvent {
+                health: WatchHealth::Stopped,
+                reason: Some(e.to_string()),
+                errors: Vec::new(),
+                backend: None,
+                timestamp,
+            };
+            publish(
+                &self.sinks,
+                app_handle.as_ref(),
+                "chat-watcher-status",
+                event,
+                timestamp,
+            );
+        }
+        result
+    }
+
+    fn open_session(
+        &self,
+        app_handle: Option<tauri::AppHandle>,
+        custom_d2_dir: Option<String>,
+        mode: WatchMode,
+        backfill: Option<BackfillOptions>,
+        history: Option<TradeHistory>,
+        registry: Option<PlayerRegistry>,
+    ) -> Result<WatchBackend, ChatWatcherError> {
+        let log_path =
+            get_chat_log_path(custom_d2_dir.as_deref()).ok_or(ChatWatcherError::LogNotFound)?;
+
+        // The game log lives next to the chat log and is followed by the same watcher
+        let game_log_path = get_game_log_path(custom_d2_dir.as_deref());
+
+        // Stop the previous session first so its readers stop emitting
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|_| ChatWatcherError::LockPoisoned("chat watcher"))?;
+        if let Some(previous) = session.take() {
+            previous.shutdown("Restarted");
+        }
+
+        // The logs may be from another install, so detect their code page afresh
+        let encoding = self.log_encoding();
+        self.set_log_encoding(encoding)
+            .map_err(|_| ChatWatcherError::LockPoisoned("log decoder"))?;
+
+        // Start reading from the current end of the files
+        let shared = Arc::new(WatchShared {
+            active: AtomicBool::new(true),
+            mode,
+            polling: AtomicBool::new(mode == WatchMode::Poll),
+            chat_dirty: AtomicBool::new(false),
+            game_dirty: AtomicBool::new(false),
+            chat_tail: Mutex::new(LogTail::at_end(log_path.clone())),
+            chat_log_path: log_path.clone(),
+            game_tail: game_log_path
+                .clone()
+                .map(|path| Mutex::new(LogTail::at_end(path))),
+            game_log_path,
+            game_session: self.game_session.clone(),
+            templates: self.templates.clone(),
+            threads: self.threads.clone(),
+            filter: self.filter.clone(),
+            decoder: self.decoder.clone(),
+            sinks: self.sinks.clone(),
+            history,
+            registry,
+            last_event_at: Mutex::new(None),
+            errors: Mutex::new(Vec::new()),
+        });
+
+        let (wake_tx, wake_rx) = mpsc::sync_channel::<()>(1);
+        let mut started_reason = None;
+        let watcher = match mode {
+            WatchMode::Poll => None,
+            WatchMode::Native => Some(create_native_watcher(
+                &shared,
+                wake_tx.clone(),
+                app_handle.clone(),
+            )?),
+            // Without working notifications at all, go straight to polling
+            WatchMode::Auto => {
+                match create_native_watcher(&shared, wake_tx.clone(), app_handle.clone()) {
+                    Ok(watcher) => Some(watcher),
+                    Err(e) => {
+                        eprintln!("[chat_watcher] falling back to polling: {}", e);
+                        started_reason = Some(format!("Polling because {}", e));
+                        shared.polling.store(true, Ordering::SeqCst);
+                        None
+                    }
+                }
+            }
+        };
+
+        // Reported before the reader runs so it precedes any backfilled line
+        shared.report(
+            app_handle.as_ref(),
+            WatchHealth::Started,
+            started_reason.as_deref(),
+        );
+
+        // Single reader so lines are processed once and in file order
+        let shared_for_reader = shared.clone();
+        let app_handle_for_reader = app_handle.clone();
+        let reader = std::thread::Builder::new()
+            .name("chat-watcher-reader".into())
+            .spawn(move || run_reader(shared_for_reader, app_handle_for_reader, wake_rx, backfill))
+            .map_err(ChatWatcherError::Thread)?;
+
+        let backend = shared.backend();
+
+        // Store the running session
+        *session = Some(WatchSession {
+            app_handle,
+            watcher,
+            wake: wake_tx,
+            reader,
+            shared,
+        });
+
+        Ok(backend)
+    }
+
+    /// Stop watching the chat log file
+    pub fn stop(&self) -> Result<(), ChatWatcherError> {
+        let mut session = self
+            .session
+            .lock()
+            .map_err(|_| ChatWatcherError::LockPoisoned("chat watcher"))?;
+
+        if let Some(session) = session.take() {
+            session.shutdown("Stopped");
+        }
+        Ok(())
+    }
+
+    /// Watched paths, read offsets and the time of the last emitted event
+    pub fn status(&self) -> ChatWatcherStatus {
+        let session = match self.session.lock() {
+            Ok(session) => session,
+            Err(_) => return ChatWatcherStatus::default(),
+        };
+        let shared = match session.as_ref() {
+            Some(session) => &session.shared,
+            None => return ChatWatcherStatus::default(),
+        };
+
+        let offset_of = |tail: &Mutex<LogTail>| tail.lock().map(|t| t.offset()).unwrap_or(0);
+        ChatWatcherStatus {
+            running: true,
+            mode: Some(shared.mode),
+            backend: Some(shared.backend()),
+            chat_log_path: shared.chat_log_path.to_str().map(str::to_string),
+            chat_log_offset: offset_of(&shared.chat_tail),
+            game_log_path: shared
+                .game_log_path
+                .as_ref()
+                .and_then(|p| p.to_str())
+                .map(str::to_string),
+            game_log_offset: shared.game_tail.as_ref().map(offset_of).unwrap_or(0),
+            last_event_at: shared
+                .last_event_at
+                .lock()
+                .ok()
+                .and_then(|last| *last)
+                .and_then(unix_millis),
+            health: Some(shared.health()),
+            errors: shared
+                .errors
+                .lock()
+                .map(|errors| errors.clone())
+                .unwrap_or_default(),
+            detected_encoding: shared
+                .decoder
+                .lock()
+                .ok()
+                .and_then(|decoder| decoder.detected()),
+        }
+    }
+
+    /// Encoding the logs are read in
+    pub fn log_encoding(&self) -> LogEncoding {
+        self.decoder
+            .lock()
+            .map(|decoder| decoder.encoding())
+            .unwrap_or_default()
+    }
+
+    /// Change the log encoding; a running watch decodes its next line with it
+    pub fn set_log_encoding(&self, encoding: LogEncoding) -> Result<(), String> {
+        let mut decoder = self
+            .decoder
+            .lock()
+            .map_err(|_| "Failed to lock log decoder".to_string())?;
+        *decoder = LineDecoder::new(encoding);
+        Ok(())
+    }
+
+    /// Whisper templates currently used to recognise trade whispers
+    pub fn whisper_templates(&self) -> Vec<WhisperTemplate> {
+        self.templates
+            .read()
+            .map(|templates| templates.templates())
+            .unwrap_or_default()
+    }
+
+    /// Replace the whisper templates; a running watch picks them up with its next line
+    pub fn set_whisper_templates(&self, templates: WhisperTemplates) -> Result<(), String> {
+        let mut current = self
+            .templates
+            .write()
+            .map_err(|_| "Failed to lock whisper templates".to_string())?;
+        *current = templates;
+        Ok(())
+    }
+
+    /// Parse a whole log with the current whisper templates, e.g. an archived one
+    pub fn analyse_log(&self, text: &str) -> LogAnalysis {
+        analyse_log(text, &self.current_templates())
+    }
+
+    pub fn analyse_log_file(&self, path: &Path) -> Result<LogAnalysis, String> {
+        analyse_log_file(path, &self.current_templates(), self.log_encoding())
+    }
+
+    fn current_templates(&self) -> WhisperTemplates {
+        self.templates
+            .read()
+            .map(|templates| templates.clone())
+            .unwrap_or_default()
+    }
+
+    /// Ignore list and spam rules currently applied to incoming whispers
+    pub fn whisper_filter(&self) -> WhisperFilterConfig {
+        self.filter
+            .lock()
+            .map(|filter| filter.config().clone())
+            .unwrap_or_default()
+    }
+
+    /// Apply new filter rules; suppression counts carry over
+    pub fn set_whisper_filter(&self, filter: WhisperFilter) -> Result<(), String> {
+        let mut current = self
+            .filter
+            .lock()
+            .map_err(|_| "Failed to lock whisper filter".to_string())?;
+        current.replace(filter);
+        Ok(())
+    }
+
+    /// Lines dropped by each filter rule since the app started
+    pub fn suppressed_counts(&self) -> SuppressedCounts {
+        self.filter
+            .lock()
+            .map(|filter| filter.counts())
+            .unwrap_or_default()
+    }
+
+    /// Configured event sinks
+    pub fn event_sinks(&self) -> Vec<SinkConfig> {
+        self.sinks
+            .lock()
+            .map(|sinks| sinks.configs().to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Swap in new event sinks; the old ones drain their queues after the swap
+    pub fn set_event_sinks(&self, sinks: EventSinks) -> Result<(), String> {
+        // Dropping waits for pending webhook retries, so do it without holding the lock
+        drop(self.replace_event_sinks(sinks)?);
+        Ok(())
+    }
+
+    /// Swap in new event sinks and hand back the old ones
+    ///
+    /// Dropping the old sinks blocks until their queues drain, which with webhook retries
+    /// can take minutes; callers on the main thread should drop them elsewhere.
+    pub fn replace_event_sinks(&self, sinks: EventSinks) -> Result<EventSinks, String> {
+        let mut current = self
+            .sinks
+            .lock()
+            .map_err(|_| "Failed to lock event sinks".to_string())?;
+        Ok(std::mem::replace(&mut *current, sinks))
+    }
+
+    /// Trade conversations seen so far, most recently active first
+    pub fn trade_threads(&self) -> Vec<TradeThread> {
+        let now = unix_millis(SystemTime::now()).unwrap_or(0);
+        match self.threads.lock() {
+            Ok(mut threads) => {
+                threads.expire(now);
+                threads.threads()
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Current game session as reconstructed from pd2_game.log
+    pub fn game_session(&self) -> GameSession {
+        self.game_session
+            .lock()
+            .map(|session| session.clone())
+            .unwrap_or_default()
+    }
+}
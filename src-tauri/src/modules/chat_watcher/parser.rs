@@ -0,0 +1,341 @@
+use super::items::{resolve_item, ItemMatch};
+use super::price::{parse_price, Price};
+use super::templates::{WhisperTemplates, SHIPPED_TEMPLATES};
+use serde::Serialize;
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WhisperEvent {
+    pub is_trade: bool,
+    pub from: String,
+    pub message: String,
+    pub item_name: Option<String>,
+    pub is_join: bool,
+    pub is_incoming: bool,
+    /// Replayed from the log on watcher start rather than seen live
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_backfilled: bool,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeMessageEvent {
+    pub is_incoming: bool,
+    pub player_name: String,
+    pub account_name: Option<String>,
+    pub character_name: Option<String>,
+    pub message: String,
+    pub item_name: Option<String>,
+    /// Quantity written before the item name ("2x Frostburn")
+    pub quantity: Option<u32>,
+    /// Catalogue item `item_name` refers to, when it could be resolved
+    pub item: Option<ItemMatch>,
+    pub price: Option<String>,
+    /// Structured form of `price`, when it could be parsed
+    pub parsed_price: Option<Price>,
+    pub listing_id: Option<String>,
+    /// Name of the whisper template that recognised the message
+    pub template: String,
+    /// Replayed from the log on watcher start rather than seen live
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_backfilled: bool,
+}
+
+/// A player reference as written in the chat log: "shrackx (*shrack)" or "shrackx(shrack)"
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatPlayer {
+    pub character: String,
+    pub account: Option<String>,
+}
+
+impl ChatPlayer {
+    /// Account name if known, otherwise the character name
+    pub fn name(&self) -> &str {
+        self.account.as_deref().unwrap_or(&self.character)
+    }
+}
+
+/// A single pd2_chat.log line, classified by its channel prefix
+///
+/// Lines are written as "<channel>,<text>". Channel 2 carries whispers (and friend
+/// notifications), channel 4 carries world/system notices. Any other numeric channel
+/// is treated as regular chat in "<speaker>: <message>" form.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ChatLine {
+    WhisperIn {
+        player: ChatPlayer,
+        message: String,
+    },
+    WhisperOut {
+        player: ChatPlayer,
+        message: String,
+    },
+    Join {
+        player: ChatPlayer,
+        message: String,
+    },
+    Leave {
+        player: ChatPlayer,
+        message: String,
+    },
+    FriendOnline {
+        name: String,
+    },
+    FriendOffline {
+        name: String,
+    },
+    Party {
+        message: String,
+    },
+    System {
+        message: String,
+    },
+    Chat {
+        channel: u32,
+        speaker: String,
+        message: String,
+    },
+    Unknown {
+        raw: String,
+    },
+}
+
+const WHISPER_CHANNEL: u32 = 2;
+const NOTICE_CHANNEL: u32 = 4;
+
+/// Parse any chat log line into a typed `ChatLine`
+/// Returns None only for empty lines
+pub fn parse_chat_line(line: &str) -> Option<ChatLine> {
+    let line = line.trim_end_matches(['\r', '\n']);
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    // Split "<channel>,<text>"; anything without a numeric prefix is unknown
+    let channel = line
+        .split_once(',')
+        .and_then(|(prefix, text)| prefix.parse::<u32>().ok().map(|c| (c, text)));
+    let (channel, text) = match channel {
+        Some(c) => c,
+        None => {
+            return Some(ChatLine::Unknown {
+                raw: line.to_string(),
+            })
+        }
+    };
+
+    let parsed = match channel {
+        WHISPER_CHANNEL => parse_whisper_text(text),
+        NOTICE_CHANNEL => Some(parse_notice_text(text)),
+        _ => parse_chat_text(channel, text),
+    };
+
+    Some(parsed.unwrap_or_else(|| ChatLine::Unknown {
+        raw: line.to_string(),
+    }))
+}
+
+/// Parse the text of a channel 2 line
+/// Format: "From <character> (*<account>): <message>" or "Sent to <character> (*<account>): <message>"
+fn parse_whisper_text(text: &str) -> Option<ChatLine> {
+    if let Some(friend) = parse_friend_notice(text) {
+        return Some(friend);
+    }
+
+    let (is_incoming, after_prefix) = if let Some(rest) = text.strip_prefix("From ") {
+        (true, rest)
+    } else if let Some(rest) = text.strip_prefix("Sent to ") {
+        (false, rest)
+    } else {
+        return None;
+    };
+
+    // Find the colon that separates sender from message
+    let colon_pos = after_prefix.find(':')?;
+    let player = parse_player(&after_prefix[..colon_pos]);
+    let message = after_prefix[colon_pos + 1..].trim();
+
+    // Friend notifications are delivered as whispers from the friend's bare "*account",
+    // a sender players can't whisper as
+    if is_incoming && after_prefix[..colon_pos].trim().starts_with('*') {
+        if let Some(friend) = parse_friend_notice(message) {
+            return Some(friend);
+        }
+    }
+
+    let message = message.to_string();
+    Some(if is_incoming {
+        ChatLine::WhisperIn { player, message }
+    } else {
+        ChatLine::WhisperOut { player, message }
+    })
+}
+
+/// Parse "Your friend <name> has entered/left Project Diablo 2." when it is the whole text
+fn parse_friend_notice(text: &str) -> Option<ChatLine> {
+    let rest = text.trim().strip_prefix("Your friend ")?;
+    let rest = rest.strip_suffix('.').unwrap_or(rest);
+
+    let (name, online) = if let Some(name) = rest.strip_suffix(" has entered Project Diablo 2") {
+        (name, true)
+    } else {
+        (rest.strip_suffix(" has left Project Diablo 2")?, false)
+    };
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+
+    let name = name.to_string();
+    Some(if online {
+        ChatLine::FriendOnline { name }
+    } else {
+        ChatLine::FriendOffline { name }
+    })
+}
+
+/// Parse the text of a channel 4 line
+/// Format: "shrackx(shrack) joined our world. Diablo's minions grow stronger."
+fn parse_notice_text(text: &str) -> ChatLine {
+    let message = text.trim().to_string();
+
+    if let Some(pos) = text.find(" joined our world") {
+        return ChatLine::Join {
+            player: parse_player(&text[..pos]),
+            message,
+        };
+    }
+    if let Some(pos) = text.find(" left our world") {
+        return ChatLine::Leave {
+            player: parse_player(&text[..pos]),
+            message,
+        };
+    }
+
+    let lower = text.to_ascii_lowercase();
+    if lower.contains("party") || lower.contains("invites you to ally") {
+        return ChatLine::Party { message };
+    }
+
+    ChatLine::System { message }
+}
+
+/// Parse "<speaker>: <message>" for the remaining channels
+fn parse_chat_text(channel: u32, text: &str) -> Option<ChatLine> {
+    let (speaker, message) = text.split_once(':')?;
+    let speaker = speaker.trim();
+    if speaker.is_empty() {
+        return None;
+    }
+
+    Some(ChatLine::Chat {
+        channel,
+        speaker: speaker.to_string(),
+        message: message.trim().to_string(),
+    })
+}
+
+/// Extract character name and account name
+/// Format: "shrack (*shrack)", "DoreetDrood (*Doreets)", "shrackx(shrack)" or just "shrack"
+fn parse_player(sender_part: &str) -> ChatPlayer {
+    let sender_part = sender_part.trim();
+
+    if let Some(paren_start) = sender_part.find('(') {
+        let character = sender_part[..paren_start].trim().to_string();
+        let account = sender_part[paren_start..].find(')').and_then(|paren_end| {
+            let account_name = sender_part[paren_start + 1..paren_start + paren_end].trim();
+            // Remove "*" prefix if present
+            let account_name = account_name.strip_prefix('*').unwrap_or(account_name);
+            (!account_name.is_empty()).then(|| account_name.to_string())
+        });
+        ChatPlayer { character, account }
+    } else {
+        ChatPlayer {
+            character: sender_part
+                .split_whitespace()
+                .next()
+                .unwrap_or(sender_part)
+                .to_string(),
+            account: None,
+        }
+    }
+}
+
+/// Build the whisper event for the frontend from a parsed line
+pub fn whisper_from_chat_line(
+    chat_line: &ChatLine,
+    templates: &WhisperTemplates,
+) -> Option<WhisperEvent> {
+    match chat_line {
+        ChatLine::WhisperIn { player, message } | ChatLine::WhisperOut { player, message } => {
+            // A trade whisper starts with one of the templates
+            let trade = templates.find(message).filter(|trade| trade.at_start);
+            Some(WhisperEvent {
+                is_trade: trade.is_some(),
+                from: player.name().to_string(),
+                message: message.clone(),
+                item_name: trade.and_then(|trade| trade.item),
+                is_join: false,
+                is_incoming: matches!(chat_line, ChatLine::WhisperIn { .. }),
+                is_backfilled: false,
+            })
+        }
+        ChatLine::Join { player, message } => Some(WhisperEvent {
+            is_trade: false,
+            from: player.name().to_string(),
+            message: message.clone(),
+            item_name: None,
+            is_join: true,
+            is_incoming: true,
+            is_backfilled: false,
+        }),
+        _ => None,
+    }
+}
+
+/// Build the trade message event for the frontend from a parsed line
+pub fn trade_message_from_chat_line(
+    chat_line: &ChatLine,
+    templates: &WhisperTemplates,
+) -> Option<TradeMessageEvent> {
+    let (player, message, is_incoming) = match chat_line {
+        ChatLine::WhisperIn { player, message } => (player, message, true),
+        ChatLine::WhisperOut { player, message } => (player, message, false),
+        _ => return None,
+    };
+
+    // Any whisper containing a template match is a trade message
+    let trade = templates.find(message)?;
+    let item_name = trade.item;
+    let price = trade.price;
+    Some(TradeMessageEvent {
+        is_incoming,
+        player_name: player.name().to_string(),
+        account_name: player.account.clone(),
+        character_name: Some(player.character.clone()),
+        message: message.clone(),
+        item: item_name.as_deref().and_then(resolve_item),
+        item_name,
+        quantity: trade.quantity,
+        parsed_price: price.as_deref().and_then(parse_price),
+        price,
+        listing_id: trade.listing_id,
+        template: trade.template,
+        is_backfilled: false,
+    })
+}
+
+/// Parse a whisper from a log line using the shipped whisper templates
+/// Format: "2,From <character> (*<account>): Hi, I'm interested in your Frostburn listed for 2 wss"
+/// Format: "4,<character>(<account>) joined our world. Diablo's minions grow stronger."
+pub fn parse_whisper(line: &str) -> Option<WhisperEvent> {
+    whisper_from_chat_line(&parse_chat_line(line)?, &SHIPPED_TEMPLATES)
+}
+
+/// Parse a trade message from a log line using the shipped whisper templates
+/// Incoming format: "2,From shrack (*shrack): Hi, I'm interested in your Frostburn listed for 2 wss"
+/// Outgoing format: "2,Sent to Shrackb (*shrack): Hi, I'm interested in your Frostburn listed for 2 wss"
+pub fn parse_trade_message(line: &str) -> Option<TradeMessageEvent> {
+    trade_message_from_chat_line(&parse_chat_line(line)?, &SHIPPED_TEMPLATES)
+}
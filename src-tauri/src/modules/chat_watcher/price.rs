@@ -0,0 +1,225 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+/// Rune names in ascending value order, as used in trade prices
+pub const RUNES: [&str; 33] = [
+    "el", "eld", "tir", "nef", "eth", "ith", "tal", "ral", "ort", "thul", "amn", "sol", "shael",
+    "dol", "hel", "io", "lum", "ko", "fal", "lem", "pul", "um", "mal", "ist", "gul", "vex", "ohm",
+    "lo", "sur", "ber", "jah", "cham", "zod",
+];
+
+/// "offer", "offers", "make an offer" or "c/o" as whole words; a leading "no" is captured
+/// so "no offers" is stripped without counting
+static OFFER_MARKER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\bno\s+)?\b(?:make\s+(?:an\s+)?offers?|offers?|c/o)\b").unwrap());
+
+/// "obo", "o.b.o" or "or best offer" as whole words, so "hobo" or "obol" don't count
+static OBO_MARKER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(?:or\s+best\s+offer\b|o\.b\.o\b\.?|obo\b)").unwrap());
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CurrencyKind {
+    Rune,
+    /// Worldstone Shards ("wss")
+    Shard,
+    /// Generic "hr" (high rune) / "mr" (mid rune) units
+    RuneClass,
+    /// Anything we don't recognise, kept verbatim
+    Other,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceComponent {
+    pub amount: f64,
+    /// Canonical currency key: a rune name ("ber"), "wss", "hr", "mr", or the original text
+    pub currency: String,
+    pub kind: CurrencyKind,
+    /// Any rune of at least this value, written "pul+" or "um-level"
+    pub level: bool,
+}
+
+/// A trade price broken into comparable parts, e.g. "1 ber + 1 jah" or "0.5 hr obo"
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Price {
+    pub components: Vec<PriceComponent>,
+    /// "obo" / "or best offer"
+    pub is_obo: bool,
+    /// "offer", "c/o", "make offer": the seller takes offers instead of (or besides) a fixed price
+    pub is_offer: bool,
+}
+
+impl Price {
+    /// Total amount of one currency across all components
+    pub fn amount_of(&self, currency: &str) -> f64 {
+        self.components
+            .iter()
+            .filter(|component| component.currency == currency)
+            .map(|component| component.amount)
+            .sum()
+    }
+}
+
+/// Parse a price string such as "2 wss", "1 ber + 1 jah", "0.5 hr", "pul+ obo" or "3 ists, 1 gul"
+/// Returns None when the text is empty or contains neither a currency nor an offer marker
+pub fn parse_price(text: &str) -> Option<Price> {
+    let text = text.trim().trim_end_matches(['.', '!']).to_lowercase();
+
+    let is_obo = OBO_MARKER.is_match(&text);
+    let text = OBO_MARKER.replace_all(&text, " ");
+
+    let is_offer = OFFER_MARKER
+        .captures_iter(&text)
+        .any(|captures| captures.get(1).is_none());
+    let text = OFFER_MARKER.replace_all(&text, " ");
+
+    // "1 ber or offer" leaves a dangling "or" behind
+    let text = text.trim_end().trim_end_matches(" or");
+
+    let components: Vec<PriceComponent> = split_components(text)
+        .iter()
+        .filter_map(|part| parse_component(part))
+        .collect();
+
+    if components.is_empty() && !is_offer && !is_obo {
+        return None;
+    }
+
+    Some(Price {
+        components,
+        is_obo,
+        is_offer,
+    })
+}
+
+/// Split on "+", ",", "&" and "and"; a "+" glued to a word ("pul+") is a level marker instead
+fn split_components(text: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let chars: Vec<char> = text.chars().collect();
+
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            '+' => {
+                let glued_to_word = i > 0 && chars[i - 1].is_alphabetic();
+                let rest: String = chars[i + 1..].iter().collect();
+                let rest = rest.trim_start();
+                let starts_component = rest
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_digit() || c == '.')
+                    || starts_with_currency(rest);
+                if glued_to_word && !starts_component {
+                    current.push('+');
+                } else {
+                    parts.push(std::mem::take(&mut current));
+                }
+            }
+            ',' | '&' => parts.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    parts.push(current);
+
+    parts
+        .iter()
+        .flat_map(|part| {
+            part.split(" and ")
+                .map(|p| p.trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+fn starts_with_currency(text: &str) -> bool {
+    let word: String = text.chars().take_while(|c| c.is_alphabetic()).collect();
+    !word.is_empty() && normalize_currency(&word).1 != CurrencyKind::Other
+}
+
+/// Parse "2 wss", "2wss", "0.5 hr", "1/2 ber", "jah", "pul+", "um-level", "3x ist"
+fn parse_component(part: &str) -> Option<PriceComponent> {
+    let part = part.trim();
+    let number_end = part
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '/'))
+        .unwrap_or(part.len());
+    let (number, rest) = part.split_at(number_end);
+    let amount = if number.is_empty() {
+        1.0
+    } else {
+        parse_amount(number)?
+    };
+
+    let mut rest = rest.trim();
+    if !number.is_empty() {
+        // "3x ist"
+        rest = rest.trim_start_matches('x').trim();
+    }
+    let mut level = false;
+    for marker in ["-level", " level", "+"] {
+        if let Some(stripped) = rest.strip_suffix(marker) {
+            rest = stripped.trim();
+            level = true;
+        }
+    }
+    if rest.is_empty() {
+        return None;
+    }
+
+    let (currency, kind) = normalize_currency(rest);
+    Some(PriceComponent {
+        amount,
+        currency,
+        kind,
+        level,
+    })
+}
+
+/// "2", "0.5", ".5" or "1/2"
+fn parse_amount(number: &str) -> Option<f64> {
+    if let Some((numerator, denominator)) = number.split_once('/') {
+        let numerator: f64 = numerator.parse().ok()?;
+        let denominator: f64 = denominator.parse().ok()?;
+        return (denominator != 0.0).then(|| numerator / denominator);
+    }
+    number.parse().ok()
+}
+
+/// Map spellings like "Bers", "ber rune", "worldstone shards", "hrs" to a canonical key
+fn normalize_currency(text: &str) -> (String, CurrencyKind) {
+    let full = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match full.as_str() {
+        "wss" | "ws" | "worldstone shard" | "worldstone shards" | "worldstone" | "shard"
+        | "shards" => return ("wss".to_string(), CurrencyKind::Shard),
+        "hr" | "hrs" | "high rune" | "high runes" => {
+            return ("hr".to_string(), CurrencyKind::RuneClass)
+        }
+        "mr" | "mrs" | "mid rune" | "mid runes" => {
+            return ("mr".to_string(), CurrencyKind::RuneClass)
+        }
+        _ => {}
+    }
+
+    // "ber rune", "Ber Runes"
+    let words: Vec<&str> = full
+        .split(' ')
+        .filter(|word| !matches!(*word, "rune" | "runes"))
+        .collect();
+
+    if let [word] = words.as_slice() {
+        let singular = word
+            .strip_suffix("'s")
+            .or_else(|| word.strip_suffix('s'))
+            .unwrap_or(word);
+        for candidate in [*word, singular] {
+            if RUNES.contains(&candidate) {
+                return (candidate.to_string(), CurrencyKind::Rune);
+            }
+        }
+    }
+
+    (full, CurrencyKind::Other)
+}
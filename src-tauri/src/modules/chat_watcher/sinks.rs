@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Events queued per sink before new ones are dropped, so a dead webhook can't stall the watcher
+const SINK_QUEUE_LEN: usize = 256;
+
+/// One event as handed to a sink; written as a single JSON object
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SinkEvent {
+    /// Same name the frontend receives, e.g. "trade-message"
+    pub event: String,
+    /// Unix time in milliseconds when the line was processed
+    pub timestamp: u64,
+    pub payload: serde_json::Value,
+}
+
+/// A destination for parsed chat events besides the frontend
+///
+/// Each sink runs on its own thread, so `send` may block (e.g. while retrying).
+pub trait EventSink: Send {
+    fn send(&mut self, event: &SinkEvent) -> Result<(), String>;
+}
+
+/// How the webhook sink retries failed requests
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryPolicy {
+    /// Attempts after the first one; 0 never retries
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for every retry after it
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Per-request timeout
+    pub timeout_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            timeout_ms: 5_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Wait before retry number `retry` (starting at 1)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 1u64 << retry.saturating_sub(1).min(32);
+        Duration::from_millis(
+            self.initial_backoff_ms
+                .saturating_mul(factor)
+                .min(self.max_backoff_ms),
+        )
+    }
+}
+
+/// Where a configured sink sends its events
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(
+    tag = "kind",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum SinkKind {
+    /// POST each event as JSON to a URL
+    Webhook {
+        url: String,
+        #[serde(default)]
+        retry: RetryPolicy,
+    },
+    /// Append each event to a JSON Lines file
+    File { path: PathBuf },
+    /// Print each event to stdout as JSON Lines
+    Stdout,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SinkConfig {
+    #[serde(flatten)]
+    pub kind: SinkKind,
+    /// Event names forwarded to this sink; empty forwards every event
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+/// Sends events to an HTTP endpoint, retrying server errors and connection failures
+pub struct WebhookSink {
+    url: String,
+    retry: RetryPolicy,
+    agent: ureq::Agent,
+}
+
+impl WebhookSink {
+    pub fn new(url: &str, retry: RetryPolicy) -> Result<Self, String> {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(format!("Invalid webhook URL: {}", url));
+        }
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_millis(retry.timeout_ms))
+            .build();
+        Ok(Self {
+            url: url.to_string(),
+            retry,
+            agent,
+        })
+    }
+
+    fn post(&self, body: &str) -> Result<(), (bool, String)> {
+        match self
+            .agent
+            .post(&self.url)
+            .set("Content-Type", "application/json")
+            .send_string(body)
+        {
+            Ok(_) => Ok(()),
+            // Rate limiting and server errors may clear up; anything else won't
+            Err(ureq::Error::Status(code, _)) => Err((
+                code == 429 || code >= 500,
+                format!("Webhook {} returned {}", self.url, code),
+            )),
+            Err(ureq::Error::Transport(e)) => {
+                Err((true, format!("Webhook {} failed: {}", self.url, e)))
+            }
+        }
+    }
+}
+
+impl EventSink for WebhookSink {
+    fn send(&mut self, event: &SinkEvent) -> Result<(), String> {
+        let body = serde_json::to_string(event)
+            .map_err(|e| format!("Failed to serialize event: {}", e))?;
+
+        let mut retry = 0;
+        loop {
+            match self.post(&body) {
+                Ok(()) => return Ok(()),
+                Err((true, _)) if retry < self.retry.max_retries => {
+                    retry += 1;
+                    thread::sleep(self.retry.backoff(retry));
+                }
+                Err((_, e)) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Appends events to a JSON Lines file
+pub struct FileSink {
+    file: File,
+}
+
+impl FileSink {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create event log directory: {}", e))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open event log {}: {}", path.display(), e))?;
+        Ok(Self { file })
+    }
+}
+
+impl EventSink for FileSink {
+    fn send(&mut self, event: &SinkEvent) -> Result<(), String> {
+        write_json_line(&mut self.file, event)
+            .map_err(|e| format!("Failed to write event log: {}", e))
+    }
+}
+
+/// Prints events to stdout, one JSON object per line, for scripting
+pub struct StdoutSink;
+
+impl EventSink for StdoutSink {
+    fn send(&mut self, event: &SinkEvent) -> Result<(), String> {
+        write_json_line(&mut std::io::stdout().lock(), event)
+            .map_err(|e| format!("Failed to write event to stdout: {}", e))
+    }
+}
+
+fn write_json_line(out: &mut impl Write, event: &SinkEvent) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(event)?;
+    line.push(b'\n');
+    out.write_all(&line)?;
+    out.flush()
+}
+
+/// Build the sink a configuration describes; files are opened right away so errors surface early
+pub fn open_sink(kind: &SinkKind) -> Result<Box<dyn EventSink>, String> {
+    Ok(match kind {
+        SinkKind::Webhook { url, retry } => Box::new(WebhookSink::new(url, retry.clone())?),
+        SinkKind::File { path } => Box::new(FileSink::open(path)?),
+        SinkKind::Stdout => Box::new(StdoutSink),
+    })
+}
+
+/// A sink and the thread feeding it
+struct SinkWorker {
+    events: Vec<String>,
+    queue: Option<SyncSender<SinkEvent>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SinkWorker {
+    fn spawn(mut sink: Box<dyn EventSink>, events: Vec<String>) -> Self {
+        let (queue, rx) = mpsc::sync_channel::<SinkEvent>(SINK_QUEUE_LEN);
+        let thread = thread::spawn(move || {
+            for event in rx {
+                if let Err(e) = sink.send(&event) {
+                    eprintln!("[chat_watcher] {}", e);
+                }
+            }
+        });
+        Self {
+            events,
+            queue: Some(queue),
+            thread: Some(thread),
+        }
+    }
+
+    fn wants(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|name| name == event)
+    }
+}
+
+impl Drop for SinkWorker {
+    /// Let the sink finish what is queued, then stop its thread
+    fn drop(&mut self) {
+        self.queue.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Sinks parsed chat events are forwarded to, alongside the frontend
+#[derive(Default)]
+pub struct EventSinks {
+    configs: Vec<SinkConfig>,
+    workers: Vec<SinkWorker>,
+}
+
+impl EventSinks {
+    /// Open every configured sink, failing if any of them can't be opened
+    pub fn new(configs: Vec<SinkConfig>) -> Result<Self, String> {
+        let sinks = configs
+            .iter()
+            .map(|config| open_sink(&config.kind))
+            .collect::<Result<Vec<_>, String>>()?;
+        let workers = sinks
+            .into_iter()
+            .zip(&configs)
+            .map(|(sink, config)| SinkWorker::spawn(sink, config.events.clone()))
+            .collect();
+        Ok(Self { configs, workers })
+    }
+
+    /// Add a sink that isn't described by a configuration
+    pub fn add(&mut self, sink: Box<dyn EventSink>, events: Vec<String>) {
+        self.workers.push(SinkWorker::spawn(sink, events));
+    }
+
+    /// Sink configurations saved at `path`, or none if nothing has been saved yet
+    pub fn load_config(path: &Path) -> Result<Vec<SinkConfig>, String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("Failed to parse event sinks: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(format!("Failed to read event sinks: {}", e)),
+        }
+    }
+
+    pub fn save_config(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let contents = serde_json::to_string_pretty(&self.configs)
+            .map_err(|e| format!("Failed to serialize event sinks: {}", e))?;
+        fs::write(path, contents).map_err(|e| format!("Failed to save event sinks: {}", e))
+    }
+
+    pub fn configs(&self) -> &[SinkConfig] {
+        &self.configs
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.workers.is_empty()
+    }
+
+    /// Queue an event for every sink that wants it; never blocks on a slow sink
+    pub fn dispatch(&self, event: &str, payload: &impl Serialize, timestamp: u64) {
+        let mut wanted = self.workers.iter().filter(|worker| worker.wants(event));
+        let first = match wanted.next() {
+            Some(worker) => worker,
+            None => return,
+        };
+        let payload = match serde_json::to_value(payload) {
+            Ok(payload) => payload,
+            Err(e) => {
+                eprintln!("[chat_watcher] Failed to serialize {}: {}", event, e);
+                return;
+            }
+        };
+        let event = SinkEvent {
+            event: event.to_string(),
+            timestamp,
+            payload,
+        };
+
+        for worker in std::iter::once(first).chain(wanted) {
+            let queue = match &worker.queue {
+                Some(queue) => queue,
+                None => continue,
+            };
+            if let Err(TrySendError::Full(event)) = queue.try_send(event.clone()) {
+                eprintln!(
+                    "[chat_watcher] Event sink is backed up, dropped {}",
+                    event.event
+                );
+            }
+        }
+    }
+}
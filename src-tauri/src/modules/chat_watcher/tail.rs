@@ -0,0 +1,167 @@
+use std::collections::VecDeque;
+use std::fs::{self, Metadata};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Identifies a concrete file behind a path so a delete+recreate can be told apart from appends
+/// (device and inode plus creation time on Unix, volume and file index on Windows)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FileIdentity {
+    file_id: Option<(u64, u64)>,
+    created: Option<SystemTime>,
+}
+
+impl FileIdentity {
+    #[cfg(unix)]
+    fn of(_path: &Path, metadata: &Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        Self {
+            file_id: Some((metadata.dev(), metadata.ino())),
+            created: metadata.created().ok(),
+        }
+    }
+
+    /// NTFS tunnelling gives a file recreated under the same name the old creation time,
+    /// so only the file index tells the two apart
+    #[cfg(windows)]
+    fn of(path: &Path, _metadata: &Metadata) -> Self {
+        use std::os::windows::io::AsRawHandle;
+        use windows_sys::Win32::Storage::FileSystem::{
+            GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION,
+        };
+
+        let file_id = fs::File::open(path).ok().and_then(|file| {
+            let mut info: BY_HANDLE_FILE_INFORMATION = unsafe { std::mem::zeroed() };
+            let ok = unsafe { GetFileInformationByHandle(file.as_raw_handle() as _, &mut info) };
+            (ok != 0).then(|| {
+                let index = (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow);
+                (u64::from(info.dwVolumeSerialNumber), index)
+            })
+        });
+        Self {
+            file_id,
+            created: None,
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn of(_path: &Path, metadata: &Metadata) -> Self {
+        Self {
+            file_id: None,
+            created: metadata.created().ok(),
+        }
+    }
+}
+
+/// Follows a log file by byte offset, surviving truncation and file replacement
+pub struct LogTail {
+    path: PathBuf,
+    offset: u64,
+    identity: Option<FileIdentity>,
+}
+
+impl LogTail {
+    /// Start reading from the beginning of the file
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            offset: 0,
+            identity: None,
+        }
+    }
+
+    /// Start reading from the current end of the file, skipping existing content
+    pub fn at_end(path: PathBuf) -> Self {
+        let mut tail = Self::new(path);
+        if let Ok(metadata) = fs::metadata(&tail.path) {
+            tail.offset = metadata.len();
+            tail.identity = Some(FileIdentity::of(&tail.path, &metadata));
+        }
+        tail
+    }
+
+    /// Byte offset up to which the file has been read
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Whether the file has grown, shrunk or been replaced since the last read
+    pub fn has_unread_data(&self) -> bool {
+        match fs::metadata(&self.path) {
+            Ok(metadata) => {
+                metadata.len() != self.offset
+                    || self.identity != Some(FileIdentity::of(&self.path, &metadata))
+            }
+            Err(_e) => self.identity.is_some(),
+        }
+    }
+
+    /// The last `max` complete lines before the current offset, oldest first
+    pub fn read_last_lines(&self, max: usize) -> io::Result<Vec<Vec<u8>>> {
+        let mut lines = VecDeque::with_capacity(max.min(1024));
+        if max == 0 || self.offset == 0 {
+            return Ok(Vec::new());
+        }
+
+        let reader = BufReader::new(fs::File::open(&self.path)?);
+        let mut reader = reader.take(self.offset);
+        let mut line = Vec::new();
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            if lines.len() == max {
+                lines.pop_front();
+            }
+            lines.push_back(std::mem::take(&mut line));
+        }
+
+        Ok(lines.into())
+    }
+
+    /// Read every complete line appended since the last call
+    ///
+    /// A different file behind the path (game recreated the log) or a file shorter than
+    /// the current offset (truncated) restarts reading at 0 so nothing written into the
+    /// fresh file is lost. A trailing line without its newline is left for the next call.
+    pub fn read_lines(&mut self) -> io::Result<Vec<Vec<u8>>> {
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                // File removed; the next Create event starts over from 0
+                self.offset = 0;
+                self.identity = None;
+                return Ok(Vec::new());
+            }
+            Err(e) => return Err(e),
+        };
+
+        let identity = FileIdentity::of(&self.path, &metadata);
+        let replaced = self.identity.is_some_and(|known| known != identity);
+        let truncated = metadata.len() < self.offset;
+
+        if replaced || truncated {
+            self.offset = 0;
+        }
+        self.identity = Some(identity);
+
+        if metadata.len() == self.offset {
+            return Ok(Vec::new());
+        }
+
+        let mut reader = BufReader::new(fs::File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(self.offset))?;
+
+        let mut lines = Vec::new();
+        let mut line = Vec::new();
+        loop {
+            let read = reader.read_until(b'\n', &mut line)?;
+            if read == 0 || !line.ends_with(b"\n") {
+                // EOF, possibly in the middle of a line still being written
+                break;
+            }
+            self.offset += read as u64;
+            lines.push(std::mem::take(&mut line));
+        }
+
+        Ok(lines)
+    }
+}
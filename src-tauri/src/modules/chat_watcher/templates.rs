@@ -0,0 +1,140 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// A whisper wording that marks a trade request
+///
+/// `pattern` is a regex searched anywhere in the whisper text. Its named captures
+/// `item`, `price`, `listing_id` and `quantity` are all optional.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct WhisperTemplate {
+    pub name: String,
+    pub pattern: String,
+}
+
+/// The trade details a template pulled out of a whisper
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TemplateMatch {
+    pub template: String,
+    /// The match begins the whisper, rather than following other text
+    pub at_start: bool,
+    pub item: Option<String>,
+    pub price: Option<String>,
+    pub listing_id: Option<String>,
+    pub quantity: Option<u32>,
+}
+
+/// Trade whisper templates in priority order, compiled
+#[derive(Clone, Debug)]
+pub struct WhisperTemplates {
+    templates: Vec<(WhisperTemplate, Regex)>,
+}
+
+/// Templates shipped with the app, used until the user saves their own
+pub static SHIPPED_TEMPLATES: Lazy<WhisperTemplates> = Lazy::new(|| {
+    WhisperTemplates::new(shipped_templates()).expect("shipped whisper templates compile")
+});
+
+fn shipped_templates() -> Vec<WhisperTemplate> {
+    let template = |name: &str, pattern: &str| WhisperTemplate {
+        name: name.to_string(),
+        pattern: pattern.to_string(),
+    };
+    vec![
+        // "Hi, I'm interested in your 2x Frostburn listed for 2 wss. (listing 65f1c0)"
+        // The price runs up to the first sentence-ending period; "0.5 hr" keeps its decimal point
+        template(
+            "pd2-trade",
+            r"Hi, I'm interested in your (?:(?:(?P<quantity>\d+)x )?(?P<item>.+?) listed for (?P<price>(?:[^.(]|\.\d)*)(?:.*?\(listing #?(?P<listing_id>[\w-]+)\))?)?",
+        ),
+        // "I'd like to offer 1 ber for your Harlequin Crest", "I can offer 2 ist for your Shako"
+        template(
+            "offer",
+            r"(?i)^(?:hi,? |hey,? )?I(?:'d like to| would like to| can) offer (?P<price>.+?) for your (?:(?P<quantity>\d+)x )?(?P<item>.+?)[.!?]?$",
+        ),
+        // "WTB your Harlequin Crest for 1 ber", "wtb 3x Key of Terror @ 1 ist"
+        template(
+            "wtb",
+            r"(?i)^wtb:? (?:your )?(?:(?P<quantity>\d+)x )?(?P<item>.+?) (?:for|@) (?P<price>.+?)[.!?]?$",
+        ),
+    ]
+}
+
+impl Default for WhisperTemplates {
+    fn default() -> Self {
+        SHIPPED_TEMPLATES.clone()
+    }
+}
+
+impl WhisperTemplates {
+    /// Compile templates, rejecting the set if any pattern is invalid
+    pub fn new(templates: Vec<WhisperTemplate>) -> Result<Self, String> {
+        let templates = templates
+            .into_iter()
+            .map(|template| {
+                let regex = Regex::new(&template.pattern).map_err(|e| {
+                    format!(
+                        "Invalid pattern for whisper template {}: {}",
+                        template.name, e
+                    )
+                })?;
+                Ok((template, regex))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { templates })
+    }
+
+    /// Templates saved at `path`, or the shipped ones if nothing has been saved yet
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read whisper templates: {}", e)),
+        };
+        let templates: Vec<WhisperTemplate> = serde_json::from_str(&contents)
+            .map_err(|e| format!("Failed to parse whisper templates: {}", e))?;
+        Self::new(templates)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let contents = serde_json::to_string_pretty(&self.templates())
+            .map_err(|e| format!("Failed to serialize whisper templates: {}", e))?;
+        fs::write(path, contents).map_err(|e| format!("Failed to save whisper templates: {}", e))
+    }
+
+    pub fn templates(&self) -> Vec<WhisperTemplate> {
+        self.templates
+            .iter()
+            .map(|(template, _)| template.clone())
+            .collect()
+    }
+
+    /// Match a whisper against the templates; the first template that matches wins
+    pub fn find(&self, message: &str) -> Option<TemplateMatch> {
+        self.templates.iter().find_map(|(template, regex)| {
+            let captures = regex.captures(message)?;
+            let capture = |name: &str| {
+                captures
+                    .name(name)
+                    .map(|m| m.as_str().trim())
+                    .filter(|text| !text.is_empty())
+                    .map(str::to_string)
+            };
+            Some(TemplateMatch {
+                template: template.name.clone(),
+                at_start: captures.get(0).is_some_and(|m| m.start() == 0),
+                item: capture("item"),
+                price: capture("price"),
+                listing_id: capture("listing_id"),
+                quantity: capture("quantity").and_then(|quantity| quantity.parse().ok()),
+            })
+        })
+    }
+}
@@ -0,0 +1,301 @@
+use super::parser::{ChatLine, ChatPlayer, TradeMessageEvent};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+/// A thread with no activity for this long is marked stale
+pub const STALE_AFTER_MS: u64 = 30 * 60 * 1000;
+/// Closed (completed or stale) threads kept for `get_trade_threads`
+const MAX_CLOSED_THREADS: usize = 100;
+/// Messages kept per thread; older ones are dropped first
+const MAX_THREAD_MESSAGES: usize = 50;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ThreadState {
+    /// Trade whisper seen, no reply yet
+    New,
+    /// We answered the trader
+    Replied,
+    /// We sent game info or an invite, or the trader joined our game
+    Invited,
+    /// The trader left our game after joining, or we closed the offer
+    Completed,
+    /// No activity for `STALE_AFTER_MS`
+    Stale,
+}
+
+impl ThreadState {
+    fn is_open(self) -> bool {
+        !matches!(self, ThreadState::Completed | ThreadState::Stale)
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadMessage {
+    pub is_incoming: bool,
+    pub message: String,
+    pub is_trade: bool,
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+}
+
+/// One trade conversation with a single trader
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeThread {
+    pub id: u64,
+    /// Account name, or the character name when the account is unknown
+    pub player_name: String,
+    pub account_name: Option<String>,
+    pub character_name: String,
+    /// The trader whispered us (they buy); false when we whispered them
+    pub is_incoming: bool,
+    pub item_name: Option<String>,
+    pub price: Option<String>,
+    pub state: ThreadState,
+    /// Unix time in milliseconds of the opening trade whisper
+    pub started_at: u64,
+    /// Unix time in milliseconds of the latest message or join/leave
+    pub last_activity: u64,
+    pub messages: Vec<ThreadMessage>,
+}
+
+/// Trade conversations built from the chat log, keyed by account (falling back to character)
+#[derive(Default)]
+pub struct TradeThreads {
+    threads: Vec<TradeThread>,
+    next_id: u64,
+}
+
+/// Lowercased account name, or character name when there is no account
+fn thread_key(player: &ChatPlayer) -> String {
+    player.name().to_lowercase()
+}
+
+/// "inv", "invite", "join", "game: name" or a "name//password" game, as whole words
+/// so "inventory", "joined" or a URL don't count
+static INVITE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\binv(?:ite)?\b|\bjoin\b|\bgame\s*:\s*\S+|(?:^|\s)[^\s:/]+//").unwrap()
+});
+
+/// Outgoing text that hands the trader a game to join
+fn is_invite(message: &str) -> bool {
+    INVITE.is_match(message)
+}
+
+/// "sold", "rejected", "declined" or a thanks for the trade, as whole words so "soldier"
+/// or "undeclined" don't count
+static CLOSING: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:sold|rejected|declined|(?:thanks|ty) for the trade)\b").unwrap()
+});
+
+/// Outgoing text that closes the offer either way
+fn is_closing(message: &str) -> bool {
+    CLOSING.is_match(message)
+}
+
+impl TradeThreads {
+    /// Fold a chat line into the threads, returning every thread it changed
+    ///
+    /// `trade` is the trade message parsed from the same line, if it is one. Threads that
+    /// went stale by `now` are returned as well.
+    pub fn apply(
+        &mut self,
+        chat_line: &ChatLine,
+        trade: Option<&TradeMessageEvent>,
+        now: u64,
+    ) -> Vec<TradeThread> {
+        let mut changed = self.expire(now);
+
+        let updated = match (chat_line, trade) {
+            (
+                ChatLine::WhisperIn { player, message } | ChatLine::WhisperOut { player, message },
+                Some(trade),
+            ) => Some(self.open(player, message, trade, now)),
+            (ChatLine::WhisperIn { player, message }, None) => {
+                self.follow_up(player, true, message, now)
+            }
+            (ChatLine::WhisperOut { player, message }, None) => {
+                self.follow_up(player, false, message, now)
+            }
+            (ChatLine::Join { player, .. }, _) => self.presence(player, true, now),
+            (ChatLine::Leave { player, .. }, _) => self.presence(player, false, now),
+            _ => None,
+        };
+
+        if let Some(index) = updated {
+            let thread = self.threads[index].clone();
+            changed.retain(|t| t.id != thread.id);
+            changed.push(thread);
+        }
+        self.prune();
+        changed
+    }
+
+    /// Mark threads without recent activity as stale, returning them
+    pub fn expire(&mut self, now: u64) -> Vec<TradeThread> {
+        self.threads
+            .iter_mut()
+            .filter(|thread| {
+                thread.state.is_open() && now.saturating_sub(thread.last_activity) >= STALE_AFTER_MS
+            })
+            .map(|thread| {
+                thread.state = ThreadState::Stale;
+                thread.clone()
+            })
+            .collect()
+    }
+
+    /// All known threads, most recently active first
+    pub fn threads(&self) -> Vec<TradeThread> {
+        let mut threads = self.threads.clone();
+        threads.sort_by_key(|thread| std::cmp::Reverse(thread.last_activity));
+        threads
+    }
+
+    /// Start a thread for a trade whisper in either direction
+    ///
+    /// A repeat of the whisper for the same item joins the trader's open thread for it instead.
+    fn open(
+        &mut self,
+        player: &ChatPlayer,
+        message: &str,
+        trade: &TradeMessageEvent,
+        now: u64,
+    ) -> usize {
+        let message = ThreadMessage {
+            is_incoming: trade.is_incoming,
+            message: message.to_string(),
+            is_trade: true,
+            timestamp: now,
+        };
+
+        if let Some(index) = self.open_for_item(player, trade) {
+            let thread = &mut self.threads[index];
+            thread.character_name = player.character.clone();
+            thread.price = trade.price.clone().or(thread.price.take());
+            thread.last_activity = now;
+            thread.messages.push(message);
+            if thread.messages.len() > MAX_THREAD_MESSAGES {
+                thread.messages.remove(0);
+            }
+            return index;
+        }
+
+        self.next_id += 1;
+        self.threads.push(TradeThread {
+            id: self.next_id,
+            player_name: player.name().to_string(),
+            account_name: player.account.clone(),
+            character_name: player.character.clone(),
+            is_incoming: trade.is_incoming,
+            item_name: trade.item_name.clone(),
+            price: trade.price.clone(),
+            state: ThreadState::New,
+            started_at: now,
+            last_activity: now,
+            messages: vec![message],
+        });
+        self.threads.len() - 1
+    }
+
+    /// Attach a non-trade whisper to the trader's most recent open thread
+    fn follow_up(
+        &mut self,
+        player: &ChatPlayer,
+        is_incoming: bool,
+        message: &str,
+        now: u64,
+    ) -> Option<usize> {
+        let index = self.latest_open(player)?;
+        let thread = &mut self.threads[index];
+
+        // Buyers often whisper from an alt of the same account
+        thread.character_name = player.character.clone();
+        thread.last_activity = now;
+        thread.messages.push(ThreadMessage {
+            is_incoming,
+            message: message.to_string(),
+            is_trade: false,
+            timestamp: now,
+        });
+        if thread.messages.len() > MAX_THREAD_MESSAGES {
+            thread.messages.remove(0);
+        }
+
+        if !is_incoming {
+            thread.state = if is_closing(message) {
+                ThreadState::Completed
+            } else if is_invite(message) {
+                ThreadState::Invited
+            } else if thread.state == ThreadState::New {
+                ThreadState::Replied
+            } else {
+                thread.state
+            };
+        }
+        Some(index)
+    }
+
+    /// A trader joining our game means the invite worked; leaving afterwards ends the trade
+    fn presence(&mut self, player: &ChatPlayer, joined: bool, now: u64) -> Option<usize> {
+        let index = self.latest_open(player)?;
+        let thread = &mut self.threads[index];
+        thread.last_activity = now;
+        if joined {
+            thread.state = ThreadState::Invited;
+        } else if thread.state == ThreadState::Invited {
+            thread.state = ThreadState::Completed;
+        }
+        Some(index)
+    }
+
+    /// The trader's open thread with the most recent activity
+    fn latest_open(&self, player: &ChatPlayer) -> Option<usize> {
+        let key = thread_key(player);
+        self.threads
+            .iter()
+            .enumerate()
+            .filter(|(_, thread)| {
+                thread.state.is_open() && thread.player_name.to_lowercase() == key
+            })
+            .max_by_key(|(index, thread)| (thread.last_activity, *index))
+            .map(|(index, _)| index)
+    }
+
+    /// The trader's open thread about the same item in the same direction
+    fn open_for_item(&self, player: &ChatPlayer, trade: &TradeMessageEvent) -> Option<usize> {
+        let key = thread_key(player);
+        let item = trade.item_name.as_deref()?.to_lowercase();
+        self.threads.iter().rposition(|thread| {
+            thread.state.is_open()
+                && thread.is_incoming == trade.is_incoming
+                && thread.player_name.to_lowercase() == key
+                && thread
+                    .item_name
+                    .as_deref()
+                    .map(str::to_lowercase)
+                    .as_deref()
+                    == Some(item.as_str())
+        })
+    }
+
+    /// Drop the oldest closed threads beyond the cap; open threads are always kept
+    fn prune(&mut self) {
+        let closed = self
+            .threads
+            .iter()
+            .filter(|thread| !thread.state.is_open())
+            .count();
+        let mut excess = closed.saturating_sub(MAX_CLOSED_THREADS);
+        self.threads.retain(|thread| {
+            if excess > 0 && !thread.state.is_open() {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+    }
+}
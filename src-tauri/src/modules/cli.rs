@@ -0,0 +1,363 @@
+//! Command line mode: runs the chat watcher without the overlay, or parses a saved log
+
+use crate::chat_watcher::sinks::StdoutSink;
+use crate::chat_watcher::templates::SHIPPED_TEMPLATES;
+use crate::chat_watcher::{
+    analyse_log_bytes, analyse_log_file, BackfillOptions, ChatWatcher, EventSink, EventSinks,
+    LogAnalysis, LogEncoding, LogStats, SinkEvent, WatchMode, WhisperTemplates,
+};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "Usage:
+  pd2-trader-cli watch [options]   Follow the chat and game logs and print every parsed event
+  pd2-trader-cli parse <file|-> [options]
+                                   Parse a saved chat log (\"-\" reads stdin) and print its
+                                   events and trade statistics
+
+Watch options:
+  --d2-dir <path>      Diablo II directory (auto-detected when omitted)
+  --format <format>    json (default) or text
+  --mode <mode>        auto (default), native or poll
+  --backfill <lines>   Replay the last <lines> of the chat log first
+  --events <names>     Comma-separated event names to print (default: all)
+  --encoding <name>    Log encoding: auto (default), utf8, windows1250, windows1251
+                       or windows1252
+
+Parse options:
+  --format <format>    json (default) or text
+  --stats-only         Leave the per-line events out
+  --templates <file>   Whisper templates JSON to use instead of the shipped ones
+  --encoding <name>    Log encoding, as for watch
+
+  -h, --help           Show this message";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// One JSON object per line: {"event", "timestamp", "payload"}
+    #[default]
+    Json,
+    /// "<event> <payload>" per line, for reading in a terminal
+    Text,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WatchArgs {
+    pub d2_dir: Option<String>,
+    pub format: OutputFormat,
+    pub mode: WatchMode,
+    pub backfill: Option<usize>,
+    pub events: Vec<String>,
+    pub encoding: LogEncoding,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseArgs {
+    /// Log file to read; None reads stdin
+    pub input: Option<PathBuf>,
+    pub format: OutputFormat,
+    pub stats_only: bool,
+    pub templates: Option<PathBuf>,
+    pub encoding: LogEncoding,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CliCommand {
+    Watch(WatchArgs),
+    Parse(ParseArgs),
+    Help,
+}
+
+/// Split "--flag=value" into its parts; anything else is returned as is
+fn split_flag(arg: String) -> (String, Option<String>) {
+    match arg.split_once('=') {
+        Some((flag, value)) if flag.starts_with("--") => {
+            (flag.to_string(), Some(value.to_string()))
+        }
+        _ => (arg, None),
+    }
+}
+
+fn parse_format(value: &str) -> Result<OutputFormat, String> {
+    match value {
+        "json" => Ok(OutputFormat::Json),
+        "text" => Ok(OutputFormat::Text),
+        other => Err(format!("Unknown format: {}", other)),
+    }
+}
+
+fn parse_encoding(value: &str) -> Result<LogEncoding, String> {
+    match value {
+        "auto" => Ok(LogEncoding::Auto),
+        "utf8" | "utf-8" => Ok(LogEncoding::Utf8),
+        "windows1250" | "windows-1250" | "cp1250" => Ok(LogEncoding::Windows1250),
+        "windows1251" | "windows-1251" | "cp1251" => Ok(LogEncoding::Windows1251),
+        "windows1252" | "windows-1252" | "cp1252" => Ok(LogEncoding::Windows1252),
+        other => Err(format!("Unknown encoding: {}", other)),
+    }
+}
+
+/// Parse the arguments after the program name
+pub fn parse_args<I>(args: I) -> Result<CliCommand, String>
+where
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    let mut args = args.into_iter().map(Into::into);
+    let command = match args.next() {
+        Some(command) => command,
+        None => return Err(USAGE.to_string()),
+    };
+
+    match command.as_str() {
+        "watch" => parse_watch_args(args),
+        "parse" => parse_parse_args(args),
+        "-h" | "--help" | "help" => Ok(CliCommand::Help),
+        other => Err(format!("Unknown command: {}\n\n{}", other, USAGE)),
+    }
+}
+
+fn parse_watch_args(mut args: impl Iterator<Item = String>) -> Result<CliCommand, String> {
+    let mut watch = WatchArgs::default();
+    while let Some(arg) = args.next() {
+        // Accept both "--flag value" and "--flag=value"
+        let (flag, inline) = split_flag(arg);
+        if flag == "-h" || flag == "--help" {
+            return Ok(CliCommand::Help);
+        }
+
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match flag.as_str() {
+            "--d2-dir" => watch.d2_dir = Some(value()?),
+            "--format" => watch.format = parse_format(&value()?)?,
+            "--mode" => {
+                watch.mode = match value()?.as_str() {
+                    "auto" => WatchMode::Auto,
+                    "native" => WatchMode::Native,
+                    "poll" => WatchMode::Poll,
+                    other => return Err(format!("Unknown watch mode: {}", other)),
+                }
+            }
+            "--backfill" => {
+                let lines = value()?;
+                watch.backfill = Some(
+                    lines
+                        .parse()
+                        .map_err(|_| format!("Invalid backfill line count: {}", lines))?,
+                );
+            }
+            "--events" => {
+                watch.events = value()?
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            }
+            "--encoding" => watch.encoding = parse_encoding(&value()?)?,
+            other => return Err(format!("Unknown option: {}\n\n{}", other, USAGE)),
+        }
+    }
+
+    Ok(CliCommand::Watch(watch))
+}
+
+fn parse_parse_args(mut args: impl Iterator<Item = String>) -> Result<CliCommand, String> {
+    let mut parse = ParseArgs::default();
+    let mut input = None;
+    while let Some(arg) = args.next() {
+        let (flag, inline) = split_flag(arg);
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("Missing value for {}", flag))
+        };
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "--format" => parse.format = parse_format(&value()?)?,
+            "--stats-only" => parse.stats_only = true,
+            "--templates" => parse.templates = Some(PathBuf::from(value()?)),
+            "--encoding" => parse.encoding = parse_encoding(&value()?)?,
+            other if other.starts_with("--") => {
+                return Err(format!("Unknown option: {}\n\n{}", other, USAGE))
+            }
+            _ if input.is_some() => return Err(format!("Unexpected argument: {}", flag)),
+            _ => input = Some(flag),
+        }
+    }
+
+    parse.input = match input.as_deref() {
+        None => return Err(format!("Missing log file to parse\n\n{}", USAGE)),
+        Some("-") => None,
+        Some(path) => Some(PathBuf::from(path)),
+    };
+    Ok(CliCommand::Parse(parse))
+}
+
+/// Prints "<event> <payload>" lines
+struct TextSink;
+
+impl EventSink for TextSink {
+    fn send(&mut self, event: &SinkEvent) -> Result<(), String> {
+        let mut out = std::io::stdout().lock();
+        writeln!(out, "{} {}", event.event, event.payload)
+            .and_then(|_| out.flush())
+            .map_err(|e| format!("Failed to write event to stdout: {}", e))
+    }
+}
+
+/// Start the watcher with stdout as its only sink and keep it running until the process is killed
+pub fn watch(args: WatchArgs) -> Result<(), String> {
+    let sink: Box<dyn EventSink> = match args.format {
+        OutputFormat::Json => Box::new(StdoutSink),
+        OutputFormat::Text => Box::new(TextSink),
+    };
+    let mut sinks = EventSinks::default();
+    sinks.add(sink, args.events);
+
+    let watcher = ChatWatcher::default();
+    watcher.set_event_sinks(sinks)?;
+    watcher.set_log_encoding(args.encoding)?;
+    let backfill = args.backfill.map(|lines| BackfillOptions {
+        lines: Some(lines),
+        minutes: None,
+    });
+    let backend = watcher.start_headless(args.d2_dir, args.mode, backfill)?;
+
+    let status = watcher.status();
+    eprintln!(
+        "Watching {} ({:?})",
+        status.chat_log_path.as_deref().unwrap_or("chat log"),
+        backend
+    );
+
+    // The reader thread does the work; Ctrl+C ends the process
+    loop {
+        std::thread::park();
+    }
+}
+
+/// Human-readable summary of the trade statistics
+pub fn format_stats(stats: &LogStats) -> String {
+    let mut out = format!(
+        "Lines: {} ({} parsed)\nWhispers: {} in, {} out\nTrades: {} ({} incoming)\n",
+        stats.total_lines,
+        stats.parsed_lines,
+        stats.whispers_in,
+        stats.whispers_out,
+        stats.trades,
+        stats.incoming_trades
+    );
+
+    let mut section = |title: &str, rows: Vec<String>| {
+        if rows.is_empty() {
+            return;
+        }
+        out.push_str(&format!("\n{}:\n", title));
+        for row in rows {
+            out.push_str(&format!("  {}\n", row));
+        }
+    };
+    section(
+        "Trades per item",
+        stats
+            .trades_per_item
+            .iter()
+            .map(|entry| format!("{:>5}  {}", entry.count, entry.name))
+            .collect(),
+    );
+    section(
+        "Trades per buyer",
+        stats
+            .trades_per_buyer
+            .iter()
+            .map(|entry| format!("{:>5}  {}", entry.count, entry.name))
+            .collect(),
+    );
+    let mut hours: Vec<String> = stats
+        .trades_per_hour
+        .iter()
+        .map(|entry| format!("{:>5}  {:02}:00", entry.count, entry.hour))
+        .collect();
+    if !hours.is_empty() && stats.untimed_trades > 0 {
+        hours.push(format!("{:>5}  no timestamp", stats.untimed_trades));
+    }
+    section("Trades per hour", hours);
+    section(
+        "Prices",
+        stats
+            .price_distribution
+            .iter()
+            .map(|price| {
+                format!(
+                    "{:>5}  {:<6} min {} / median {} / mean {:.2} / max {}",
+                    price.count, price.currency, price.min, price.median, price.mean, price.max
+                )
+            })
+            .collect(),
+    );
+    out
+}
+
+/// Parse a saved log and print the events and statistics
+pub fn parse(args: ParseArgs) -> Result<(), String> {
+    let templates = match &args.templates {
+        Some(path) => WhisperTemplates::load(path)?,
+        None => SHIPPED_TEMPLATES.clone(),
+    };
+    let mut analysis: LogAnalysis = match &args.input {
+        Some(path) => analyse_log_file(path, &templates, args.encoding)?,
+        None => {
+            let mut bytes = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed to read stdin: {}", e))?;
+            analyse_log_bytes(&bytes, &templates, args.encoding)
+        }
+    };
+    if args.stats_only {
+        analysis.events.clear();
+    }
+
+    let mut out = std::io::stdout().lock();
+    let written = match args.format {
+        OutputFormat::Json => serde_json::to_string_pretty(&analysis)
+            .map_err(|e| format!("Failed to serialize analysis: {}", e))
+            .and_then(|json| writeln!(out, "{}", json).map_err(|e| e.to_string())),
+        OutputFormat::Text => {
+            let mut text = String::new();
+            for event in &analysis.events {
+                let json = serde_json::to_string(&event.chat_line).unwrap_or_default();
+                text.push_str(&format!("{:>6}  {}\n", event.line, json));
+            }
+            if !analysis.events.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&format_stats(&analysis.stats));
+            write!(out, "{}", text).map_err(|e| e.to_string())
+        }
+    };
+    written.map_err(|e| format!("Failed to write analysis: {}", e))
+}
+
+/// Entry point of the `pd2-trader-cli` binary
+pub fn run<I>(args: I) -> Result<(), String>
+where
+    I: IntoIterator,
+    I::Item: Into<String>,
+{
+    match parse_args(args)? {
+        CliCommand::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        CliCommand::Watch(args) => watch(args),
+        CliCommand::Parse(args) => parse(args),
+    }
+}
@@ -1,5 +1,9 @@
-use crate::{chat_watcher, keyboard, window};
+use crate::{
+    chat_watcher, game_chat, hotkeys, input_capture, keyboard, player_registry, trade_history,
+    window,
+};
 use tauri::Manager;
+use tauri_plugin_clipboard_manager::ClipboardExt;
 
 #[tauri::command]
 pub fn greet(name: &str) -> String {
@@ -16,6 +20,88 @@ pub fn press_key(sequence: String) -> Result<(), String> {
     keyboard::press_key(sequence)
 }
 
+#[tauri::command]
+pub async fn press_key_sequence(steps: Vec<keyboard::KeyStep>) -> Result<(), String> {
+    // Waits between steps would otherwise hold up the command thread
+    tauri::async_runtime::spawn_blocking(move || keyboard::run_key_sequence(&steps))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn send_game_chat(
+    app_handle: tauri::AppHandle,
+    target: String,
+    text: String,
+) -> Result<(), String> {
+    let lines = game_chat::whisper_lines(&target, &text)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        window::focus_diablo()?;
+
+        // Lines the game can't take as typed keys go through the clipboard; put back what was there
+        let clipboard = app_handle.clipboard();
+        let previous = clipboard.read_text().ok();
+        let mut pasted = false;
+        let result = game_chat::send_chat_lines(&lines, |line| {
+            pasted = true;
+            clipboard.write_text(line).map_err(|e| e.to_string())
+        });
+        if pasted {
+            if let Some(previous) = previous {
+                let _ = clipboard.write_text(previous);
+            }
+        }
+        result
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+// Async so registering shortcuts, which waits on the main thread, doesn't block it
+#[tauri::command]
+pub async fn set_input_capture_enabled(
+    app_handle: tauri::AppHandle,
+    input_capture: tauri::State<'_, input_capture::InputCapture>,
+    hotkeys: tauri::State<'_, hotkeys::HotkeyRegistry>,
+    enabled: bool,
+) -> Result<input_capture::InputCaptureStatus, String> {
+    // The key listener reports hotkeys itself, so global shortcuts would fire them twice
+    hotkeys.set_global_shortcuts(!enabled)?;
+    Ok(input_capture.set_enabled(app_handle, enabled))
+}
+
+#[tauri::command]
+pub fn get_input_capture_status(
+    input_capture: tauri::State<input_capture::InputCapture>,
+) -> input_capture::InputCaptureStatus {
+    input_capture.status()
+}
+
+// Async for the same reason as set_input_capture_enabled
+#[tauri::command]
+pub async fn set_hotkeys(
+    hotkeys: tauri::State<'_, hotkeys::HotkeyRegistry>,
+    bindings: Vec<hotkeys::HotkeyBinding>,
+) -> Result<Vec<hotkeys::HotkeyProblem>, String> {
+    hotkeys.set_bindings(bindings)
+}
+
+// Async since checking waits for a registration in progress
+#[tauri::command]
+pub async fn check_hotkeys(
+    hotkeys: tauri::State<'_, hotkeys::HotkeyRegistry>,
+    bindings: Vec<hotkeys::HotkeyBinding>,
+) -> Result<Vec<hotkeys::HotkeyProblem>, String> {
+    hotkeys.check(&bindings)
+}
+
+#[tauri::command]
+pub async fn get_hotkey_status(
+    hotkeys: tauri::State<'_, hotkeys::HotkeyRegistry>,
+) -> Result<hotkeys::HotkeyStatus, String> {
+    hotkeys.status()
+}
+
 #[tauri::command]
 pub fn is_diablo_focused() -> bool {
     window::is_diablo_focused()
@@ -124,16 +210,178 @@ pub fn reposition_toast_window(app_handle: tauri::AppHandle) -> Result<(), Strin
 }
 
 #[tauri::command]
-pub fn start_chat_watcher(
+pub async fn start_chat_watcher(
     app_handle: tauri::AppHandle,
     custom_d2_dir: Option<String>,
+    mode: Option<chat_watcher::WatchMode>,
+    backfill: Option<chat_watcher::BackfillOptions>,
+) -> Result<chat_watcher::WatchBackend, String> {
+    // Starting replaces a running watch, which waits for its reader thread to finish
+    tauri::async_runtime::spawn_blocking(move || {
+        app_handle
+            .state::<chat_watcher::ChatWatcher>()
+            .start(
+                app_handle.clone(),
+                custom_d2_dir,
+                mode.unwrap_or_default(),
+                backfill,
+            )
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn stop_chat_watcher(app_handle: tauri::AppHandle) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        app_handle
+            .state::<chat_watcher::ChatWatcher>()
+            .stop()
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn get_chat_watcher_status(
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+) -> chat_watcher::ChatWatcherStatus {
+    chat_watcher.status()
+}
+
+#[tauri::command]
+pub fn get_game_session(
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+) -> chat_watcher::GameSession {
+    chat_watcher.game_session()
+}
+
+#[tauri::command]
+pub fn get_trade_threads(
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+) -> Vec<chat_watcher::TradeThread> {
+    chat_watcher.trade_threads()
+}
+
+#[tauri::command]
+pub fn analyse_chat_log(
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+    path: Option<String>,
+    text: Option<String>,
+) -> Result<chat_watcher::LogAnalysis, String> {
+    match (path, text) {
+        (Some(path), _) => chat_watcher.analyse_log_file(std::path::Path::new(&path)),
+        (None, Some(text)) => Ok(chat_watcher.analyse_log(&text)),
+        (None, None) => Err("Either a log file path or log text is required".to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn get_whisper_templates(
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+) -> Vec<chat_watcher::WhisperTemplate> {
+    chat_watcher.whisper_templates()
+}
+
+#[tauri::command]
+pub fn set_whisper_templates(
+    app_handle: tauri::AppHandle,
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+    templates: Vec<chat_watcher::WhisperTemplate>,
+) -> Result<(), String> {
+    // Compile before saving so a bad pattern never replaces a working set
+    let templates = chat_watcher::WhisperTemplates::new(templates)?;
+    templates.save(&chat_watcher::whisper_templates_path(&app_handle)?)?;
+    chat_watcher.set_whisper_templates(templates)
+}
+
+#[tauri::command]
+pub fn get_whisper_filter(
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+) -> chat_watcher::WhisperFilterConfig {
+    chat_watcher.whisper_filter()
+}
+
+#[tauri::command]
+pub fn set_whisper_filter(
+    app_handle: tauri::AppHandle,
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+    config: chat_watcher::WhisperFilterConfig,
+) -> Result<(), String> {
+    let filter = chat_watcher::WhisperFilter::new(config)?;
+    filter.save_config(&chat_watcher::whisper_filter_path(&app_handle)?)?;
+    chat_watcher.set_whisper_filter(filter)
+}
+
+#[tauri::command]
+pub fn get_log_encoding(
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+) -> chat_watcher::LogEncoding {
+    chat_watcher.log_encoding()
+}
+
+#[tauri::command]
+pub fn set_log_encoding(
+    app_handle: tauri::AppHandle,
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+    encoding: chat_watcher::LogEncoding,
 ) -> Result<(), String> {
-    chat_watcher::start_watching(app_handle, custom_d2_dir)
+    encoding.save_config(&chat_watcher::log_encoding_path(&app_handle)?)?;
+    chat_watcher.set_log_encoding(encoding)
+}
+
+#[tauri::command]
+pub fn get_suppressed_whisper_counts(
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+) -> chat_watcher::SuppressedCounts {
+    chat_watcher.suppressed_counts()
+}
+
+#[tauri::command]
+pub fn get_event_sinks(
+    chat_watcher: tauri::State<chat_watcher::ChatWatcher>,
+) -> Vec<chat_watcher::SinkConfig> {
+    chat_watcher.event_sinks()
+}
+
+#[tauri::command]
+pub async fn set_event_sinks(
+    app_handle: tauri::AppHandle,
+    chat_watcher: tauri::State<'_, chat_watcher::ChatWatcher>,
+    sinks: Vec<chat_watcher::SinkConfig>,
+) -> Result<(), String> {
+    let sinks = chat_watcher::EventSinks::new(sinks)?;
+    sinks.save_config(&chat_watcher::event_sinks_path(&app_handle)?)?;
+    let previous = chat_watcher.replace_event_sinks(sinks)?;
+    // The old sinks finish their webhook retries in the background instead of holding up the UI
+    tauri::async_runtime::spawn_blocking(move || drop(previous));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn query_trade_history(
+    history: tauri::State<trade_history::TradeHistory>,
+    query: Option<trade_history::TradeHistoryQuery>,
+) -> Result<Vec<trade_history::TradeHistoryEntry>, String> {
+    history.query(&query.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn get_account_characters(
+    registry: tauri::State<player_registry::PlayerRegistry>,
+    account: String,
+) -> Result<Vec<player_registry::KnownCharacter>, String> {
+    registry.characters(&account)
 }
 
 #[tauri::command]
-pub fn stop_chat_watcher() -> Result<(), String> {
-    chat_watcher::stop_watching()
+pub fn get_character_accounts(
+    registry: tauri::State<player_registry::PlayerRegistry>,
+    character: String,
+) -> Result<Vec<player_registry::KnownCharacter>, String> {
+    registry.accounts(&character)
 }
 
 #[tauri::command]
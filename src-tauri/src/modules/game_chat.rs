@@ -0,0 +1,154 @@
+use crate::keyboard::{self, KeyStep};
+use encoding_rs::{WINDOWS_1250, WINDOWS_1251, WINDOWS_1252};
+use std::thread;
+use std::time::Duration;
+
+/// Longest line the game's chat box takes, counting the `/w *account ` prefix
+pub const CHAT_LIMIT: usize = 255;
+
+/// Gives the chat box time to open and take the text before the next key
+const CHAT_KEY_DELAY_MS: u64 = 50;
+
+/// Pause between the lines of a split message, so the game doesn't drop them as spam
+const CHAT_LINE_DELAY: Duration = Duration::from_millis(400);
+
+/// Characters the game can show; its fonts only cover the Windows code pages logs are written in
+fn is_displayable(c: char) -> bool {
+    if c.is_ascii() {
+        return !c.is_ascii_control();
+    }
+    if c.is_control() {
+        return false;
+    }
+    let mut buf = [0u8; 4];
+    let s = c.encode_utf8(&mut buf);
+    [WINDOWS_1252, WINDOWS_1250, WINDOWS_1251]
+        .iter()
+        .any(|encoding| !encoding.encode(s).2)
+}
+
+/// Replace typographic characters with plain ones and drop what the game can't show
+///
+/// Newlines and other whitespace become single spaces, since Enter would send the line early.
+pub fn sanitize_chat_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{2032}' => out.push('\''),
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{2033}' => out.push('"'),
+            '\u{2010}'..='\u{2015}' | '\u{2212}' => out.push('-'),
+            '\u{2026}' => out.push_str("..."),
+            // Collapse runs and skip leading whitespace
+            c if c.is_whitespace() && !out.is_empty() && !out.ends_with(' ') => out.push(' '),
+            c if c.is_whitespace() => {}
+            c if is_displayable(c) => out.push(c),
+            _ => {}
+        }
+    }
+    out.trim_end().to_string()
+}
+
+/// The `/w *account ` prefix for a whisper; a leading `*` on the target is optional
+fn whisper_prefix(target: &str) -> Result<String, String> {
+    let account = target.trim().trim_start_matches('*');
+    if account.is_empty() {
+        return Err("Whisper target is empty".to_string());
+    }
+    if account.contains(char::is_whitespace) {
+        return Err(format!("Invalid whisper target: {target}"));
+    }
+    Ok(format!("/w *{account} "))
+}
+
+/// Split a whisper into chat lines of at most `CHAT_LIMIT` characters
+///
+/// Lines break between words; a word longer than a whole line is cut.
+pub fn whisper_lines(target: &str, text: &str) -> Result<Vec<String>, String> {
+    let prefix = whisper_prefix(target)?;
+    let text = sanitize_chat_text(text);
+    if text.is_empty() {
+        return Err("Message is empty".to_string());
+    }
+    let budget = CHAT_LIMIT
+        .checked_sub(prefix.chars().count())
+        .filter(|budget| *budget > 0)
+        .ok_or_else(|| format!("Whisper target is too long: {target}"))?;
+
+    let mut chunks: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for word in text.split(' ') {
+        let mut word: Vec<char> = word.chars().collect();
+        loop {
+            let separator = usize::from(current_len > 0);
+            if current_len + separator + word.len() <= budget {
+                if separator > 0 {
+                    current.push(' ');
+                }
+                current.extend(word.iter());
+                current_len += separator + word.len();
+                break;
+            }
+            if current_len > 0 {
+                chunks.push(std::mem::take(&mut current));
+                current_len = 0;
+                continue;
+            }
+            // Too long for a line of its own
+            let rest = word.split_off(budget);
+            chunks.push(word.into_iter().collect());
+            word = rest;
+        }
+    }
+    if current_len > 0 {
+        chunks.push(current);
+    }
+
+    Ok(chunks
+        .into_iter()
+        .map(|chunk| format!("{prefix}{chunk}"))
+        .collect())
+}
+
+/// Whether a line has to be pasted: the game ignores non-ASCII characters typed as key events
+pub fn needs_paste(line: &str) -> bool {
+    !line.is_ascii()
+}
+
+/// Open the chat box, enter the line and send it
+///
+/// With `pasted`, the line is expected on the clipboard and entered with Ctrl+V.
+pub fn chat_line_steps(line: &str, pasted: bool) -> Vec<KeyStep> {
+    let entry = if pasted {
+        KeyStep::Chord("ctrl+v".to_string())
+    } else {
+        KeyStep::Text(line.to_string())
+    };
+    vec![
+        KeyStep::Chord("enter".to_string()),
+        KeyStep::WaitMs(CHAT_KEY_DELAY_MS),
+        entry,
+        KeyStep::WaitMs(CHAT_KEY_DELAY_MS),
+        KeyStep::Chord("enter".to_string()),
+    ]
+}
+
+/// Send each line through the chat box, calling `paste` to put a line on the clipboard when needed
+///
+/// Diablo II has to be focused already.
+pub fn send_chat_lines(
+    lines: &[String],
+    mut paste: impl FnMut(&str) -> Result<(), String>,
+) -> Result<(), String> {
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            thread::sleep(CHAT_LINE_DELAY);
+        }
+        let pasted = needs_paste(line);
+        if pasted {
+            paste(line)?;
+        }
+        keyboard::run_key_sequence(&chat_line_steps(line, pasted))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use super::{HotkeyRegistry, HotkeyTriggeredEvent, ShortcutBackend, HOTKEY_TRIGGERED_EVENT};
+use std::sync::mpsc;
+use std::thread;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+
+/// Registers hotkeys as OS-wide shortcuts with `tauri-plugin-global-shortcut`
+pub struct GlobalShortcutBackend {
+    app_handle: AppHandle,
+}
+
+impl GlobalShortcutBackend {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self { app_handle }
+    }
+}
+
+fn parse_shortcut(sequence: &str) -> Result<Shortcut, String> {
+    sequence
+        .parse()
+        .map_err(|e| format!("Can't use {sequence} as a global shortcut: {e}"))
+}
+
+impl ShortcutBackend for GlobalShortcutBackend {
+    fn check(&self, sequence: &str) -> Result<(), String> {
+        parse_shortcut(sequence).map(|_| ())
+    }
+
+    fn register(&mut self, sequence: &str) -> Result<u32, String> {
+        let shortcut = parse_shortcut(sequence)?;
+        self.app_handle
+            .global_shortcut()
+            .register(shortcut)
+            .map_err(|e| e.to_string())?;
+        Ok(shortcut.id())
+    }
+
+    fn unregister(&mut self, sequence: &str) -> Result<(), String> {
+        self.app_handle
+            .global_shortcut()
+            .unregister(parse_shortcut(sequence)?)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Plugin handler: emits `hotkey-triggered` when a registered hotkey is pressed
+pub fn handle_shortcut(app_handle: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let Some(registry) = app_handle.try_state::<HotkeyRegistry>() else {
+        return;
+    };
+    if let Some(action) = registry.action_for_id(shortcut.id()) {
+        if let Err(e) = app_handle.emit(HOTKEY_TRIGGERED_EVENT, HotkeyTriggeredEvent { action }) {
+            eprintln!("[hotkeys] Failed to emit hotkey: {}", e);
+        }
+    }
+}
+
+/// Applies focus changes to the registry on its own thread, in order
+///
+/// Registering waits on the main thread, which is where Windows reports focus changes.
+pub fn spawn_focus_worker(app_handle: AppHandle) -> mpsc::Sender<bool> {
+    let (tx, rx) = mpsc::channel::<bool>();
+    let spawned = thread::Builder::new()
+        .name("hotkey-focus".into())
+        .spawn(move || {
+            for focused in rx {
+                let registry = app_handle.state::<HotkeyRegistry>();
+                if let Err(e) = registry.set_focused(focused) {
+                    eprintln!("[hotkeys] {}", e);
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        eprintln!("[hotkeys] Failed to start focus thread: {}", e);
+    }
+    tx
+}
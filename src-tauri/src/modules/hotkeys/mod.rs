@@ -0,0 +1,398 @@
+use crate::keyboard::str_to_keys;
+use enigo::Key;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Mutex;
+
+pub mod global_shortcut;
+
+/// Event emitted with a `HotkeyTriggeredEvent` when a bound hotkey is pressed
+pub const HOTKEY_TRIGGERED_EVENT: &str = "hotkey-triggered";
+
+/// Keys Diablo II uses out of the box, plus Ctrl+C which copies an item's text for price checks
+///
+/// A global shortcut on one of these would take the key away from the game.
+pub const GAME_BINDS: &[(&str, &str)] = &[
+    ("ctrl+c", "copy item text"),
+    ("a", "character screen"),
+    ("c", "character screen"),
+    ("b", "inventory"),
+    ("i", "inventory"),
+    ("t", "skill tree"),
+    ("s", "skill speed bar"),
+    ("q", "quest log"),
+    ("p", "party screen"),
+    ("o", "hireling screen"),
+    ("m", "message log"),
+    ("h", "help"),
+    ("w", "swap weapons"),
+    ("r", "toggle run/walk"),
+    ("z", "show portraits"),
+    ("n", "clear messages"),
+    ("tab", "automap"),
+    ("space", "clear screen"),
+    ("enter", "chat"),
+    ("esc", "game menu"),
+    ("`", "show belt"),
+    ("1", "belt slot 1"),
+    ("2", "belt slot 2"),
+    ("3", "belt slot 3"),
+    ("4", "belt slot 4"),
+    ("f1", "skill 1"),
+    ("f2", "skill 2"),
+    ("f3", "skill 3"),
+    ("f4", "skill 4"),
+    ("f5", "skill 5"),
+    ("f6", "skill 6"),
+    ("f7", "skill 7"),
+    ("f8", "skill 8"),
+];
+
+/// What a hotkey does in the app
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum HotkeyAction {
+    PriceCheck,
+    ListItem,
+    Settings,
+    CurrencyValuation,
+    Chat,
+    Offers,
+}
+
+impl fmt::Display for HotkeyAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HotkeyAction::PriceCheck => "price check",
+            HotkeyAction::ListItem => "list item",
+            HotkeyAction::Settings => "settings",
+            HotkeyAction::CurrencyValuation => "currency valuation",
+            HotkeyAction::Chat => "chat",
+            HotkeyAction::Offers => "offers",
+        })
+    }
+}
+
+/// A key sequence in the `str_to_keys` grammar, e.g. "ctrl+d", bound to an action
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyBinding {
+    pub action: HotkeyAction,
+    pub sequence: String,
+}
+
+/// Why a binding can't be used, or failed to register
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyProblem {
+    pub action: HotkeyAction,
+    pub sequence: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct HotkeyTriggeredEvent {
+    pub action: HotkeyAction,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct HotkeyStatus {
+    pub bindings: Vec<HotkeyBinding>,
+    /// Whether Diablo II (or one of our windows) has focus
+    pub focused: bool,
+    /// Off while the input capture fallback reports keys instead
+    pub global_shortcuts: bool,
+    /// Actions whose shortcut is registered with the OS right now
+    pub registered: Vec<HotkeyAction>,
+    /// Bindings left out when they were set, then those the OS refused on the last
+    /// activation, e.g. taken by another app
+    pub failed: Vec<HotkeyProblem>,
+}
+
+type Keys = (Vec<Key>, Key);
+
+/// Same main key and the same modifiers, in any order
+fn same_keys(a: &Keys, b: &Keys) -> bool {
+    a.1 == b.1 && a.0.len() == b.0.len() && a.0.iter().all(|key| b.0.contains(key))
+}
+
+/// Everything wrong with a set of bindings: bad sequences, actions bound twice, and
+/// sequences used by another binding or by the game
+pub fn check_bindings(bindings: &[HotkeyBinding]) -> Vec<HotkeyProblem> {
+    let mut problems = Vec::new();
+    let mut parsed: Vec<(&HotkeyBinding, Keys)> = Vec::new();
+    for binding in bindings {
+        let problem = |message: String| HotkeyProblem {
+            action: binding.action,
+            sequence: binding.sequence.clone(),
+            message,
+        };
+        if parsed
+            .iter()
+            .any(|(other, _)| other.action == binding.action)
+        {
+            problems.push(problem(format!(
+                "The {} hotkey is bound twice",
+                binding.action
+            )));
+            continue;
+        }
+        let keys = match str_to_keys(&binding.sequence) {
+            Ok(keys) => keys,
+            Err(e) => {
+                problems.push(problem(e));
+                continue;
+            }
+        };
+
+        if let Some((other, _)) = parsed.iter().find(|(_, other)| same_keys(other, &keys)) {
+            problems.push(problem(format!("Already used for {}", other.action)));
+        } else if let Some((_, name)) = GAME_BINDS
+            .iter()
+            .find(|(seq, _)| str_to_keys(seq).is_ok_and(|game| same_keys(&game, &keys)))
+        {
+            problems.push(problem(format!("Diablo II uses this key for {name}")));
+        }
+        parsed.push((binding, keys));
+    }
+    problems
+}
+
+/// Where hotkeys are registered with the OS; the global shortcut plugin in the app
+pub trait ShortcutBackend: Send {
+    /// Whether the backend can register a sequence at all, without registering it
+    ///
+    /// Its key names don't all match `str_to_keys`, so a valid binding can still be refused here.
+    fn check(&self, sequence: &str) -> Result<(), String>;
+    /// Register a sequence, returning the id its presses are reported with
+    fn register(&mut self, sequence: &str) -> Result<u32, String>;
+    fn unregister(&mut self, sequence: &str) -> Result<(), String>;
+}
+
+struct Registration {
+    action: HotkeyAction,
+    sequence: String,
+    id: u32,
+}
+
+struct RegistryState {
+    bindings: Vec<HotkeyBinding>,
+    focused: bool,
+    global_shortcuts: bool,
+    registered: Vec<Registration>,
+    /// Problems found in the bindings last set; those bindings are never registered
+    rejected: Vec<HotkeyProblem>,
+    failed: Vec<HotkeyProblem>,
+    backend: Box<dyn ShortcutBackend>,
+}
+
+impl RegistryState {
+    fn should_register(&self) -> bool {
+        self.focused && self.global_shortcuts
+    }
+
+    /// `check_bindings`, plus sequences the backend can't register
+    fn check(&self, bindings: &[HotkeyBinding]) -> Vec<HotkeyProblem> {
+        let mut problems = check_bindings(bindings);
+        for binding in bindings {
+            if problems
+                .iter()
+                .any(|problem| problem.action == binding.action)
+            {
+                continue;
+            }
+            if let Err(message) = self.backend.check(&binding.sequence) {
+                problems.push(HotkeyProblem {
+                    action: binding.action,
+                    sequence: binding.sequence.clone(),
+                    message,
+                });
+            }
+        }
+        problems
+    }
+
+    /// Unregister everything, then register the bindings again if they should be active
+    fn sync(&mut self) {
+        for registration in std::mem::take(&mut self.registered) {
+            if let Err(e) = self.backend.unregister(&registration.sequence) {
+                eprintln!(
+                    "[hotkeys] Failed to unregister {} ({}): {}",
+                    registration.action, registration.sequence, e
+                );
+            }
+        }
+        self.failed.clear();
+        if !self.should_register() {
+            return;
+        }
+
+        for binding in &self.bindings {
+            match self.backend.register(&binding.sequence) {
+                Ok(id) => self.registered.push(Registration {
+                    action: binding.action,
+                    sequence: binding.sequence.clone(),
+                    id,
+                }),
+                Err(e) => {
+                    eprintln!(
+                        "[hotkeys] Failed to register {} ({}): {}",
+                        binding.action, binding.sequence, e
+                    );
+                    self.failed.push(HotkeyProblem {
+                        action: binding.action,
+                        sequence: binding.sequence.clone(),
+                        message: e,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Owns every hotkey binding and registers them only while Diablo II is focused
+///
+/// All changes go through one lock, so focus flips and binding updates can't interleave.
+pub struct HotkeyRegistry {
+    state: Mutex<RegistryState>,
+    /// Registered ids, kept apart so a key press never waits on a registration in progress
+    actions: Mutex<Vec<(u32, HotkeyAction)>>,
+}
+
+impl HotkeyRegistry {
+    pub fn new(backend: impl ShortcutBackend + 'static) -> Self {
+        Self {
+            state: Mutex::new(RegistryState {
+                bindings: Vec::new(),
+                focused: false,
+                global_shortcuts: true,
+                registered: Vec::new(),
+                rejected: Vec::new(),
+                failed: Vec::new(),
+                backend: Box::new(backend),
+            }),
+            actions: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Everything wrong with a set of bindings, including sequences the backend can't register
+    pub fn check(&self, bindings: &[HotkeyBinding]) -> Result<Vec<HotkeyProblem>, String> {
+        let state = self
+            .state
+            .lock()
+            .map_err(|_| "Failed to lock hotkey registry".to_string())?;
+        Ok(state.check(bindings))
+    }
+
+    /// Replace all bindings, keeping the usable ones and returning the problems with the rest
+    pub fn set_bindings(&self, bindings: Vec<HotkeyBinding>) -> Result<Vec<HotkeyProblem>, String> {
+        let mut rejected = Vec::new();
+        self.update(|state| {
+            let problems = state.check(&bindings);
+            let mut unmatched: Vec<&HotkeyProblem> = problems.iter().collect();
+            state.bindings = bindings
+                .into_iter()
+                .filter(|binding| {
+                    let problem = unmatched.iter().position(|problem| {
+                        problem.action == binding.action && problem.sequence == binding.sequence
+                    });
+                    problem.map(|index| unmatched.remove(index)).is_none()
+                })
+                .collect();
+            state.rejected = problems.clone();
+            rejected = problems;
+            Ok(true)
+        })?;
+        Ok(rejected)
+    }
+
+    /// Called when Diablo II gains or loses focus
+    pub fn set_focused(&self, focused: bool) -> Result<(), String> {
+        self.update(|state| {
+            let was_active = state.should_register();
+            state.focused = focused;
+            Ok(was_active != state.should_register())
+        })
+    }
+
+    /// Turn OS shortcuts off while the input capture fallback reports keys instead
+    pub fn set_global_shortcuts(&self, enabled: bool) -> Result<(), String> {
+        self.update(|state| {
+            let was_active = state.should_register();
+            state.global_shortcuts = enabled;
+            Ok(was_active != state.should_register())
+        })
+    }
+
+    /// Apply a change and re-register if it says so
+    fn update(
+        &self,
+        change: impl FnOnce(&mut RegistryState) -> Result<bool, String>,
+    ) -> Result<(), String> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| "Failed to lock hotkey registry".to_string())?;
+        if !change(&mut state)? {
+            return Ok(());
+        }
+        state.sync();
+
+        let mut actions = self
+            .actions
+            .lock()
+            .map_err(|_| "Failed to lock hotkey actions".to_string())?;
+        *actions = state
+            .registered
+            .iter()
+            .map(|registration| (registration.id, registration.action))
+            .collect();
+        Ok(())
+    }
+
+    /// The action for a registered shortcut id, as reported by the OS on a key press
+    pub fn action_for_id(&self, id: u32) -> Option<HotkeyAction> {
+        let actions = self.actions.lock().ok()?;
+        actions
+            .iter()
+            .find(|(registered, _)| *registered == id)
+            .map(|(_, action)| *action)
+    }
+
+    /// The action for a hotkey reported by input capture, e.g. "ctrl+d"
+    pub fn action_for_hotkey(&self, hotkey: &str) -> Option<HotkeyAction> {
+        let keys = str_to_keys(hotkey).ok()?;
+        let state = self.state.lock().ok()?;
+        state
+            .bindings
+            .iter()
+            .find(|binding| {
+                str_to_keys(&binding.sequence).is_ok_and(|bound| same_keys(&bound, &keys))
+            })
+            .map(|binding| binding.action)
+    }
+
+    pub fn status(&self) -> Result<HotkeyStatus, String> {
+        let state = self
+            .state
+            .lock()
+            .map_err(|_| "Failed to lock hotkey registry".to_string())?;
+        Ok(HotkeyStatus {
+            bindings: state.bindings.clone(),
+            focused: state.focused,
+            global_shortcuts: state.global_shortcuts,
+            registered: state
+                .registered
+                .iter()
+                .map(|registration| registration.action)
+                .collect(),
+            failed: state
+                .rejected
+                .iter()
+                .chain(&state.failed)
+                .cloned()
+                .collect(),
+        })
+    }
+}
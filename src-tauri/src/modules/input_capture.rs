@@ -0,0 +1,191 @@
+use crate::hotkeys::{HotkeyRegistry, HotkeyTriggeredEvent, HOTKEY_TRIGGERED_EVENT};
+use crate::keyboard::{is_modifier, key_to_string, modifier_name};
+use crate::window;
+use rdev::{EventType, Key as RdevKey};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event emitted with a `HotkeyPressedEvent` for every hotkey pressed while Diablo II is focused;
+/// bound ones are also emitted as `hotkey-triggered`, like the registry's global shortcuts
+pub const HOTKEY_PRESSED_EVENT: &str = "hotkey-pressed";
+
+/// Order modifiers appear in a normalized hotkey, e.g. "ctrl+shift+c"
+const MODIFIER_ORDER: [&str; 4] = ["ctrl", "shift", "alt", "cmd"];
+
+/// Turns raw key events into normalized hotkeys like "ctrl+b" by tracking which keys are down
+#[derive(Default, Debug)]
+pub struct HotkeyTracker {
+    held: Vec<RdevKey>,
+}
+
+impl HotkeyTracker {
+    /// The hotkey a key press completes, if any
+    ///
+    /// Modifiers alone, keys without a name and auto-repeats of a key already down give `None`.
+    pub fn handle(&mut self, event: &EventType) -> Option<String> {
+        match event {
+            EventType::KeyPress(key) => {
+                if self.held.contains(key) {
+                    return None;
+                }
+                self.held.push(*key);
+                if is_modifier(*key) {
+                    return None;
+                }
+                let name = key_to_string(*key)?;
+                let mut parts = self.held_modifiers();
+                parts.push(name);
+                Some(parts.join("+"))
+            }
+            EventType::KeyRelease(key) => {
+                self.held.retain(|held| held != key);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Modifiers currently down, left and right variants merged
+    pub fn held_modifiers(&self) -> Vec<&'static str> {
+        MODIFIER_ORDER
+            .into_iter()
+            .filter(|modifier| {
+                self.held
+                    .iter()
+                    .any(|key| modifier_name(*key) == Some(*modifier))
+            })
+            .collect()
+    }
+}
+
+/// The hotkey an event completes, if it should fire now
+///
+/// Hotkeys only fire while capture is enabled and Diablo II itself is in the foreground; the
+/// focus check is skipped otherwise since it queries the window system.
+pub fn gate_hotkey(
+    tracker: &mut HotkeyTracker,
+    event: &EventType,
+    enabled: bool,
+    diablo_foreground: impl FnOnce() -> bool,
+) -> Option<String> {
+    let hotkey = tracker.handle(event)?;
+    (enabled && diablo_foreground()).then_some(hotkey)
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct HotkeyPressedEvent {
+    pub hotkey: String,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct InputCaptureStatus {
+    pub enabled: bool,
+    /// Whether the listener thread is up; it stops only if it couldn't hook the keyboard
+    pub running: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct CaptureShared {
+    enabled: AtomicBool,
+    running: AtomicBool,
+    error: Mutex<Option<String>>,
+}
+
+impl CaptureShared {
+    fn fail(&self, message: String) {
+        eprintln!("[input_capture] {}", message);
+        if let Ok(mut error) = self.error.lock() {
+            *error = Some(message);
+        }
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Listens to every key press with `rdev` and emits hotkeys itself, for setups where
+/// the global shortcut plugin can't grab keys (Wayland, XWayland under Proton)
+///
+/// `rdev::listen` can't be stopped, so the listener is started on first enable and
+/// disabling only stops events from being emitted.
+#[derive(Default)]
+pub struct InputCapture {
+    shared: Arc<CaptureShared>,
+}
+
+impl InputCapture {
+    pub fn set_enabled(&self, app_handle: AppHandle, enabled: bool) -> InputCaptureStatus {
+        self.shared.enabled.store(enabled, Ordering::SeqCst);
+        if enabled && !self.shared.running.swap(true, Ordering::SeqCst) {
+            if let Ok(mut error) = self.shared.error.lock() {
+                error.take();
+            }
+            if let Err(e) = self.spawn_listener(app_handle) {
+                self.shared
+                    .fail(format!("Failed to start input capture thread: {}", e));
+            }
+        }
+        self.status()
+    }
+
+    pub fn status(&self) -> InputCaptureStatus {
+        InputCaptureStatus {
+            enabled: self.shared.enabled.load(Ordering::SeqCst),
+            running: self.shared.running.load(Ordering::SeqCst),
+            error: self
+                .shared
+                .error
+                .lock()
+                .ok()
+                .and_then(|error| error.clone()),
+        }
+    }
+
+    fn spawn_listener(&self, app_handle: AppHandle) -> std::io::Result<()> {
+        let shared = self.shared.clone();
+        thread::Builder::new()
+            .name("input-capture".into())
+            .spawn(move || {
+                let listener = shared.clone();
+                // Keys are tracked even while disabled so modifiers held across enabling aren't missed
+                let mut tracker = HotkeyTracker::default();
+                let result = rdev::listen(move |event| {
+                    // Our own overlay counts as focused, but keys typed into it aren't game hotkeys
+                    let Some(hotkey) = gate_hotkey(
+                        &mut tracker,
+                        &event.event_type,
+                        listener.enabled.load(Ordering::SeqCst),
+                        window::is_diablo_foreground,
+                    ) else {
+                        return;
+                    };
+                    let action = app_handle
+                        .try_state::<HotkeyRegistry>()
+                        .and_then(|registry| registry.action_for_hotkey(&hotkey));
+                    if let Err(e) =
+                        app_handle.emit(HOTKEY_PRESSED_EVENT, HotkeyPressedEvent { hotkey })
+                    {
+                        eprintln!("[input_capture] Failed to emit hotkey: {}", e);
+                    }
+                    if let Some(action) = action {
+                        if let Err(e) =
+                            app_handle.emit(HOTKEY_TRIGGERED_EVENT, HotkeyTriggeredEvent { action })
+                        {
+                            eprintln!("[input_capture] Failed to emit hotkey: {}", e);
+                        }
+                    }
+                });
+
+                // listen only returns when it couldn't hook the keyboard
+                let message = match result {
+                    Ok(()) => "Key listener stopped".to_string(),
+                    Err(e) => format!("Failed to listen for keys: {:?}", e),
+                };
+                shared.fail(message);
+            })
+            .map(|_| ())
+    }
+}
@@ -1,86 +1,448 @@
 use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+use once_cell::sync::Lazy;
 use rdev::Key as RdevKey;
+use serde::{Deserialize, Serialize};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Duration;
 
+/// One `+`-separated part of a key sequence
+enum Fragment {
+    Modifier(Key),
+    Main(Key),
+}
+
+/// Split a key sequence on `+`
+///
+/// `\+` is the plus key, as is a `+` where a key name is expected ("ctrl++", "+").
+/// A trailing `+` that completes a key name ("num+", "ctrl+kp+") stays part of it.
+/// Any other character after `\` is taken literally; a trailing `\` is the backslash key.
+fn split_sequence(seq: &str) -> Result<Vec<String>, String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut chars = seq.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => current.push(chars.next().unwrap_or('\\')),
+            '+' if current.is_empty() => current.push('+'),
+            '+' if chars.as_str().is_empty() && parse_fragment(&format!("{current}+")).is_ok() => {
+                current.push('+')
+            }
+            '+' => parts.push(std::mem::take(&mut current)),
+            ch => current.push(ch),
+        }
+    }
+
+    if current.is_empty() {
+        if !parts.is_empty() {
+            return Err(format!("Missing key after '+' in {seq}"));
+        }
+    } else {
+        parts.push(current);
+    }
+    Ok(parts)
+}
+
+// Left and right modifier keys differ per platform; Linux gets the X11 keysyms
+// since enigo maps its own left Alt variant to the Menu key there
+#[cfg(target_os = "windows")]
+mod sided {
+    use enigo::Key;
+    pub const LEFT_ALT: Key = Key::LMenu;
+    pub const RIGHT_ALT: Key = Key::RMenu;
+    pub const LEFT_META: Key = Key::LWin;
+    pub const RIGHT_META: Key = Key::RWin;
+}
+
+#[cfg(target_os = "macos")]
+mod sided {
+    use enigo::Key;
+    pub const LEFT_ALT: Key = Key::Option;
+    pub const RIGHT_ALT: Key = Key::ROption;
+    pub const LEFT_META: Key = Key::Meta;
+    pub const RIGHT_META: Key = Key::RCommand;
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod sided {
+    use enigo::Key;
+    pub const LEFT_ALT: Key = Key::Other(0xffe9);
+    pub const RIGHT_ALT: Key = Key::Other(0xffea);
+    pub const LEFT_META: Key = Key::Other(0xffeb);
+    pub const RIGHT_META: Key = Key::Other(0xffec);
+}
+
+fn modifier_key(name: &str) -> Option<Key> {
+    Some(match name {
+        "ctrl" | "control" => Key::Control,
+        "lctrl" | "lcontrol" | "leftctrl" | "leftcontrol" => Key::LControl,
+        "rctrl" | "rcontrol" | "rightctrl" | "rightcontrol" => Key::RControl,
+        "shift" => Key::Shift,
+        "lshift" | "leftshift" => Key::LShift,
+        "rshift" | "rightshift" => Key::RShift,
+        "alt" | "option" | "opt" => Key::Alt,
+        "lalt" | "leftalt" | "loption" | "leftoption" => sided::LEFT_ALT,
+        "ralt" | "rightalt" | "altgr" | "roption" | "rightoption" => sided::RIGHT_ALT,
+        "cmd" | "command" | "meta" | "super" | "win" | "windows" => Key::Meta,
+        "lcmd" | "lcommand" | "lmeta" | "lsuper" | "lwin" | "leftcmd" | "leftmeta"
+        | "leftsuper" | "leftwin" => sided::LEFT_META,
+        "rcmd" | "rcommand" | "rmeta" | "rsuper" | "rwin" | "rightcmd" | "rightmeta"
+        | "rightsuper" | "rightwin" => sided::RIGHT_META,
+        _ => return None,
+    })
+}
+
+fn function_key(n: u8) -> Result<Key, String> {
+    Ok(match n {
+        1 => Key::F1,
+        2 => Key::F2,
+        3 => Key::F3,
+        4 => Key::F4,
+        5 => Key::F5,
+        6 => Key::F6,
+        7 => Key::F7,
+        8 => Key::F8,
+        9 => Key::F9,
+        10 => Key::F10,
+        11 => Key::F11,
+        12 => Key::F12,
+        13 => Key::F13,
+        14 => Key::F14,
+        15 => Key::F15,
+        16 => Key::F16,
+        17 => Key::F17,
+        18 => Key::F18,
+        19 => Key::F19,
+        20 => Key::F20,
+        #[cfg(not(target_os = "macos"))]
+        21 => Key::F21,
+        #[cfg(not(target_os = "macos"))]
+        22 => Key::F22,
+        #[cfg(not(target_os = "macos"))]
+        23 => Key::F23,
+        #[cfg(not(target_os = "macos"))]
+        24 => Key::F24,
+        _ => return Err(format!("Unsupported function key: F{}", n)),
+    })
+}
+
+/// "num5", "numpad5", "kp5", "numadd", ...
+fn numpad_key(name: &str) -> Option<Key> {
+    let rest = ["numpad", "num", "kp"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))?;
+    Some(match rest {
+        "0" => Key::Numpad0,
+        "1" => Key::Numpad1,
+        "2" => Key::Numpad2,
+        "3" => Key::Numpad3,
+        "4" => Key::Numpad4,
+        "5" => Key::Numpad5,
+        "6" => Key::Numpad6,
+        "7" => Key::Numpad7,
+        "8" => Key::Numpad8,
+        "9" => Key::Numpad9,
+        "add" | "plus" | "+" => Key::Add,
+        "sub" | "subtract" | "minus" | "-" => Key::Subtract,
+        "mul" | "multiply" | "*" => Key::Multiply,
+        "div" | "divide" | "/" => Key::Divide,
+        "decimal" | "dot" | "." => Key::Decimal,
+        // enigo has no separate keypad Enter
+        "enter" | "return" => Key::Return,
+        _ => return None,
+    })
+}
+
+fn named_key(name: &str) -> Option<Key> {
+    Some(match name {
+        "enter" | "return" => Key::Return,
+        "esc" | "escape" => Key::Escape,
+        "tab" => Key::Tab,
+        "space" | "spacebar" => Key::Space,
+        "backspace" | "back" => Key::Backspace,
+        "delete" | "del" => Key::Delete,
+        #[cfg(not(target_os = "macos"))]
+        "insert" | "ins" => Key::Insert,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" | "pgup" => Key::PageUp,
+        "pagedown" | "pgdn" | "pgdown" => Key::PageDown,
+        "up" | "uparrow" | "arrowup" => Key::UpArrow,
+        "down" | "downarrow" | "arrowdown" => Key::DownArrow,
+        "left" | "leftarrow" | "arrowleft" => Key::LeftArrow,
+        "right" | "rightarrow" | "arrowright" => Key::RightArrow,
+        "capslock" | "caps" => Key::CapsLock,
+        #[cfg(not(target_os = "macos"))]
+        "numlock" => Key::Numlock,
+        #[cfg(target_os = "windows")]
+        "scrolllock" => Key::Scroll,
+        #[cfg(all(unix, not(target_os = "macos")))]
+        "scrolllock" => Key::ScrollLock,
+        #[cfg(not(target_os = "macos"))]
+        "printscreen" | "prtsc" | "print" => Key::PrintScr,
+        #[cfg(not(target_os = "macos"))]
+        "pause" | "break" => Key::Pause,
+        "plus" => Key::Unicode('+'),
+        "minus" => Key::Unicode('-'),
+        "backslash" => Key::Unicode('\\'),
+        _ => return None,
+    })
+}
+
+fn parse_fragment(part: &str) -> Result<Fragment, String> {
+    // A lone space is the space key; otherwise surrounding whitespace is ignored
+    let trimmed = part.trim();
+    let part = if trimmed.is_empty() { part } else { trimmed };
+
+    let mut chars = part.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return Ok(Fragment::Main(match ch {
+            ' ' => Key::Space,
+            ch => Key::Unicode(ch.to_ascii_lowercase()),
+        }));
+    }
+
+    // Case, spaces and underscores don't matter: "Page Up", "page_up", "PAGEUP"
+    let name: String = part
+        .chars()
+        .filter(|ch| *ch != ' ' && *ch != '_')
+        .collect::<String>()
+        .to_lowercase();
+
+    if let Some(key) = modifier_key(&name) {
+        return Ok(Fragment::Modifier(key));
+    }
+    if let Some(key) = named_key(&name).or_else(|| numpad_key(&name)) {
+        return Ok(Fragment::Main(key));
+    }
+    if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        return function_key(n).map(Fragment::Main);
+    }
+    Err(format!("Unsupported fragment: {part}"))
+}
+
+/// Parse a key sequence like "ctrl+shift+enter" into its modifiers and main key
+///
+/// Names are case-insensitive and have aliases ("return", "esc", "num5"); modifiers
+/// have left/right variants ("lctrl", "rshift", "altgr"). See `split_sequence` for `+`.
 pub fn str_to_keys(seq: &str) -> Result<(Vec<Key>, Key), String> {
     let mut mods = Vec::<Key>::new();
     let mut main: Option<Key> = None;
 
-    for part in seq.split('+') {
-        match part.to_ascii_lowercase().as_str() {
-            "ctrl" | "control" => {
-                mods.push(Key::Control);
-            }
-            "alt" => {
-                mods.push(Key::Alt);
+    for part in split_sequence(seq)? {
+        match parse_fragment(&part)? {
+            Fragment::Modifier(key) => mods.push(key),
+            Fragment::Main(key) => {
+                if main.is_some() {
+                    return Err(format!("More than one main key in {seq}"));
+                }
+                main = Some(key);
             }
-            "shift" => {
-                mods.push(Key::Shift);
+        }
+    }
+
+    let main = main.ok_or_else(|| "No main key found".to_string())?;
+
+    Ok((mods, main))
+}
+
+/// Longest single wait in a key sequence, so a typo can't block input for minutes
+const MAX_WAIT_MS: u64 = 10_000;
+
+/// One step of a scripted key sequence, e.g. `{"chord": "enter"}` or `{"wait_ms": 50}`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStep {
+    /// Press and release a key sequence in the `str_to_keys` grammar
+    Chord(String),
+    /// Type text as is
+    Text(String),
+    WaitMs(u64),
+    /// Keep a single key down until a matching `Release` or the end of the sequence
+    Hold(String),
+    Release(String),
+}
+
+/// What a validated key sequence sends, in order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyAction {
+    Press(Key),
+    Release(Key),
+    Click(Key),
+    Text(String),
+    Wait(Duration),
+}
+
+/// A single key name such as "shift", "rctrl" or "a"
+pub fn str_to_key(name: &str) -> Result<Key, String> {
+    let parts = split_sequence(name)?;
+    let part = match parts.as_slice() {
+        [part] => part,
+        _ => return Err(format!("Expected a single key, got {name}")),
+    };
+    match parse_fragment(part)? {
+        Fragment::Modifier(key) | Fragment::Main(key) => Ok(key),
+    }
+}
+
+/// Check every step and turn them into key actions; nothing is sent if any step is invalid
+///
+/// Keys still held at the end are released so a sequence never leaves a key stuck down.
+pub fn compile_key_steps(steps: &[KeyStep]) -> Result<Vec<KeyAction>, String> {
+    if steps.is_empty() {
+        return Err("Key sequence is empty".to_string());
+    }
+
+    let mut actions = Vec::new();
+    let mut held: Vec<Key> = Vec::new();
+    for (index, step) in steps.iter().enumerate() {
+        let at = |e: String| format!("Step {}: {}", index + 1, e);
+        match step {
+            KeyStep::Chord(seq) => {
+                let (mods, main) = str_to_keys(seq).map_err(at)?;
+                actions.extend(mods.iter().map(|key| KeyAction::Press(*key)));
+                actions.push(KeyAction::Click(main));
+                actions.extend(mods.iter().rev().map(|key| KeyAction::Release(*key)));
             }
-            "cmd" | "command" => {
-                mods.push(Key::Meta);
+            KeyStep::Text(text) => {
+                if text.is_empty() {
+                    return Err(at("Text is empty".to_string()));
+                }
+                actions.push(KeyAction::Text(text.clone()));
             }
-            k if k.len() == 1 => {
-                let ch = k.chars().next().unwrap();
-                main = Some(Key::Unicode(ch));
+            KeyStep::WaitMs(ms) => {
+                if *ms > MAX_WAIT_MS {
+                    return Err(at(format!("Wait of {ms} ms is over {MAX_WAIT_MS} ms")));
+                }
+                actions.push(KeyAction::Wait(Duration::from_millis(*ms)));
             }
-            k if k.starts_with('f') && k.len() <= 3 => {
-                if let Ok(n) = k[1..].parse::<u8>() {
-                    main = Some(match n {
-                        1 => Key::F1,
-                        2 => Key::F2,
-                        3 => Key::F3,
-                        4 => Key::F4,
-                        5 => Key::F5,
-                        6 => Key::F6,
-                        7 => Key::F7,
-                        8 => Key::F8,
-                        9 => Key::F9,
-                        10 => Key::F10,
-                        11 => Key::F11,
-                        12 => Key::F12,
-                        _ => {
-                            let err = format!("Unsupported function key: F{}", n);
-                            return Err(err);
-                        }
-                    });
+            KeyStep::Hold(name) => {
+                let key = str_to_key(name).map_err(at)?;
+                if held.contains(&key) {
+                    return Err(at(format!("{name} is already held")));
                 }
+                held.push(key);
+                actions.push(KeyAction::Press(key));
             }
-            _ => {
-                let err = format!("Unsupported fragment: {part}");
-                return Err(err);
+            KeyStep::Release(name) => {
+                let key = str_to_key(name).map_err(at)?;
+                let position = held
+                    .iter()
+                    .position(|held| *held == key)
+                    .ok_or_else(|| at(format!("{name} is not held")))?;
+                held.remove(position);
+                actions.push(KeyAction::Release(key));
             }
         }
     }
 
-    let main = main.ok_or_else(|| {
-        let err = "No main key found".to_string();
-        // eprintln!("[str_to_keys] Error: {}", err);
-        err
-    })?;
+    actions.extend(held.into_iter().rev().map(KeyAction::Release));
+    Ok(actions)
+}
 
-    Ok((mods, main))
+/// Where key actions are sent; `Enigo` in the app
+pub trait KeyOutput {
+    fn key(&mut self, key: Key, direction: Direction) -> Result<(), String>;
+    fn text(&mut self, text: &str) -> Result<(), String>;
 }
 
-pub fn press_key(sequence: String) -> Result<(), String> {
-    let (mods, main) = str_to_keys(&sequence)?;
-    let mut enigo =
-        Enigo::new(&Settings::default()).map_err(|e| format!("Failed to init Enigo: {:?}", e))?;
+impl KeyOutput for Enigo {
+    fn key(&mut self, key: Key, direction: Direction) -> Result<(), String> {
+        Keyboard::key(self, key, direction).map_err(|e| e.to_string())
+    }
+
+    fn text(&mut self, text: &str) -> Result<(), String> {
+        Keyboard::text(self, text).map_err(|e| e.to_string())
+    }
+}
+
+/// Send the actions in order, releasing whatever is still down if one of them fails
+pub fn run_key_actions(output: &mut impl KeyOutput, actions: &[KeyAction]) -> Result<(), String> {
+    let mut pressed: Vec<Key> = Vec::new();
+    let mut result = Ok(());
+    for action in actions {
+        result = match action {
+            KeyAction::Press(key) => output
+                .key(*key, Direction::Press)
+                .map(|_| pressed.push(*key)),
+            KeyAction::Release(key) => {
+                pressed.retain(|pressed| pressed != key);
+                output.key(*key, Direction::Release)
+            }
+            KeyAction::Click(key) => output.key(*key, Direction::Click),
+            KeyAction::Text(text) => output.text(text),
+            KeyAction::Wait(duration) => {
+                thread::sleep(*duration);
+                Ok(())
+            }
+        };
+        if result.is_err() {
+            break;
+        }
+    }
 
-    for m in &mods {
-        enigo.key(*m, Direction::Press).map_err(|e| e.to_string())?;
+    if result.is_err() {
+        for key in pressed.iter().rev() {
+            let _ = output.key(*key, Direction::Release);
+        }
     }
+    result
+}
+
+type InputJob = (Vec<KeyAction>, mpsc::Sender<Result<(), String>>);
 
-    enigo
-        .key(main, Direction::Click)
-        .map_err(|e| e.to_string())?;
+/// Every sequence is sent from this one thread, so two sequences never interleave
+static INPUT_THREAD: Lazy<Mutex<Option<mpsc::Sender<InputJob>>>> = Lazy::new(|| Mutex::new(None));
 
-    for m in mods.iter().rev() {
-        enigo
-            .key(*m, Direction::Release)
-            .map_err(|e| e.to_string())?;
+fn input_thread() -> Result<mpsc::Sender<InputJob>, String> {
+    let mut sender = INPUT_THREAD
+        .lock()
+        .map_err(|_| "Failed to lock input thread".to_string())?;
+    if let Some(sender) = sender.as_ref() {
+        return Ok(sender.clone());
     }
 
-    Ok(())
+    let (tx, rx) = mpsc::channel::<InputJob>();
+    thread::Builder::new()
+        .name("key-input".into())
+        .spawn(move || {
+            // Created on first use and kept, since connecting to the display is slow on Linux
+            let mut enigo: Option<Enigo> = None;
+            for (actions, reply) in rx {
+                let result = match enigo.as_mut() {
+                    Some(enigo) => run_key_actions(enigo, &actions),
+                    None => match Enigo::new(&Settings::default()) {
+                        Ok(created) => run_key_actions(enigo.insert(created), &actions),
+                        Err(e) => Err(format!("Failed to init Enigo: {:?}", e)),
+                    },
+                };
+                let _ = reply.send(result);
+            }
+        })
+        .map_err(|e| format!("Failed to start input thread: {}", e))?;
+    *sender = Some(tx.clone());
+    Ok(tx)
+}
+
+/// Validate a whole key sequence, then send it on the input thread and wait until it's done
+pub fn run_key_sequence(steps: &[KeyStep]) -> Result<(), String> {
+    let actions = compile_key_steps(steps)?;
+    let (reply_tx, reply_rx) = mpsc::channel();
+    let stopped = || {
+        // Let the next call start a fresh thread
+        if let Ok(mut sender) = INPUT_THREAD.lock() {
+            sender.take();
+        }
+        "Input thread stopped".to_string()
+    };
+    input_thread()?
+        .send((actions, reply_tx))
+        .map_err(|_| stopped())?;
+    reply_rx.recv().map_err(|_| stopped())?
+}
+
+pub fn press_key(sequence: String) -> Result<(), String> {
+    run_key_sequence(&[KeyStep::Chord(sequence)])
 }
 
 pub fn is_modifier(key: RdevKey) -> bool {
@@ -92,9 +454,12 @@ pub fn is_modifier(key: RdevKey) -> bool {
             | RdevKey::ShiftRight
             | RdevKey::Alt
             | RdevKey::AltGr
+            | RdevKey::MetaLeft
+            | RdevKey::MetaRight
     )
 }
 
+/// Name of a captured key in the grammar `str_to_keys` accepts
 pub fn key_to_string(key: RdevKey) -> Option<&'static str> {
     use RdevKey::*;
     match key {
@@ -136,11 +501,89 @@ pub fn key_to_string(key: RdevKey) -> Option<&'static str> {
         Num8 => Some("8"),
         Num9 => Some("9"),
 
-        ControlLeft => Some("ctrl"),
-        ControlRight => Some("ctrl"),
-        Alt => Some("alt"),
-        AltGr => Some("alt"),
+        BackQuote => Some("`"),
+        Minus => Some("-"),
+        Equal => Some("="),
+        LeftBracket => Some("["),
+        RightBracket => Some("]"),
+        SemiColon => Some(";"),
+        Quote => Some("'"),
+        BackSlash => Some("backslash"),
+        Comma => Some(","),
+        Dot => Some("."),
+        Slash => Some("/"),
+
+        F1 => Some("f1"),
+        F2 => Some("f2"),
+        F3 => Some("f3"),
+        F4 => Some("f4"),
+        F5 => Some("f5"),
+        F6 => Some("f6"),
+        F7 => Some("f7"),
+        F8 => Some("f8"),
+        F9 => Some("f9"),
+        F10 => Some("f10"),
+        F11 => Some("f11"),
+        F12 => Some("f12"),
+
+        Return => Some("enter"),
+        Escape => Some("escape"),
+        Tab => Some("tab"),
+        Space => Some("space"),
+        Backspace => Some("backspace"),
+        Delete => Some("delete"),
+        Insert => Some("insert"),
+        Home => Some("home"),
+        End => Some("end"),
+        PageUp => Some("pageup"),
+        PageDown => Some("pagedown"),
+        UpArrow => Some("up"),
+        DownArrow => Some("down"),
+        LeftArrow => Some("left"),
+        RightArrow => Some("right"),
+        CapsLock => Some("capslock"),
+        NumLock => Some("numlock"),
+        ScrollLock => Some("scrolllock"),
+        PrintScreen => Some("printscreen"),
+        Pause => Some("pause"),
+
+        Kp0 => Some("num0"),
+        Kp1 => Some("num1"),
+        Kp2 => Some("num2"),
+        Kp3 => Some("num3"),
+        Kp4 => Some("num4"),
+        Kp5 => Some("num5"),
+        Kp6 => Some("num6"),
+        Kp7 => Some("num7"),
+        Kp8 => Some("num8"),
+        Kp9 => Some("num9"),
+        KpPlus => Some("numadd"),
+        KpMinus => Some("numsub"),
+        KpMultiply => Some("nummul"),
+        KpDivide => Some("numdiv"),
+        KpDelete => Some("numdecimal"),
+        KpReturn => Some("numenter"),
+
+        ControlLeft => Some("lctrl"),
+        ControlRight => Some("rctrl"),
+        ShiftLeft => Some("lshift"),
+        ShiftRight => Some("rshift"),
+        Alt => Some("lalt"),
+        AltGr => Some("ralt"),
+        MetaLeft => Some("lcmd"),
+        MetaRight => Some("rcmd"),
 
         _ => None,
     }
 }
+
+/// Name of a captured modifier with the side left out, e.g. "ctrl" for either Control key
+pub fn modifier_name(key: RdevKey) -> Option<&'static str> {
+    match key {
+        RdevKey::ControlLeft | RdevKey::ControlRight => Some("ctrl"),
+        RdevKey::ShiftLeft | RdevKey::ShiftRight => Some("shift"),
+        RdevKey::Alt | RdevKey::AltGr => Some("alt"),
+        RdevKey::MetaLeft | RdevKey::MetaRight => Some("cmd"),
+        _ => None,
+    }
+}
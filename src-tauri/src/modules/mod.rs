@@ -1,7 +1,13 @@
 pub mod chat_watcher;
+pub mod cli;
 pub mod commands;
 pub mod config;
+pub mod game_chat;
+pub mod hotkeys;
+pub mod input_capture;
 pub mod keyboard;
+pub mod player_registry;
 pub mod system;
+pub mod trade_history;
 pub mod webview;
 pub mod window;
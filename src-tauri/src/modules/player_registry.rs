@@ -0,0 +1,179 @@
+use crate::chat_watcher::{ChatLine, ChatPlayer};
+use rusqlite::{params, Connection, Row};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// A character seen playing on an account
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownCharacter {
+    pub account: String,
+    pub character: String,
+    /// Unix time in milliseconds when the pair was first seen
+    pub first_seen: u64,
+    /// Unix time in milliseconds when the pair was last seen
+    pub last_seen: u64,
+    /// Number of log lines the pair appeared in
+    pub times_seen: u64,
+}
+
+/// Persistent account -> character mapping learned from whispers and join/leave lines
+#[derive(Clone)]
+pub struct PlayerRegistry {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl PlayerRegistry {
+    /// Open (or create) the registry database at the given path
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create player registry directory: {}", e))?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Failed to open player registry database: {}", e))?;
+        Self::init(conn)
+    }
+
+    /// Registry that lives only as long as the process, used when the database can't be opened
+    pub fn in_memory() -> Result<Self, String> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| format!("Failed to open in-memory player registry: {}", e))?;
+        Self::init(conn)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS known_characters (
+                account TEXT NOT NULL COLLATE NOCASE,
+                character TEXT NOT NULL COLLATE NOCASE,
+                first_seen INTEGER NOT NULL,
+                last_seen INTEGER NOT NULL,
+                times_seen INTEGER NOT NULL,
+                PRIMARY KEY (account, character)
+            );
+            CREATE INDEX IF NOT EXISTS idx_known_characters_character ON known_characters (character);",
+        )
+        .map_err(|e| format!("Failed to initialize player registry: {}", e))?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Remember the character/account pair of a whisper, join or leave line
+    /// Lines without an account name teach us nothing and are ignored
+    pub fn record(&self, chat_line: &ChatLine, timestamp: u64) -> Result<(), String> {
+        // Names are matched case-insensitively; keep the most recently seen spelling
+        self.upsert(
+            chat_line,
+            timestamp,
+            "ON CONFLICT (account, character) DO UPDATE SET
+                account = excluded.account,
+                character = excluded.character,
+                first_seen = MIN(first_seen, excluded.first_seen),
+                last_seen = MAX(last_seen, excluded.last_seen),
+                times_seen = times_seen + 1",
+        )
+    }
+
+    /// Like `record`, for lines replayed from the log on watcher start
+    /// Replayed lines were most likely recorded on an earlier run, so only unknown pairs are added
+    pub fn record_replayed(&self, chat_line: &ChatLine, timestamp: u64) -> Result<(), String> {
+        self.upsert(
+            chat_line,
+            timestamp,
+            "ON CONFLICT (account, character) DO NOTHING",
+        )
+    }
+
+    fn upsert(
+        &self,
+        chat_line: &ChatLine,
+        timestamp: u64,
+        on_conflict: &str,
+    ) -> Result<(), String> {
+        let (account, character) = match identity(chat_line) {
+            Some(identity) => identity,
+            None => return Ok(()),
+        };
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Failed to lock player registry".to_string())?;
+        conn.execute(
+            &format!(
+                "INSERT INTO known_characters (account, character, first_seen, last_seen, times_seen)
+                 VALUES (?1, ?2, ?3, ?3, 1) {}",
+                on_conflict
+            ),
+            params![account, character, timestamp as i64],
+        )
+        .map_err(|e| format!("Failed to record player: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Every character seen on an account, most recently seen first
+    pub fn characters(&self, account: &str) -> Result<Vec<KnownCharacter>, String> {
+        self.select(
+            "SELECT account, character, first_seen, last_seen, times_seen FROM known_characters
+             WHERE account = ?1 ORDER BY last_seen DESC, character",
+            account,
+        )
+    }
+
+    /// Accounts a character name has been seen on, most recently seen first
+    pub fn accounts(&self, character: &str) -> Result<Vec<KnownCharacter>, String> {
+        self.select(
+            "SELECT account, character, first_seen, last_seen, times_seen FROM known_characters
+             WHERE character = ?1 ORDER BY last_seen DESC, account",
+            character,
+        )
+    }
+
+    fn select(&self, sql: &str, name: &str) -> Result<Vec<KnownCharacter>, String> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Failed to lock player registry".to_string())?;
+        let mut stmt = conn
+            .prepare(sql)
+            .map_err(|e| format!("Failed to query player registry: {}", e))?;
+        let rows = stmt
+            .query_map(params![name], character_from_row)
+            .map_err(|e| format!("Failed to query player registry: {}", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read player registry: {}", e))
+    }
+}
+
+/// Account and character of the lines that carry both
+fn identity(chat_line: &ChatLine) -> Option<(&str, &str)> {
+    let player: &ChatPlayer = match chat_line {
+        ChatLine::WhisperIn { player, .. }
+        | ChatLine::WhisperOut { player, .. }
+        | ChatLine::Join { player, .. }
+        | ChatLine::Leave { player, .. } => player,
+        _ => return None,
+    };
+    let account = player.account.as_deref()?;
+    let character = player.character.as_str();
+    (!character.is_empty()).then_some((account, character))
+}
+
+fn character_from_row(row: &Row) -> rusqlite::Result<KnownCharacter> {
+    let first_seen: i64 = row.get(2)?;
+    let last_seen: i64 = row.get(3)?;
+    let times_seen: i64 = row.get(4)?;
+    Ok(KnownCharacter {
+        account: row.get(0)?,
+        character: row.get(1)?,
+        first_seen: first_seen.max(0) as u64,
+        last_seen: last_seen.max(0) as u64,
+        times_seen: times_seen.max(0) as u64,
+    })
+}
@@ -0,0 +1,295 @@
+use crate::chat_watcher::{ChatLine, ChatPlayer, TradeMessageEvent};
+use rusqlite::{params, params_from_iter, Connection, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Default number of rows returned by a query without an explicit limit
+const DEFAULT_QUERY_LIMIT: u32 = 500;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TradeDirection {
+    Incoming,
+    Outgoing,
+}
+
+/// What produced a history row: a trade whisper, any other whisper, or a "joined our world" line
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TradeHistoryKind {
+    Trade,
+    Whisper,
+    Join,
+}
+
+impl TradeHistoryKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            TradeHistoryKind::Trade => "trade",
+            TradeHistoryKind::Whisper => "whisper",
+            TradeHistoryKind::Join => "join",
+        }
+    }
+
+    fn from_str(kind: &str) -> Self {
+        match kind {
+            "trade" => TradeHistoryKind::Trade,
+            "join" => TradeHistoryKind::Join,
+            _ => TradeHistoryKind::Whisper,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeHistoryEntry {
+    pub id: i64,
+    pub kind: TradeHistoryKind,
+    /// Unix time in milliseconds when the line was read
+    pub timestamp: u64,
+    pub direction: TradeDirection,
+    pub player_name: String,
+    pub account_name: Option<String>,
+    pub character_name: Option<String>,
+    pub message: String,
+    pub item_name: Option<String>,
+    pub price: Option<String>,
+}
+
+/// Filters for `TradeHistory::query`; every field is optional and they combine with AND
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeHistoryQuery {
+    /// Account or character name, case-insensitive
+    pub player: Option<String>,
+    /// Substring of the item name, case-insensitive
+    pub item: Option<String>,
+    /// Inclusive lower bound, unix milliseconds
+    pub from: Option<u64>,
+    /// Inclusive upper bound, unix milliseconds
+    pub to: Option<u64>,
+    pub direction: Option<TradeDirection>,
+    pub kind: Option<TradeHistoryKind>,
+    pub limit: Option<u32>,
+    /// Matching rows to skip, for paging through results `limit` at a time
+    pub offset: Option<u32>,
+}
+
+/// Persistent store of trade whispers, backed by SQLite in the app data directory
+#[derive(Clone)]
+pub struct TradeHistory {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl TradeHistory {
+    /// Open (or create) the history database at the given path
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create trade history directory: {}", e))?;
+        }
+        let conn = Connection::open(path)
+            .map_err(|e| format!("Failed to open trade history database: {}", e))?;
+        Self::init(conn)
+    }
+
+    /// History that lives only as long as the process, used when the database can't be opened
+    pub fn in_memory() -> Result<Self, String> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| format!("Failed to open in-memory trade history: {}", e))?;
+        Self::init(conn)
+    }
+
+    fn init(conn: Connection) -> Result<Self, String> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS trade_messages (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                kind TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                is_incoming INTEGER NOT NULL,
+                player_name TEXT NOT NULL,
+                account_name TEXT,
+                character_name TEXT,
+                message TEXT NOT NULL,
+                item_name TEXT,
+                price TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_trade_messages_timestamp ON trade_messages (timestamp);
+            CREATE INDEX IF NOT EXISTS idx_trade_messages_player ON trade_messages (player_name COLLATE NOCASE);
+            CREATE INDEX IF NOT EXISTS idx_trade_messages_item ON trade_messages (item_name COLLATE NOCASE);",
+        )
+        .map_err(|e| format!("Failed to initialize trade history: {}", e))?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    /// Store a parsed chat line if it is a whisper or join; other lines are ignored
+    /// `trade` is the trade message parsed from the same line, if it is one
+    pub fn record(
+        &self,
+        chat_line: &ChatLine,
+        trade: Option<&TradeMessageEvent>,
+        timestamp: u64,
+    ) -> Result<(), String> {
+        let (kind, is_incoming, player, message) = match recorded_fields(chat_line) {
+            Some(fields) => fields,
+            None => return Ok(()),
+        };
+
+        let kind = if trade.is_some() {
+            TradeHistoryKind::Trade
+        } else {
+            kind
+        };
+        let (item_name, price) = trade
+            .map(|trade| (trade.item_name.clone(), trade.price.clone()))
+            .unwrap_or_default();
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Failed to lock trade history".to_string())?;
+        conn.execute(
+            "INSERT INTO trade_messages
+                (kind, timestamp, is_incoming, player_name, account_name, character_name, message, item_name, price)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                kind.as_str(),
+                timestamp as i64,
+                is_incoming,
+                player.name(),
+                player.account,
+                player.character,
+                message,
+                item_name,
+                price,
+            ],
+        )
+        .map_err(|e| format!("Failed to record trade message: {}", e))?;
+
+        Ok(())
+    }
+
+    /// How many rows with the same direction, sender and text as `chat_line` were stored since `since`
+    pub fn count_recorded(&self, chat_line: &ChatLine, since: u64) -> Result<usize, String> {
+        let (_, is_incoming, player, message) = match recorded_fields(chat_line) {
+            Some(fields) => fields,
+            None => return Ok(0),
+        };
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Failed to lock trade history".to_string())?;
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM trade_messages
+                 WHERE timestamp >= ?1 AND is_incoming = ?2 AND player_name = ?3 AND message = ?4",
+                params![since as i64, is_incoming, player.name(), message],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to query trade history: {}", e))?;
+
+        Ok(count.max(0) as usize)
+    }
+
+    /// Newest-first rows matching every filter set in `query`
+    pub fn query(&self, query: &TradeHistoryQuery) -> Result<Vec<TradeHistoryEntry>, String> {
+        let mut sql = String::from(
+            "SELECT id, kind, timestamp, is_incoming, player_name, account_name, character_name, message, item_name, price
+             FROM trade_messages WHERE 1 = 1",
+        );
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+
+        if let Some(player) = &query.player {
+            sql.push_str(
+                " AND (account_name = ? COLLATE NOCASE OR character_name = ? COLLATE NOCASE)",
+            );
+            values.push(player.clone().into());
+            values.push(player.clone().into());
+        }
+        if let Some(item) = &query.item {
+            sql.push_str(" AND item_name LIKE ? ESCAPE '\\'");
+            values.push(format!("%{}%", escape_like(item)).into());
+        }
+        if let Some(from) = query.from {
+            sql.push_str(" AND timestamp >= ?");
+            values.push((from as i64).into());
+        }
+        if let Some(to) = query.to {
+            sql.push_str(" AND timestamp <= ?");
+            values.push((to as i64).into());
+        }
+        if let Some(direction) = query.direction {
+            sql.push_str(" AND is_incoming = ?");
+            values.push((direction == TradeDirection::Incoming).into());
+        }
+        if let Some(kind) = query.kind {
+            sql.push_str(" AND kind = ?");
+            values.push(kind.as_str().to_string().into());
+        }
+        sql.push_str(" ORDER BY timestamp DESC, id DESC LIMIT ? OFFSET ?");
+        values.push(query.limit.unwrap_or(DEFAULT_QUERY_LIMIT).into());
+        values.push(query.offset.unwrap_or(0).into());
+
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| "Failed to lock trade history".to_string())?;
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| format!("Failed to query trade history: {}", e))?;
+        let rows = stmt
+            .query_map(params_from_iter(values), entry_from_row)
+            .map_err(|e| format!("Failed to query trade history: {}", e))?;
+
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to read trade history: {}", e))
+    }
+}
+
+/// Kind, direction, sender and text of the lines that end up in the history
+fn recorded_fields(chat_line: &ChatLine) -> Option<(TradeHistoryKind, bool, &ChatPlayer, &str)> {
+    match chat_line {
+        ChatLine::WhisperIn { player, message } => {
+            Some((TradeHistoryKind::Whisper, true, player, message))
+        }
+        ChatLine::WhisperOut { player, message } => {
+            Some((TradeHistoryKind::Whisper, false, player, message))
+        }
+        ChatLine::Join { player, message } => Some((TradeHistoryKind::Join, true, player, message)),
+        _ => None,
+    }
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<TradeHistoryEntry> {
+    let kind: String = row.get(1)?;
+    let timestamp: i64 = row.get(2)?;
+    let is_incoming: bool = row.get(3)?;
+    Ok(TradeHistoryEntry {
+        id: row.get(0)?,
+        kind: TradeHistoryKind::from_str(&kind),
+        timestamp: timestamp.max(0) as u64,
+        direction: if is_incoming {
+            TradeDirection::Incoming
+        } else {
+            TradeDirection::Outgoing
+        },
+        player_name: row.get(4)?,
+        account_name: row.get(5)?,
+        character_name: row.get(6)?,
+        message: row.get(7)?,
+        item_name: row.get(8)?,
+        price: row.get(9)?,
+    })
+}
+
+/// Escape LIKE wildcards so item names containing "%" or "_" match literally
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
@@ -2,7 +2,9 @@ use super::WindowRect;
 use std::error::Error;
 use tauri::{AppHandle, Emitter};
 use x11rb::connection::Connection;
-use x11rb::protocol::xproto::{AtomEnum, ConnectionExt, Window};
+use x11rb::protocol::xproto::{
+    AtomEnum, ClientMessageEvent, ConnectionExt, EventMask, InputFocus, Window,
+};
 
 // Use a persistent connection to avoid reconnecting on every call
 use once_cell::sync::Lazy;
@@ -142,6 +144,77 @@ pub fn get_diablo_rect(_app: &AppHandle) -> Option<WindowRect> {
     .flatten()
 }
 
+/// True only when the Diablo II window itself is active, unlike `is_diablo_focused`
+///
+/// Under Wayland `_NET_ACTIVE_WINDOW` keeps naming the last X window while a native Wayland
+/// window has the keyboard, so the X input focus must be on Diablo II as well; XWayland
+/// drops it when keyboard focus leaves its windows.
+pub fn is_diablo_foreground() -> bool {
+    with_connection(|conn, screen_num| -> Result<bool, Box<dyn Error>> {
+        let Some(diablo) = find_diablo_window(conn)? else {
+            return Ok(false);
+        };
+        let root = conn.setup().roots[screen_num].root;
+        let net_active_window = get_atom(conn, "_NET_ACTIVE_WINDOW")?;
+        let active = get_property_u32(conn, root, net_active_window, AtomEnum::WINDOW.into())?
+            .and_then(|v| v.first().copied());
+        if active != Some(diablo) {
+            return Ok(false);
+        }
+
+        let focus = conn.get_input_focus()?.reply()?.focus;
+        Ok(focus == diablo
+            || ancestors(conn, focus)?.contains(&diablo)
+            || ancestors(conn, diablo)?.contains(&focus))
+    })
+    .and_then(Result::ok)
+    .unwrap_or(false)
+}
+
+/// Parents of a window up to (not including) the root; none for the None and PointerRoot focus values
+fn ancestors(conn: &impl Connection, window: Window) -> Result<Vec<Window>, Box<dyn Error>> {
+    let mut ancestors = Vec::new();
+    if window == x11rb::NONE || window == u32::from(InputFocus::POINTER_ROOT) {
+        return Ok(ancestors);
+    }
+    let mut window = window;
+    loop {
+        let tree = conn.query_tree(window)?.reply()?;
+        if tree.parent == x11rb::NONE || tree.parent == tree.root {
+            return Ok(ancestors);
+        }
+        ancestors.push(tree.parent);
+        window = tree.parent;
+    }
+}
+
+/// Asks the window manager to activate the Diablo II window
+pub fn activate_diablo_window() -> Result<(), String> {
+    with_connection(|conn, screen_num| -> Result<(), Box<dyn Error>> {
+        let window = find_diablo_window(conn)?.ok_or("Diablo II window not found")?;
+        let root = conn.setup().roots[screen_num].root;
+        let net_active_window = get_atom(conn, "_NET_ACTIVE_WINDOW")?;
+
+        // Source indication 2 (pager) so focus stealing prevention lets the request through
+        let event = ClientMessageEvent::new(
+            32,
+            window,
+            net_active_window,
+            [2, x11rb::CURRENT_TIME, 0, 0, 0],
+        );
+        conn.send_event(
+            false,
+            root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )?;
+        conn.flush()?;
+        Ok(())
+    })
+    .ok_or_else(|| "No X11 connection".to_string())?
+    .map_err(|e| e.to_string())
+}
+
 pub fn is_diablo_focused() -> bool {
     with_connection(|conn, screen_num| {
         let screen = &conn.setup().roots[screen_num];
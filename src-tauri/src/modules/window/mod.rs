@@ -165,3 +165,21 @@ pub fn start_tracking_thread(app: AppHandle) {
         }
     });
 }
+
+/// How long `focus_diablo` waits for the window manager to hand over focus
+const FOCUS_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(1000);
+
+/// Brings the Diablo II window to the front and waits until it has keyboard focus,
+/// so keys sent afterwards land in the game rather than in one of our windows
+pub fn focus_diablo() -> Result<(), String> {
+    activate_diablo_window()?;
+
+    let deadline = std::time::Instant::now() + FOCUS_TIMEOUT;
+    while !is_diablo_foreground() {
+        if std::time::Instant::now() >= deadline {
+            return Err("Diablo II did not take focus".to_string());
+        }
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+    Ok(())
+}
@@ -8,7 +8,8 @@ use windows_sys::Win32::{
     UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
     UI::WindowsAndMessaging::{
         FindWindowW, GetCursorPos, GetForegroundWindow, GetWindowRect, GetWindowThreadProcessId,
-        SystemParametersInfoW, EVENT_SYSTEM_FOREGROUND, SPI_GETWORKAREA, WINEVENT_OUTOFCONTEXT,
+        IsIconic, SetForegroundWindow, ShowWindow, SystemParametersInfoW, EVENT_SYSTEM_FOREGROUND,
+        SPI_GETWORKAREA, SW_RESTORE, WINEVENT_OUTOFCONTEXT,
     },
 };
 
@@ -68,6 +69,32 @@ pub fn is_diablo_focused() -> bool {
     false
 }
 
+/// True only when the Diablo II window itself is in the foreground, unlike `is_diablo_focused`
+pub fn is_diablo_foreground() -> bool {
+    let title_w = to_wide("Diablo II");
+    let hwnd: HWND = unsafe { FindWindowW(ptr::null(), title_w.as_ptr()) };
+    hwnd != 0 && hwnd == unsafe { GetForegroundWindow() }
+}
+
+/// Asks Windows to bring the Diablo II window to the front, restoring it if minimized
+pub fn activate_diablo_window() -> Result<(), String> {
+    let title_w = to_wide("Diablo II");
+    let hwnd: HWND = unsafe { FindWindowW(ptr::null(), title_w.as_ptr()) };
+    if hwnd == 0 {
+        return Err("Diablo II window not found".to_string());
+    }
+
+    unsafe {
+        if IsIconic(hwnd) != 0 {
+            ShowWindow(hwnd, SW_RESTORE);
+        }
+        if SetForegroundWindow(hwnd) == 0 {
+            return Err("Failed to bring Diablo II to the front".to_string());
+        }
+    }
+    Ok(())
+}
+
 pub fn get_work_area(_app: &AppHandle) -> Option<WindowRect> {
     let mut work_area = RECT {
         left: 0,
@@ -0,0 +1,117 @@
+//! Golden tests for the chat log parsers
+//!
+//! Every `*.log` file in `tests/fixtures/chat_log` is split into lines (line endings kept
+//! as written) and each line is run through `parse_whisper` and `parse_trade_message`.
+//! The results must match the entry at the same index in the sibling `*.expected.json`.
+
+use dmg_meter_widget_lib::chat_watcher::{
+    parse_chat_line, parse_trade_message, parse_whisper, ChatLine,
+};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("chat_log")
+}
+
+#[test]
+fn chat_log_fixtures_match_golden_output() {
+    let mut checked = 0;
+
+    for entry in fs::read_dir(fixtures_dir()).expect("fixtures directory") {
+        let log_path = entry.expect("fixture entry").path();
+        if log_path.extension().and_then(|e| e.to_str()) != Some("log") {
+            continue;
+        }
+
+        let expected_path = log_path.with_extension("expected.json");
+        let log = fs::read_to_string(&log_path).expect("read fixture log");
+        let expected: Vec<Value> =
+            serde_json::from_str(&fs::read_to_string(&expected_path).expect("read golden output"))
+                .expect("parse golden output");
+
+        let lines: Vec<&str> = log.split_inclusive('\n').collect();
+        assert_eq!(
+            lines.len(),
+            expected.len(),
+            "{} has {} lines but {} golden entries",
+            log_path.display(),
+            lines.len(),
+            expected.len()
+        );
+
+        for (index, (line, expected)) in lines.iter().zip(&expected).enumerate() {
+            let actual = json!({
+                "whisper": parse_whisper(line),
+                "trade": parse_trade_message(line),
+            });
+            assert_eq!(
+                &actual,
+                expected,
+                "{}:{} {:?}",
+                log_path.display(),
+                index + 1,
+                line
+            );
+            checked += 1;
+        }
+    }
+
+    assert!(checked > 0, "no chat log fixtures found");
+}
+
+#[test]
+fn whisper_and_trade_agree_on_sender() {
+    let line = "2,From shrackx (*shrack): Hi, I'm interested in your Frostburn listed for 2 wss";
+    let whisper = parse_whisper(line).expect("whisper");
+    let trade = parse_trade_message(line).expect("trade");
+
+    assert_eq!(whisper.from, trade.player_name);
+    assert_eq!(whisper.item_name, trade.item_name);
+}
+
+#[test]
+fn unrelated_lines_are_ignored() {
+    for line in ["", "\r\n", "1,", "5,no colon here", "not a log line"] {
+        assert!(parse_whisper(line).is_none(), "{:?}", line);
+        assert!(parse_trade_message(line).is_none(), "{:?}", line);
+    }
+}
+
+#[test]
+fn friend_notices_come_from_a_bare_account() {
+    assert_eq!(
+        parse_chat_line("2,From *pal: Your friend palchar has entered Project Diablo 2."),
+        Some(ChatLine::FriendOnline {
+            name: "palchar".to_string()
+        })
+    );
+    assert_eq!(
+        parse_chat_line("2,From *pal: Your friend palchar has left Project Diablo 2."),
+        Some(ChatLine::FriendOffline {
+            name: "palchar".to_string()
+        })
+    );
+
+    // Players can type the notice text, but only as a whisper from their own character
+    for line in [
+        "2,From scammer (*scam): Your friend palchar has entered Project Diablo 2.",
+        "2,From scammer: Your friend palchar has entered Project Diablo 2.",
+        "2,From *scam: wtb? Your friend palchar has entered Project Diablo 2.",
+        "2,From *scam: Your friend palchar has entered Project Diablo 2. Trade me!",
+    ] {
+        assert!(
+            matches!(parse_chat_line(line), Some(ChatLine::WhisperIn { .. })),
+            "{:?}",
+            line
+        );
+    }
+    assert!(matches!(
+        parse_chat_line("2,Sent to *pal: Your friend palchar has entered Project Diablo 2."),
+        Some(ChatLine::WhisperOut { .. })
+    ));
+}
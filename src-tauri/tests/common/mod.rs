@@ -0,0 +1,151 @@
+//! Helpers shared by the integration tests
+
+// Each test binary uses only some of these
+#![allow(dead_code)]
+
+use dmg_meter_widget_lib::chat_watcher::{ChatWatcher, EventSink, EventSinks, SinkEvent};
+use dmg_meter_widget_lib::hotkeys::ShortcutBackend;
+use dmg_meter_widget_lib::keyboard::KeyOutput;
+use enigo::{Direction, Key};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Records every event it is sent
+pub struct Collect(pub Arc<Mutex<Vec<SinkEvent>>>);
+
+impl EventSink for Collect {
+    fn send(&mut self, event: &SinkEvent) -> Result<(), String> {
+        self.0.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+}
+
+/// A watcher whose `events` are collected for the test to inspect
+pub fn collecting_watcher(events: &[&str]) -> (ChatWatcher, Arc<Mutex<Vec<SinkEvent>>>) {
+    let collected = Arc::new(Mutex::new(Vec::new()));
+    let mut sinks = EventSinks::default();
+    sinks.add(
+        Box::new(Collect(collected.clone())),
+        events.iter().map(|event| event.to_string()).collect(),
+    );
+    let watcher = ChatWatcher::default();
+    watcher.set_event_sinks(sinks).expect("sinks");
+    (watcher, collected)
+}
+
+/// Collected events once there are `count` of them, or whatever arrived within 10 seconds
+pub fn wait_for(events: &Arc<Mutex<Vec<SinkEvent>>>, count: usize) -> Vec<SinkEvent> {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if events.lock().unwrap().len() >= count {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    events.lock().unwrap().clone()
+}
+
+/// An empty Diablo II directory for one test
+pub fn d2_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pd2-trader-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create d2 dir");
+    dir
+}
+
+/// Stands in for the keyboard: records what would be sent, in order
+#[derive(Default)]
+pub struct KeyRecorder {
+    sent: Vec<String>,
+    fail_at: Option<usize>,
+}
+
+impl KeyRecorder {
+    /// Fail the call that would add entry `index` to the log, once
+    pub fn failing_at(index: usize) -> Self {
+        Self {
+            fail_at: Some(index),
+            ..Self::default()
+        }
+    }
+
+    fn record(&mut self, entry: String) -> Result<(), String> {
+        if self.fail_at == Some(self.sent.len()) {
+            self.fail_at = None;
+            return Err("display went away".to_string());
+        }
+        self.sent.push(entry);
+        Ok(())
+    }
+
+    pub fn log(&self) -> &[String] {
+        &self.sent
+    }
+}
+
+impl KeyOutput for KeyRecorder {
+    fn key(&mut self, key: Key, direction: Direction) -> Result<(), String> {
+        self.record(format!("{:?} {:?}", direction, key))
+    }
+
+    fn text(&mut self, text: &str) -> Result<(), String> {
+        self.record(format!("Text {}", text))
+    }
+}
+
+/// Stands in for the OS shortcut API: records what is registered, refusing sequences in `taken`
+#[derive(Clone, Default)]
+pub struct ShortcutRecorder {
+    registered: Arc<Mutex<Vec<String>>>,
+    log: Arc<Mutex<Vec<String>>>,
+    taken: Vec<String>,
+    unsupported: Vec<String>,
+}
+
+impl ShortcutRecorder {
+    /// Refuse to register these shortcuts, as if another app had them
+    pub fn taken(mut self, sequences: &[&str]) -> Self {
+        self.taken = sequences.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// Fail the backend check for these shortcuts, as if it couldn't parse them
+    pub fn unsupported(mut self, sequences: &[&str]) -> Self {
+        self.unsupported = sequences.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    pub fn registered(&self) -> Vec<String> {
+        self.registered.lock().unwrap().clone()
+    }
+
+    pub fn take_log(&self) -> Vec<String> {
+        std::mem::take(&mut self.log.lock().unwrap())
+    }
+}
+
+impl ShortcutBackend for ShortcutRecorder {
+    fn check(&self, sequence: &str) -> Result<(), String> {
+        if self.unsupported.iter().any(|s| s == sequence) {
+            return Err(format!("Can't use {sequence} as a global shortcut"));
+        }
+        Ok(())
+    }
+
+    fn register(&mut self, sequence: &str) -> Result<u32, String> {
+        if self.taken.iter().any(|taken| taken == sequence) {
+            return Err(format!("{sequence} is already registered"));
+        }
+        self.log.lock().unwrap().push(format!("+{sequence}"));
+        let mut registered = self.registered.lock().unwrap();
+        registered.push(sequence.to_string());
+        Ok(100 + registered.len() as u32)
+    }
+
+    fn unregister(&mut self, sequence: &str) -> Result<(), String> {
+        self.log.lock().unwrap().push(format!("-{sequence}"));
+        self.registered.lock().unwrap().retain(|s| s != sequence);
+        Ok(())
+    }
+}
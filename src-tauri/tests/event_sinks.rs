@@ -0,0 +1,292 @@
+//! Tests for forwarding parsed chat events to webhook and file sinks
+
+use dmg_meter_widget_lib::chat_watcher::sinks::{FileSink, WebhookSink};
+use dmg_meter_widget_lib::chat_watcher::{
+    EventSink, EventSinks, RetryPolicy, SinkConfig, SinkEvent, SinkKind,
+};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// HTTP server on a free local port that answers with `statuses` in turn (200 once they run out)
+/// and records every request body
+fn stub_server(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    let url = format!("http://{}/hook", listener.local_addr().expect("addr"));
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+
+    let received = bodies.clone();
+    thread::spawn(move || {
+        let mut statuses = statuses.into_iter();
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => break,
+            };
+            let mut reader = BufReader::new(stream.try_clone().expect("clone"));
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                if reader.read_line(&mut header).unwrap_or(0) == 0 || header == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap_or(0);
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).expect("body");
+            received
+                .lock()
+                .unwrap()
+                .push(String::from_utf8(body).expect("utf8"));
+
+            let status = statuses.next().unwrap_or(200);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {} Stub\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            );
+        }
+    });
+
+    (url, bodies)
+}
+
+fn fast_retry(max_retries: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_retries,
+        initial_backoff_ms: 1,
+        max_backoff_ms: 5,
+        timeout_ms: 2_000,
+    }
+}
+
+fn event(name: &str) -> SinkEvent {
+    SinkEvent {
+        event: name.to_string(),
+        timestamp: 1_000,
+        payload: json!({ "message": "hi" }),
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("pd2-trader-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn read_lines(path: &PathBuf) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(path)
+        .expect("read")
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("json line"))
+        .collect()
+}
+
+#[test]
+fn webhook_posts_the_event_as_json() {
+    let (url, bodies) = stub_server(Vec::new());
+    let mut sink = WebhookSink::new(&url, fast_retry(0)).expect("sink");
+    sink.send(&event("trade-message")).expect("send");
+
+    let bodies = bodies.lock().unwrap();
+    assert_eq!(bodies.len(), 1);
+    let body: SinkEvent = serde_json::from_str(&bodies[0]).expect("json");
+    assert_eq!(body, event("trade-message"));
+}
+
+#[test]
+fn webhook_retries_server_errors() {
+    let (url, bodies) = stub_server(vec![500, 503, 429]);
+    let mut sink = WebhookSink::new(&url, fast_retry(3)).expect("sink");
+    sink.send(&event("whisper-received")).expect("send");
+    assert_eq!(bodies.lock().unwrap().len(), 4);
+}
+
+#[test]
+fn webhook_gives_up_after_max_retries() {
+    let (url, bodies) = stub_server(vec![500, 500, 500, 500]);
+    let mut sink = WebhookSink::new(&url, fast_retry(2)).expect("sink");
+    let error = sink.send(&event("whisper-received")).unwrap_err();
+    assert!(error.contains("500"), "{}", error);
+    assert_eq!(bodies.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn webhook_does_not_retry_client_errors() {
+    let (url, bodies) = stub_server(vec![404]);
+    let mut sink = WebhookSink::new(&url, fast_retry(3)).expect("sink");
+    assert!(sink.send(&event("chat-line")).is_err());
+    assert_eq!(bodies.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn webhook_retries_connection_failures() {
+    // Grab a free port, then close it so nothing is listening
+    let port = TcpListener::bind("127.0.0.1:0")
+        .expect("bind")
+        .local_addr()
+        .expect("addr")
+        .port();
+    let mut sink =
+        WebhookSink::new(&format!("http://127.0.0.1:{}/hook", port), fast_retry(1)).expect("sink");
+    assert!(sink.send(&event("chat-line")).is_err());
+}
+
+#[test]
+fn webhook_rejects_non_http_urls() {
+    assert!(WebhookSink::new("ftp://example.com", RetryPolicy::default()).is_err());
+}
+
+#[test]
+fn backoff_doubles_up_to_the_cap() {
+    let retry = RetryPolicy {
+        initial_backoff_ms: 100,
+        max_backoff_ms: 350,
+        ..RetryPolicy::default()
+    };
+    assert_eq!(retry.backoff(1), Duration::from_millis(100));
+    assert_eq!(retry.backoff(2), Duration::from_millis(200));
+    assert_eq!(retry.backoff(3), Duration::from_millis(350));
+    assert_eq!(retry.backoff(60), Duration::from_millis(350));
+}
+
+#[test]
+fn file_sink_appends_json_lines() {
+    let path = temp_path("events.jsonl");
+    FileSink::open(&path)
+        .expect("open")
+        .send(&event("trade-message"))
+        .expect("send");
+    // Reopening appends rather than truncating
+    FileSink::open(&path)
+        .expect("open")
+        .send(&event("chat-line"))
+        .expect("send");
+
+    let lines = read_lines(&path);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["event"], "trade-message");
+    assert_eq!(lines[1]["event"], "chat-line");
+    assert_eq!(lines[1]["payload"]["message"], "hi");
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn dispatch_forwards_only_the_wanted_events() {
+    let all = temp_path("all-events.jsonl");
+    let trades = temp_path("trade-events.jsonl");
+    let (url, bodies) = stub_server(Vec::new());
+
+    let sinks = EventSinks::new(vec![
+        SinkConfig {
+            kind: SinkKind::File { path: all.clone() },
+            events: Vec::new(),
+        },
+        SinkConfig {
+            kind: SinkKind::File {
+                path: trades.clone(),
+            },
+            events: vec!["trade-message".to_string()],
+        },
+        SinkConfig {
+            kind: SinkKind::Webhook {
+                url,
+                retry: fast_retry(0),
+            },
+            events: vec!["trade-message".to_string()],
+        },
+    ])
+    .expect("sinks");
+
+    sinks.dispatch("chat-line", &json!({ "n": 1 }), 1);
+    sinks.dispatch("trade-message", &json!({ "n": 2 }), 2);
+    // Dropping waits for every queued event to be delivered
+    drop(sinks);
+
+    assert_eq!(read_lines(&all).len(), 2);
+    let lines = read_lines(&trades);
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["payload"]["n"], 2);
+    assert_eq!(lines[0]["timestamp"], 2);
+    assert_eq!(bodies.lock().unwrap().len(), 1);
+
+    let _ = std::fs::remove_file(&all);
+    let _ = std::fs::remove_file(&trades);
+}
+
+#[test]
+fn custom_sinks_can_be_added() {
+    struct Collect(Arc<Mutex<Vec<String>>>);
+    impl EventSink for Collect {
+        fn send(&mut self, event: &SinkEvent) -> Result<(), String> {
+            self.0.lock().unwrap().push(event.event.clone());
+            Ok(())
+        }
+    }
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut sinks = EventSinks::default();
+    assert!(sinks.is_empty());
+    sinks.add(Box::new(Collect(seen.clone())), Vec::new());
+    sinks.dispatch("whisper-received", &json!({}), 0);
+    drop(sinks);
+
+    assert_eq!(*seen.lock().unwrap(), ["whisper-received"]);
+}
+
+#[test]
+fn config_round_trips_through_a_file() {
+    let path = temp_path("event-sinks.json");
+    assert!(EventSinks::load_config(&path).expect("defaults").is_empty());
+
+    let log = temp_path("round-trip-events.jsonl");
+    let configs = vec![
+        SinkConfig {
+            kind: SinkKind::Webhook {
+                url: "http://127.0.0.1:9/hook".to_string(),
+                retry: RetryPolicy::default(),
+            },
+            events: vec!["trade-message".to_string()],
+        },
+        SinkConfig {
+            kind: SinkKind::File { path: log.clone() },
+            events: Vec::new(),
+        },
+        SinkConfig {
+            kind: SinkKind::Stdout,
+            events: Vec::new(),
+        },
+    ];
+    EventSinks::new(configs.clone())
+        .expect("sinks")
+        .save_config(&path)
+        .expect("save");
+    assert_eq!(EventSinks::load_config(&path).expect("load"), configs);
+
+    // Retry settings and event filters are optional
+    std::fs::write(
+        &path,
+        r#"[{"kind": "webhook", "url": "http://localhost:8080/"}, {"kind": "stdout"}]"#,
+    )
+    .expect("write");
+    let loaded = EventSinks::load_config(&path).expect("load partial");
+    assert_eq!(
+        loaded[0].kind,
+        SinkKind::Webhook {
+            url: "http://localhost:8080/".to_string(),
+            retry: RetryPolicy::default(),
+        }
+    );
+    assert!(loaded[1].events.is_empty());
+
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&log);
+}
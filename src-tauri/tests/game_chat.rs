@@ -0,0 +1,90 @@
+//! Tests for turning a whisper into lines for the game's chat box
+
+use dmg_meter_widget_lib::game_chat::{
+    chat_line_steps, needs_paste, sanitize_chat_text, whisper_lines, CHAT_LIMIT,
+};
+use dmg_meter_widget_lib::keyboard::{compile_key_steps, KeyStep};
+
+#[test]
+fn short_whisper_is_one_line() {
+    assert_eq!(
+        whisper_lines("shrack", "Your offer has been accepted. Game: trade-1////x"),
+        Ok(vec![
+            "/w *shrack Your offer has been accepted. Game: trade-1////x".to_string()
+        ])
+    );
+    assert_eq!(
+        whisper_lines(" *shrack ", "hi"),
+        Ok(vec!["/w *shrack hi".to_string()])
+    );
+}
+
+#[test]
+fn typographic_characters_are_replaced() {
+    assert_eq!(
+        sanitize_chat_text("It\u{2019}s \u{201C}sold\u{201D} \u{2014} sorry\u{2026}"),
+        "It's \"sold\" - sorry..."
+    );
+    assert_eq!(
+        sanitize_chat_text("  two\nlines\r\n\tand  gaps  "),
+        "two lines and gaps"
+    );
+    // Code page characters stay, emoji and control characters go
+    assert_eq!(
+        sanitize_chat_text("Привет Žluť café 🙂\u{7}!"),
+        "Привет Žluť café !"
+    );
+}
+
+#[test]
+fn long_whispers_split_between_words() {
+    let text = ["jah"; 100].join(" ");
+    let lines = whisper_lines("shrack", &text).expect("split");
+    assert_eq!(lines.len(), 2);
+    for line in &lines {
+        assert!(line.starts_with("/w *shrack "), "{}", line);
+        assert!(line.chars().count() <= CHAT_LIMIT, "{}", line.len());
+        assert!(line.ends_with("jah"), "{}", line);
+    }
+    let words: usize = lines.iter().map(|line| line.split(' ').count() - 2).sum();
+    assert_eq!(words, 100);
+
+    // A single word longer than a line is cut
+    let word = "x".repeat(400);
+    let lines = whisper_lines("shrack", &word).expect("split");
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0].chars().count(), CHAT_LIMIT);
+}
+
+#[test]
+fn invalid_whispers() {
+    for (target, text, error) in [
+        ("", "hi", "Whisper target is empty"),
+        ("*", "hi", "Whisper target is empty"),
+        ("two words", "hi", "Invalid whisper target"),
+        ("shrack", " \n ", "Message is empty"),
+        ("shrack", "🙂", "Message is empty"),
+    ] {
+        let message = whisper_lines(target, text).unwrap_err();
+        assert!(message.contains(error), "{:?}: {}", target, message);
+    }
+}
+
+#[test]
+fn lines_are_typed_unless_they_need_pasting() {
+    assert!(!needs_paste("/w *shrack 2 wss?"));
+    assert!(needs_paste("/w *shrack café"));
+
+    let steps = chat_line_steps("/w *shrack hi", false);
+    assert_eq!(steps.first(), Some(&KeyStep::Chord("enter".to_string())));
+    assert_eq!(steps.last(), Some(&KeyStep::Chord("enter".to_string())));
+    assert!(steps.contains(&KeyStep::Text("/w *shrack hi".to_string())));
+
+    let steps = chat_line_steps("/w *shrack café", true);
+    assert!(steps.contains(&KeyStep::Chord("ctrl+v".to_string())));
+    assert!(!steps.iter().any(|step| matches!(step, KeyStep::Text(_))));
+
+    for pasted in [false, true] {
+        compile_key_steps(&chat_line_steps("/w *shrack hi", pasted)).expect("valid steps");
+    }
+}
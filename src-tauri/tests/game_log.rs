@@ -0,0 +1,103 @@
+//! Tests for reading pd2_game.log into game events and the current session
+//!
+//! `tests/fixtures/game_log/session.log` holds one line of each form `parse_game_line`
+//! accepts, in the order a play session writes them.
+
+use dmg_meter_widget_lib::chat_watcher::{parse_game_line, GameLogEvent, GameSession};
+use std::path::PathBuf;
+
+fn session_log() -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("game_log")
+        .join("session.log");
+    std::fs::read_to_string(path).expect("read fixture")
+}
+
+fn joined(name: &str, password: Option<&str>) -> GameLogEvent {
+    GameLogEvent::GameJoined {
+        name: name.to_string(),
+        password: password.map(str::to_string),
+    }
+}
+
+#[test]
+fn fixture_lines_become_events() {
+    let events: Vec<Option<GameLogEvent>> = session_log().lines().map(parse_game_line).collect();
+    assert_eq!(
+        events,
+        [
+            Some(GameLogEvent::CharacterChanged {
+                name: "Shrackx".to_string()
+            }),
+            Some(joined("baal-run-12", Some("123"))),
+            Some(GameLogEvent::ActChanged { act: 5 }),
+            Some(GameLogEvent::GameLeft),
+            Some(joined("trade-me", Some("xyz"))),
+            Some(GameLogEvent::ActChanged { act: 2 }),
+            Some(GameLogEvent::GameLeft),
+            Some(GameLogEvent::CharacterChanged {
+                name: "Shrackb".to_string()
+            }),
+        ]
+    );
+}
+
+#[test]
+fn session_follows_the_log() {
+    let mut session = GameSession::default();
+    let mut states = Vec::new();
+    for event in session_log().lines().filter_map(parse_game_line) {
+        assert!(session.apply(&event), "{:?} changed nothing", event);
+        states.push((session.in_game, session.game_info(), session.act));
+    }
+
+    assert_eq!(
+        states,
+        [
+            (false, None, None),
+            (true, Some("baal-run-12////123".to_string()), Some(1)),
+            (true, Some("baal-run-12////123".to_string()), Some(5)),
+            (false, None, None),
+            (true, Some("trade-me////xyz".to_string()), Some(1)),
+            (true, Some("trade-me////xyz".to_string()), Some(2)),
+            (false, None, None),
+            (false, None, None),
+        ]
+    );
+    assert_eq!(session.character.as_deref(), Some("Shrackb"));
+
+    // Leaving again changes nothing
+    assert!(!session.apply(&GameLogEvent::GameLeft));
+}
+
+#[test]
+fn joins_without_a_password_and_crlf_endings() {
+    assert_eq!(
+        parse_game_line("Joined game: crlf-game\r\n"),
+        Some(joined("crlf-game", None))
+    );
+    assert_eq!(
+        parse_game_line("Left game\r\n"),
+        Some(GameLogEvent::GameLeft)
+    );
+}
+
+#[test]
+fn markers_only_count_at_the_start_of_a_line() {
+    for line in [
+        "",
+        "\r\n",
+        "2,From shrackx (*shrack): Joined game: fake//pw",
+        "0,Someone: I left game early",
+        "Joined gameplay: nope",
+        "Left game and came back",
+        "exact 3",
+        "Entered Act 6",
+        "Entered Actually",
+        "Selected character Shrackx",
+    ] {
+        assert_eq!(parse_game_line(line), None, "{:?}", line);
+    }
+}
@@ -0,0 +1,202 @@
+//! Tests for the command line arguments and the headless chat watcher behind them
+
+mod common;
+
+use common::{collecting_watcher, d2_dir, wait_for};
+use dmg_meter_widget_lib::chat_watcher::{BackfillOptions, LogEncoding, WatchMode};
+use dmg_meter_widget_lib::cli::{parse_args, CliCommand, OutputFormat, ParseArgs, WatchArgs};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+fn watch_args(args: &[&str]) -> WatchArgs {
+    match parse_args(args.iter().copied()).expect("parse") {
+        CliCommand::Watch(args) => args,
+        other => panic!("expected watch, got {:?}", other),
+    }
+}
+
+#[test]
+fn watch_defaults() {
+    assert_eq!(watch_args(&["watch"]), WatchArgs::default());
+    assert_eq!(WatchArgs::default().format, OutputFormat::Json);
+    assert_eq!(WatchArgs::default().mode, WatchMode::Auto);
+}
+
+#[test]
+fn watch_options() {
+    let args = watch_args(&[
+        "watch",
+        "--d2-dir",
+        "C:/Games/Diablo II",
+        "--format=text",
+        "--mode",
+        "poll",
+        "--backfill",
+        "50",
+        "--events",
+        "trade-message, whisper-received",
+        "--encoding",
+        "cp1251",
+    ]);
+    assert_eq!(args.d2_dir.as_deref(), Some("C:/Games/Diablo II"));
+    assert_eq!(args.format, OutputFormat::Text);
+    assert_eq!(args.mode, WatchMode::Poll);
+    assert_eq!(args.backfill, Some(50));
+    assert_eq!(args.events, ["trade-message", "whisper-received"]);
+    assert_eq!(args.encoding, LogEncoding::Windows1251);
+}
+
+#[test]
+fn parse_options() {
+    assert_eq!(
+        parse_args(["parse", "archive/pd2_chat.log"]),
+        Ok(CliCommand::Parse(ParseArgs {
+            input: Some(PathBuf::from("archive/pd2_chat.log")),
+            ..ParseArgs::default()
+        }))
+    );
+    assert_eq!(
+        parse_args([
+            "parse",
+            "-",
+            "--stats-only",
+            "--format",
+            "text",
+            "--templates=custom.json",
+            "--encoding=windows-1252"
+        ]),
+        Ok(CliCommand::Parse(ParseArgs {
+            input: None,
+            format: OutputFormat::Text,
+            stats_only: true,
+            templates: Some(PathBuf::from("custom.json")),
+            encoding: LogEncoding::Windows1252,
+        }))
+    );
+}
+
+#[test]
+fn help_and_errors() {
+    assert_eq!(parse_args(["--help"]), Ok(CliCommand::Help));
+    assert_eq!(parse_args(["watch", "-h"]), Ok(CliCommand::Help));
+
+    for (args, error) in [
+        (&[][..], "Usage"),
+        (&["tail"][..], "Unknown command: tail"),
+        (&["watch", "--d2-dir"][..], "Missing value for --d2-dir"),
+        (&["watch", "--format", "xml"][..], "Unknown format: xml"),
+        (&["watch", "--mode", "fast"][..], "Unknown watch mode: fast"),
+        (
+            &["watch", "--encoding", "koi8"][..],
+            "Unknown encoding: koi8",
+        ),
+        (
+            &["watch", "--backfill", "lots"][..],
+            "Invalid backfill line count",
+        ),
+        (&["watch", "--verbose"][..], "Unknown option: --verbose"),
+        (&["parse"][..], "Missing log file"),
+        (
+            &["parse", "a.log", "b.log"][..],
+            "Unexpected argument: b.log",
+        ),
+        (
+            &["parse", "a.log", "--limit", "3"][..],
+            "Unknown option: --limit",
+        ),
+    ] {
+        let message = parse_args(args.iter().copied()).unwrap_err();
+        assert!(message.contains(error), "{:?}: {}", args, message);
+    }
+}
+
+#[test]
+fn headless_watcher_sends_new_lines_to_its_sinks() {
+    let dir = d2_dir("headless-watch");
+    let (watcher, events) = collecting_watcher(&["trade-message", "chat-line"]);
+    watcher
+        .start_headless(
+            Some(dir.to_string_lossy().into_owned()),
+            WatchMode::Poll,
+            None,
+        )
+        .expect("start");
+
+    let log = dir.join("ProjectD2").join("pd2logs").join("pd2_chat.log");
+    let mut file = OpenOptions::new().append(true).open(&log).expect("log");
+    writeln!(
+        file,
+        "2,From shrackx (*shrack): Hi, I'm interested in your Frostburn listed for 2 wss"
+    )
+    .expect("write");
+
+    let seen = wait_for(&events, 2);
+    watcher.stop().expect("stop");
+    let names: Vec<&str> = seen.iter().map(|event| event.event.as_str()).collect();
+    assert_eq!(names, ["trade-message", "chat-line"]);
+    assert_eq!(seen[0].payload["itemName"], "Frostburn");
+    assert_eq!(seen[1].payload["kind"], "whisperIn");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn headless_backfill_replays_existing_lines() {
+    let dir = d2_dir("headless-backfill");
+    let logs = dir.join("ProjectD2").join("pd2logs");
+    std::fs::create_dir_all(&logs).expect("logs dir");
+    std::fs::write(
+        logs.join("pd2_chat.log"),
+        "2,From a (*a): first\n2,From b (*b): second\n2,From c (*c): third\n",
+    )
+    .expect("write");
+
+    let (watcher, events) = collecting_watcher(&["chat-line-backfilled"]);
+    watcher
+        .start_headless(
+            Some(dir.to_string_lossy().into_owned()),
+            WatchMode::Poll,
+            Some(BackfillOptions {
+                lines: Some(2),
+                minutes: None,
+            }),
+        )
+        .expect("start");
+
+    let seen = wait_for(&events, 2);
+    watcher.stop().expect("stop");
+    let messages: Vec<&str> = seen
+        .iter()
+        .filter_map(|event| event.payload["message"].as_str())
+        .collect();
+    assert_eq!(messages, ["second", "third"]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn headless_backfill_takes_an_unbounded_time_window() {
+    let dir = d2_dir("headless-backfill-window");
+    let logs = dir.join("ProjectD2").join("pd2logs");
+    std::fs::create_dir_all(&logs).expect("logs dir");
+    std::fs::write(logs.join("pd2_chat.log"), "2,From a (*a): first\n").expect("write");
+
+    let (watcher, events) = collecting_watcher(&["chat-line-backfilled"]);
+    watcher
+        .start_headless(
+            Some(dir.to_string_lossy().into_owned()),
+            WatchMode::Poll,
+            Some(BackfillOptions {
+                lines: None,
+                minutes: Some(u64::MAX),
+            }),
+        )
+        .expect("start");
+
+    let seen = wait_for(&events, 1);
+    watcher.stop().expect("stop");
+    assert_eq!(seen[0].payload["message"], "first");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,213 @@
+//! Tests for hotkey validation and focus-scoped registration in `HotkeyRegistry`
+
+mod common;
+
+use common::ShortcutRecorder;
+use dmg_meter_widget_lib::hotkeys::{check_bindings, HotkeyAction, HotkeyBinding, HotkeyRegistry};
+
+fn bind(action: HotkeyAction, sequence: &str) -> HotkeyBinding {
+    HotkeyBinding {
+        action,
+        sequence: sequence.to_string(),
+    }
+}
+
+fn app_bindings() -> Vec<HotkeyBinding> {
+    vec![
+        bind(HotkeyAction::PriceCheck, "ctrl+d"),
+        bind(HotkeyAction::ListItem, "ctrl+l"),
+        bind(HotkeyAction::Offers, "alt+b"),
+    ]
+}
+
+#[test]
+fn valid_bindings_have_no_problems() {
+    assert_eq!(check_bindings(&app_bindings()), []);
+    assert_eq!(check_bindings(&[]), []);
+}
+
+#[test]
+fn conflicts_between_bindings_are_reported() {
+    let problems = check_bindings(&[
+        bind(HotkeyAction::PriceCheck, "ctrl+shift+d"),
+        bind(HotkeyAction::ListItem, "Shift+Ctrl+D"),
+        bind(HotkeyAction::Chat, "ctrl+hyper"),
+        bind(HotkeyAction::PriceCheck, "ctrl+x"),
+    ]);
+    let messages: Vec<_> = problems
+        .iter()
+        .map(|p| (p.action, p.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            (HotkeyAction::ListItem, "Already used for price check"),
+            (HotkeyAction::Chat, "Unsupported fragment: hyper"),
+            (
+                HotkeyAction::PriceCheck,
+                "The price check hotkey is bound twice"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn game_binds_are_reserved() {
+    for (sequence, game_use) in [
+        ("ctrl+c", "copy item text"),
+        ("C", "character screen"),
+        ("tab", "automap"),
+        ("f3", "skill 3"),
+    ] {
+        let problems = check_bindings(&[bind(HotkeyAction::Settings, sequence)]);
+        assert_eq!(problems.len(), 1, "{sequence}");
+        assert_eq!(
+            problems[0].message,
+            format!("Diablo II uses this key for {game_use}")
+        );
+    }
+    // Modifiers make a game key free to use
+    assert_eq!(check_bindings(&[bind(HotkeyAction::Chat, "ctrl+t")]), []);
+}
+
+#[test]
+fn bindings_are_registered_only_while_focused() {
+    let backend = ShortcutRecorder::default();
+    let registry = HotkeyRegistry::new(backend.clone());
+    registry.set_bindings(app_bindings()).expect("set bindings");
+    assert!(backend.registered().is_empty());
+
+    registry.set_focused(true).expect("focus");
+    assert_eq!(backend.registered(), ["ctrl+d", "ctrl+l", "alt+b"]);
+    let status = registry.status().expect("status");
+    assert!(status.focused);
+    assert_eq!(
+        status.registered,
+        [
+            HotkeyAction::PriceCheck,
+            HotkeyAction::ListItem,
+            HotkeyAction::Offers
+        ]
+    );
+
+    // The same focus state again doesn't touch the OS
+    backend.take_log();
+    registry.set_focused(true).expect("focus");
+    assert!(backend.take_log().is_empty());
+
+    registry.set_focused(false).expect("blur");
+    assert!(backend.registered().is_empty());
+    assert!(registry.status().unwrap().registered.is_empty());
+
+    // New bindings while focused replace the old ones
+    registry.set_focused(true).expect("focus");
+    registry
+        .set_bindings(vec![bind(HotkeyAction::Chat, "alt+t")])
+        .expect("set bindings");
+    assert_eq!(backend.registered(), ["alt+t"]);
+}
+
+#[test]
+fn bindings_with_problems_are_left_out() {
+    let backend = ShortcutRecorder::default();
+    let registry = HotkeyRegistry::new(backend.clone());
+    registry.set_bindings(app_bindings()).expect("set bindings");
+    registry.set_focused(true).expect("focus");
+
+    let rejected = registry
+        .set_bindings(vec![
+            bind(HotkeyAction::PriceCheck, "ctrl+d"),
+            bind(HotkeyAction::Offers, "ctrl+d"),
+            bind(HotkeyAction::Chat, "alt+t"),
+        ])
+        .expect("set bindings");
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0].action, HotkeyAction::Offers);
+    assert_eq!(rejected[0].message, "Already used for price check");
+    assert_eq!(backend.registered(), ["ctrl+d", "alt+t"]);
+
+    let status = registry.status().expect("status");
+    assert_eq!(
+        status.registered,
+        [HotkeyAction::PriceCheck, HotkeyAction::Chat]
+    );
+    assert_eq!(status.failed, rejected);
+
+    // Fixing the binding clears the problem
+    registry.set_bindings(app_bindings()).expect("set bindings");
+    assert!(registry.status().unwrap().failed.is_empty());
+}
+
+#[test]
+fn sequences_the_backend_cannot_parse_are_rejected() {
+    let backend = ShortcutRecorder::default().unsupported(&["ctrl+num+"]);
+    let registry = HotkeyRegistry::new(backend.clone());
+    let bindings = vec![
+        bind(HotkeyAction::PriceCheck, "ctrl+d"),
+        bind(HotkeyAction::Chat, "ctrl+num+"),
+    ];
+
+    let problems = registry.check(&bindings).expect("check");
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].action, HotkeyAction::Chat);
+    assert_eq!(
+        problems[0].message,
+        "Can't use ctrl+num+ as a global shortcut"
+    );
+
+    assert_eq!(
+        registry.set_bindings(bindings).expect("set bindings"),
+        problems
+    );
+    assert_eq!(
+        registry.status().unwrap().bindings,
+        [bind(HotkeyAction::PriceCheck, "ctrl+d")]
+    );
+}
+
+#[test]
+fn registration_failures_are_reported() {
+    let backend = ShortcutRecorder::default().taken(&["ctrl+l"]);
+    let registry = HotkeyRegistry::new(backend.clone());
+    registry.set_bindings(app_bindings()).expect("set bindings");
+    registry.set_focused(true).expect("focus");
+
+    let status = registry.status().expect("status");
+    assert_eq!(
+        status.registered,
+        [HotkeyAction::PriceCheck, HotkeyAction::Offers]
+    );
+    assert_eq!(status.failed.len(), 1);
+    assert_eq!(status.failed[0].action, HotkeyAction::ListItem);
+    assert_eq!(status.failed[0].message, "ctrl+l is already registered");
+}
+
+#[test]
+fn presses_map_back_to_actions() {
+    let backend = ShortcutRecorder::default();
+    let registry = HotkeyRegistry::new(backend.clone());
+    registry.set_bindings(app_bindings()).expect("set bindings");
+
+    // Ids only resolve while registered
+    assert_eq!(registry.action_for_id(101), None);
+    registry.set_focused(true).expect("focus");
+    assert_eq!(registry.action_for_id(101), Some(HotkeyAction::PriceCheck));
+    assert_eq!(registry.action_for_id(103), Some(HotkeyAction::Offers));
+    assert_eq!(registry.action_for_id(7), None);
+
+    // Captured keys come in the input capture's normalized form
+    assert_eq!(
+        registry.action_for_hotkey("ctrl+l"),
+        Some(HotkeyAction::ListItem)
+    );
+    assert_eq!(registry.action_for_hotkey("ctrl+shift+l"), None);
+
+    // The key listener takes over from global shortcuts
+    registry.set_global_shortcuts(false).expect("disable");
+    assert!(backend.registered().is_empty());
+    assert_eq!(registry.action_for_id(101), None);
+    assert_eq!(
+        registry.action_for_hotkey("alt+b"),
+        Some(HotkeyAction::Offers)
+    );
+}
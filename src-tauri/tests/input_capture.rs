@@ -0,0 +1,116 @@
+//! Tests for turning captured key events into normalized hotkeys
+
+use dmg_meter_widget_lib::input_capture::{gate_hotkey, HotkeyTracker};
+use rdev::{Button, EventType, Key};
+use std::cell::Cell;
+
+fn press(tracker: &mut HotkeyTracker, key: Key) -> Option<String> {
+    tracker.handle(&EventType::KeyPress(key))
+}
+
+fn release(tracker: &mut HotkeyTracker, key: Key) -> Option<String> {
+    tracker.handle(&EventType::KeyRelease(key))
+}
+
+#[test]
+fn modifiers_combine_in_a_fixed_order() {
+    let mut tracker = HotkeyTracker::default();
+    assert_eq!(press(&mut tracker, Key::ShiftLeft), None);
+    assert_eq!(press(&mut tracker, Key::ControlRight), None);
+    assert_eq!(tracker.held_modifiers(), ["ctrl", "shift"]);
+    assert_eq!(
+        press(&mut tracker, Key::KeyD),
+        Some("ctrl+shift+d".to_string())
+    );
+    assert_eq!(release(&mut tracker, Key::KeyD), None);
+
+    assert_eq!(release(&mut tracker, Key::ShiftLeft), None);
+    assert_eq!(press(&mut tracker, Key::KeyB), Some("ctrl+b".to_string()));
+    release(&mut tracker, Key::KeyB);
+    release(&mut tracker, Key::ControlRight);
+
+    assert_eq!(press(&mut tracker, Key::F5), Some("f5".to_string()));
+    release(&mut tracker, Key::F5);
+    press(&mut tracker, Key::AltGr);
+    assert_eq!(press(&mut tracker, Key::Num1), Some("alt+1".to_string()));
+}
+
+#[test]
+fn left_and_right_modifiers_merge() {
+    let mut tracker = HotkeyTracker::default();
+    press(&mut tracker, Key::ControlLeft);
+    press(&mut tracker, Key::ControlRight);
+    release(&mut tracker, Key::ControlLeft);
+    assert_eq!(tracker.held_modifiers(), ["ctrl"]);
+    assert_eq!(press(&mut tracker, Key::KeyC), Some("ctrl+c".to_string()));
+}
+
+#[test]
+fn auto_repeat_and_unnamed_keys_are_ignored() {
+    let mut tracker = HotkeyTracker::default();
+    press(&mut tracker, Key::Alt);
+    assert_eq!(press(&mut tracker, Key::KeyT), Some("alt+t".to_string()));
+    // Held down: the OS repeats the press without a release
+    assert_eq!(press(&mut tracker, Key::KeyT), None);
+    assert_eq!(press(&mut tracker, Key::Alt), None);
+    release(&mut tracker, Key::KeyT);
+    assert_eq!(press(&mut tracker, Key::KeyT), Some("alt+t".to_string()));
+
+    assert_eq!(press(&mut tracker, Key::Unknown(0xfe03)), None);
+    assert_eq!(tracker.handle(&EventType::ButtonPress(Button::Left)), None);
+    assert_eq!(
+        tracker.handle(&EventType::MouseMove { x: 1.0, y: 2.0 }),
+        None
+    );
+}
+
+#[test]
+fn hotkeys_fire_only_while_enabled_and_diablo_is_in_front() {
+    let mut tracker = HotkeyTracker::default();
+    let checks = Cell::new(0);
+    let gate = |tracker: &mut HotkeyTracker, event, enabled, foreground| {
+        gate_hotkey(tracker, &event, enabled, || {
+            checks.set(checks.get() + 1);
+            foreground
+        })
+    };
+
+    // Disabled: tracked, but the window system isn't asked
+    assert_eq!(
+        gate(
+            &mut tracker,
+            EventType::KeyPress(Key::ControlLeft),
+            false,
+            true
+        ),
+        None
+    );
+    assert_eq!(
+        gate(&mut tracker, EventType::KeyPress(Key::KeyB), false, true),
+        None
+    );
+    assert_eq!(checks.get(), 0);
+    gate(&mut tracker, EventType::KeyRelease(Key::KeyB), true, true);
+
+    // Enabled while another window has the keyboard
+    assert_eq!(
+        gate(&mut tracker, EventType::KeyPress(Key::KeyB), true, false),
+        None
+    );
+    assert_eq!(checks.get(), 1);
+    gate(&mut tracker, EventType::KeyRelease(Key::KeyB), true, true);
+
+    // The modifier pressed while disabled still counts
+    assert_eq!(
+        gate(&mut tracker, EventType::KeyPress(Key::KeyB), true, true),
+        Some("ctrl+b".to_string())
+    );
+    assert_eq!(checks.get(), 2);
+
+    // Events that complete no hotkey never reach the focus check
+    assert_eq!(
+        gate(&mut tracker, EventType::KeyRelease(Key::KeyB), true, true),
+        None
+    );
+    assert_eq!(checks.get(), 2);
+}
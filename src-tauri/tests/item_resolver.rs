@@ -0,0 +1,143 @@
+//! Tests for resolving whisper item text against the generated item catalogue
+
+use dmg_meter_widget_lib::chat_watcher::items::ITEM_CATALOGUE;
+use dmg_meter_widget_lib::chat_watcher::{parse_trade_message, resolve_item, ItemQuality};
+
+#[test]
+fn catalogue_is_generated_from_items_ts() {
+    let count = |quality| {
+        ITEM_CATALOGUE
+            .iter()
+            .filter(|item| item.quality == quality)
+            .count()
+    };
+    assert!(count(ItemQuality::Unique) > 400);
+    assert!(count(ItemQuality::Set) > 100);
+    assert!(count(ItemQuality::Runeword) > 150);
+
+    let shako = ITEM_CATALOGUE
+        .iter()
+        .find(|item| item.name == "Harlequin Crest")
+        .expect("Harlequin Crest");
+    assert_eq!(shako.base, Some("Shako"));
+    assert_eq!(shako.quality, ItemQuality::Unique);
+}
+
+#[test]
+fn exact_names_resolve_with_full_confidence() {
+    for (text, key, quality) in [
+        ("Frostburn", "Frostburn", ItemQuality::Unique),
+        ("frostburn", "Frostburn", ItemQuality::Unique),
+        ("Sigon's Guard", "Sigon's Guard", ItemQuality::Set),
+        ("Infinity", "Runeword60", ItemQuality::Runeword),
+        // Catalogue keys differ from display names for some uniques
+        ("Skull Splitter", "Mindrend", ItemQuality::Unique),
+    ] {
+        let item = resolve_item(text).unwrap_or_else(|| panic!("{:?}", text));
+        assert_eq!(item.key, key, "{:?}", text);
+        assert_eq!(item.quality, quality, "{:?}", text);
+        assert_eq!(item.confidence, 1.0, "{:?}", text);
+    }
+}
+
+#[test]
+fn apostrophes_are_optional() {
+    for text in [
+        "Titan's Revenge",
+        "Titans Revenge",
+        "Titan\u{2019}s Revenge",
+    ] {
+        let item = resolve_item(text).unwrap_or_else(|| panic!("{:?}", text));
+        assert_eq!(item.name, "Titan's Revenge", "{:?}", text);
+        assert_eq!(item.confidence, 1.0, "{:?}", text);
+    }
+}
+
+#[test]
+fn descriptors_are_stripped() {
+    let item = resolve_item("ethereal Titan's Revenge").expect("item");
+    assert_eq!(item.name, "Titan's Revenge");
+    assert!(item.is_ethereal);
+    assert_eq!(item.confidence, 1.0);
+
+    for text in [
+        "eth Shako",
+        "superior Harlequin Crest",
+        "4os Harlequin Crest",
+        "Harlequin Crest unid",
+    ] {
+        let item = resolve_item(text).unwrap_or_else(|| panic!("{:?}", text));
+        assert_eq!(item.name, "Harlequin Crest", "{:?}", text);
+    }
+    assert!(!resolve_item("Harlequin Crest").expect("item").is_ethereal);
+}
+
+#[test]
+fn runeword_base_is_taken_from_the_whisper() {
+    let item = resolve_item("Spirit Monarch").expect("item");
+    assert_eq!(item.name, "Spirit");
+    assert_eq!(item.quality, ItemQuality::Runeword);
+    assert_eq!(item.base.as_deref(), Some("Monarch"));
+    assert!(item.confidence < 1.0);
+
+    let item = resolve_item("eth 4os Crescent Moon Phase Blade").expect("item");
+    assert_eq!(item.name, "Crescent Moon");
+    assert_eq!(item.quality, ItemQuality::Runeword);
+    assert_eq!(item.base.as_deref(), Some("Phase Blade"));
+    assert!(item.is_ethereal);
+}
+
+#[test]
+fn unique_names_win_over_runewords_of_the_same_name() {
+    let item = resolve_item("Crescent Moon").expect("item");
+    assert_eq!(item.quality, ItemQuality::Unique);
+    assert_eq!(item.base.as_deref(), Some("Amulet"));
+}
+
+#[test]
+fn name_followed_by_its_base() {
+    let item = resolve_item("Harlequin Crest Shako").expect("item");
+    assert_eq!(item.name, "Harlequin Crest");
+    assert_eq!(item.confidence, 1.0);
+}
+
+#[test]
+fn base_used_by_a_single_item() {
+    let item = resolve_item("Shako").expect("item");
+    assert_eq!(item.name, "Harlequin Crest");
+    assert!(item.confidence < 1.0);
+}
+
+#[test]
+fn typos_resolve_with_reduced_confidence() {
+    for (text, name) in [
+        ("Harlequen Crest", "Harlequin Crest"),
+        ("Frostbrn", "Frostburn"),
+        ("Arachnid Mes", "Arachnid Mesh"),
+    ] {
+        let item = resolve_item(text).unwrap_or_else(|| panic!("{:?}", text));
+        assert_eq!(item.name, name, "{:?}", text);
+        assert!(item.confidence < 0.9, "{:?}", text);
+        assert!(item.confidence > 0.5, "{:?}", text);
+    }
+}
+
+#[test]
+fn unknown_text_does_not_resolve() {
+    for text in ["", "   ", "random junk", "eth", "4os", "my stuff"] {
+        assert!(resolve_item(text).is_none(), "{:?}", text);
+    }
+}
+
+#[test]
+fn trade_message_carries_resolved_item() {
+    let trade = parse_trade_message(
+        "2,From shrackx (*shrack): Hi, I'm interested in your ethereal Titan's Revenge listed for 1 ber",
+    )
+    .expect("trade");
+
+    assert_eq!(trade.item_name.as_deref(), Some("ethereal Titan's Revenge"));
+    let item = trade.item.expect("resolved item");
+    assert_eq!(item.name, "Titan's Revenge");
+    assert!(item.is_ethereal);
+}
@@ -0,0 +1,193 @@
+//! Tests for the key sequence grammar of `keyboard::str_to_keys`
+
+use dmg_meter_widget_lib::keyboard::{key_to_string, str_to_keys};
+use enigo::Key;
+use rdev::Key as RdevKey;
+
+fn main_key(seq: &str) -> Key {
+    let (mods, main) = str_to_keys(seq).unwrap_or_else(|e| panic!("{:?}: {}", seq, e));
+    assert!(mods.is_empty(), "{:?} has modifiers", seq);
+    main
+}
+
+#[test]
+fn modifiers_and_characters() {
+    assert_eq!(
+        str_to_keys("ctrl+shift+c"),
+        Ok((vec![Key::Control, Key::Shift], Key::Unicode('c')))
+    );
+    assert_eq!(
+        str_to_keys("Control+ALT+Cmd+X"),
+        Ok((vec![Key::Control, Key::Alt, Key::Meta], Key::Unicode('x')))
+    );
+    assert_eq!(
+        str_to_keys("lctrl+rshift+a"),
+        Ok((vec![Key::LControl, Key::RShift], Key::Unicode('a')))
+    );
+    assert!(str_to_keys("altgr+e").is_ok());
+    assert!(str_to_keys("rwin+lalt+e").is_ok());
+    assert_eq!(str_to_keys(" ctrl + v "), str_to_keys("ctrl+v"));
+}
+
+#[test]
+fn named_keys_and_aliases() {
+    for (names, key) in [
+        (&["enter", "Return"][..], Key::Return),
+        (&["esc", "ESCAPE"][..], Key::Escape),
+        (&["tab"][..], Key::Tab),
+        (&["space", "spacebar", " "][..], Key::Space),
+        (&["backspace"][..], Key::Backspace),
+        (&["delete", "del"][..], Key::Delete),
+        (&["home"][..], Key::Home),
+        (&["end"][..], Key::End),
+        (&["pageup", "PgUp", "page_up", "Page Up"][..], Key::PageUp),
+        (&["pagedown", "pgdn"][..], Key::PageDown),
+        (&["up", "arrowup"][..], Key::UpArrow),
+        (&["down"][..], Key::DownArrow),
+        (&["left"][..], Key::LeftArrow),
+        (&["right", "RightArrow"][..], Key::RightArrow),
+        (&["num5", "numpad5", "kp5"][..], Key::Numpad5),
+        (&["num0"][..], Key::Numpad0),
+        (&["numadd", "numplus", "num\\+"][..], Key::Add),
+        (&["numsub", "num-"][..], Key::Subtract),
+        (&["nummul", "num*"][..], Key::Multiply),
+        (&["numdiv", "num/"][..], Key::Divide),
+        (&["numdecimal", "num."][..], Key::Decimal),
+        (&["f1"][..], Key::F1),
+        (&["F12"][..], Key::F12),
+        (&["f13"][..], Key::F13),
+        (&["f20"][..], Key::F20),
+    ] {
+        for name in names {
+            assert_eq!(main_key(name), key, "{:?}", name);
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        assert_eq!(main_key("f24"), Key::F24);
+        assert_eq!(main_key("ins"), Key::Insert);
+    }
+}
+
+#[test]
+fn plus_key() {
+    let ctrl_plus = Ok((vec![Key::Control], Key::Unicode('+')));
+    assert_eq!(str_to_keys("ctrl++"), ctrl_plus);
+    assert_eq!(str_to_keys("ctrl+\\+"), ctrl_plus);
+    assert_eq!(str_to_keys("ctrl+plus"), ctrl_plus);
+    assert_eq!(main_key("+"), Key::Unicode('+'));
+    assert_eq!(main_key("\\"), Key::Unicode('\\'));
+}
+
+#[test]
+fn trailing_plus_completes_a_key_name() {
+    assert_eq!(main_key("num+"), Key::Add);
+    assert_eq!(main_key("KP+"), Key::Add);
+    assert_eq!(str_to_keys("ctrl+num+"), Ok((vec![Key::Control], Key::Add)));
+    assert_eq!(
+        str_to_keys("shift+numpad+"),
+        Ok((vec![Key::Shift], Key::Add))
+    );
+    // Not a key name with the plus, so still a missing key
+    assert!(str_to_keys("ctrl+shift+")
+        .unwrap_err()
+        .contains("Missing key after '+'"));
+}
+
+#[test]
+fn invalid_sequences() {
+    for (seq, error) in [
+        ("", "No main key found"),
+        ("ctrl+shift", "No main key found"),
+        ("ctrl+", "Missing key after '+'"),
+        ("ctrl+hyper+a", "Unsupported fragment: hyper"),
+        ("f25", "Unsupported function key: F25"),
+        ("a+b", "More than one main key"),
+    ] {
+        let message = str_to_keys(seq).unwrap_err();
+        assert!(message.contains(error), "{:?}: {}", seq, message);
+    }
+}
+
+/// Every key name `key_to_string` produces for a captured key must parse back
+#[test]
+fn captured_key_names_round_trip() {
+    let keys = [
+        (RdevKey::KeyA, Key::Unicode('a')),
+        (RdevKey::KeyZ, Key::Unicode('z')),
+        (RdevKey::Num0, Key::Unicode('0')),
+        (RdevKey::Num9, Key::Unicode('9')),
+        (RdevKey::BackQuote, Key::Unicode('`')),
+        (RdevKey::Minus, Key::Unicode('-')),
+        (RdevKey::Equal, Key::Unicode('=')),
+        (RdevKey::LeftBracket, Key::Unicode('[')),
+        (RdevKey::RightBracket, Key::Unicode(']')),
+        (RdevKey::SemiColon, Key::Unicode(';')),
+        (RdevKey::Quote, Key::Unicode('\'')),
+        (RdevKey::BackSlash, Key::Unicode('\\')),
+        (RdevKey::Comma, Key::Unicode(',')),
+        (RdevKey::Dot, Key::Unicode('.')),
+        (RdevKey::Slash, Key::Unicode('/')),
+        (RdevKey::F1, Key::F1),
+        (RdevKey::F12, Key::F12),
+        (RdevKey::Return, Key::Return),
+        (RdevKey::Escape, Key::Escape),
+        (RdevKey::Tab, Key::Tab),
+        (RdevKey::Space, Key::Space),
+        (RdevKey::Backspace, Key::Backspace),
+        (RdevKey::Delete, Key::Delete),
+        (RdevKey::Home, Key::Home),
+        (RdevKey::End, Key::End),
+        (RdevKey::PageUp, Key::PageUp),
+        (RdevKey::PageDown, Key::PageDown),
+        (RdevKey::UpArrow, Key::UpArrow),
+        (RdevKey::DownArrow, Key::DownArrow),
+        (RdevKey::LeftArrow, Key::LeftArrow),
+        (RdevKey::RightArrow, Key::RightArrow),
+        (RdevKey::CapsLock, Key::CapsLock),
+        (RdevKey::Kp0, Key::Numpad0),
+        (RdevKey::Kp9, Key::Numpad9),
+        (RdevKey::KpPlus, Key::Add),
+        (RdevKey::KpMinus, Key::Subtract),
+        (RdevKey::KpMultiply, Key::Multiply),
+        (RdevKey::KpDivide, Key::Divide),
+        (RdevKey::KpDelete, Key::Decimal),
+        (RdevKey::KpReturn, Key::Return),
+        #[cfg(not(target_os = "macos"))]
+        (RdevKey::Insert, Key::Insert),
+        #[cfg(not(target_os = "macos"))]
+        (RdevKey::NumLock, Key::Numlock),
+        #[cfg(not(target_os = "macos"))]
+        (RdevKey::PrintScreen, Key::PrintScr),
+        #[cfg(not(target_os = "macos"))]
+        (RdevKey::Pause, Key::Pause),
+    ];
+    for (captured, key) in keys {
+        let name = key_to_string(captured).unwrap_or_else(|| panic!("{:?} has no name", captured));
+        assert_eq!(main_key(name), key, "{:?} -> {:?}", captured, name);
+    }
+
+    // Modifiers keep their side
+    let modifiers = [
+        (RdevKey::ControlLeft, "lctrl"),
+        (RdevKey::ControlRight, "rctrl"),
+        (RdevKey::ShiftLeft, "lshift"),
+        (RdevKey::ShiftRight, "rshift"),
+        (RdevKey::Alt, "lalt"),
+        (RdevKey::AltGr, "ralt"),
+        (RdevKey::MetaLeft, "lcmd"),
+        (RdevKey::MetaRight, "rcmd"),
+    ];
+    for (captured, expected) in modifiers {
+        let name = key_to_string(captured).unwrap_or_else(|| panic!("{:?} has no name", captured));
+        assert_eq!(name, expected, "{:?}", captured);
+        let seq = format!("{}+a", name);
+        assert!(str_to_keys(&seq).is_ok(), "{:?}", seq);
+    }
+    assert_eq!(
+        str_to_keys("rctrl+a"),
+        Ok((vec![Key::RControl], Key::Unicode('a')))
+    );
+    assert_ne!(str_to_keys("lalt+a"), str_to_keys("ralt+a"));
+}
@@ -0,0 +1,158 @@
+//! Tests for scripted key sequences: validation and the order keys are sent in
+
+mod common;
+
+use common::KeyRecorder;
+use dmg_meter_widget_lib::keyboard::{compile_key_steps, run_key_actions, KeyAction, KeyStep};
+use enigo::Key;
+use std::time::{Duration, Instant};
+
+#[test]
+fn steps_deserialize_from_json() {
+    let steps: Vec<KeyStep> = serde_json::from_str(
+        r#"[
+            {"chord": "enter"},
+            {"wait_ms": 50},
+            {"text": "/w *shrack 2 wss?"},
+            {"hold": "shift"},
+            {"release": "shift"}
+        ]"#,
+    )
+    .expect("parse steps");
+    assert_eq!(
+        steps,
+        [
+            KeyStep::Chord("enter".to_string()),
+            KeyStep::WaitMs(50),
+            KeyStep::Text("/w *shrack 2 wss?".to_string()),
+            KeyStep::Hold("shift".to_string()),
+            KeyStep::Release("shift".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn chat_workflow_compiles_in_order() {
+    let actions = compile_key_steps(&[
+        KeyStep::Chord("enter".to_string()),
+        KeyStep::Chord("ctrl+v".to_string()),
+        KeyStep::WaitMs(20),
+        KeyStep::Chord("enter".to_string()),
+    ])
+    .expect("compile");
+    assert_eq!(
+        actions,
+        [
+            KeyAction::Click(Key::Return),
+            KeyAction::Press(Key::Control),
+            KeyAction::Click(Key::Unicode('v')),
+            KeyAction::Release(Key::Control),
+            KeyAction::Wait(Duration::from_millis(20)),
+            KeyAction::Click(Key::Return),
+        ]
+    );
+}
+
+#[test]
+fn held_keys_are_released_at_the_end() {
+    let actions = compile_key_steps(&[
+        KeyStep::Hold("shift".to_string()),
+        KeyStep::Hold("ctrl".to_string()),
+        KeyStep::Chord("left".to_string()),
+        KeyStep::Release("shift".to_string()),
+    ])
+    .expect("compile");
+    assert_eq!(
+        actions,
+        [
+            KeyAction::Press(Key::Shift),
+            KeyAction::Press(Key::Control),
+            KeyAction::Click(Key::LeftArrow),
+            KeyAction::Release(Key::Shift),
+            KeyAction::Release(Key::Control),
+        ]
+    );
+}
+
+#[test]
+fn invalid_steps_are_reported_before_anything_is_sent() {
+    for (steps, error) in [
+        (vec![], "Key sequence is empty"),
+        (
+            vec![
+                KeyStep::Chord("enter".to_string()),
+                KeyStep::Chord("ctrl+hyper".to_string()),
+            ],
+            "Step 2: Unsupported fragment: hyper",
+        ),
+        (vec![KeyStep::Text(String::new())], "Step 1: Text is empty"),
+        (vec![KeyStep::WaitMs(60_000)], "Step 1: Wait of 60000 ms"),
+        (
+            vec![KeyStep::Hold("ctrl+a".to_string())],
+            "Step 1: Expected a single key",
+        ),
+        (
+            vec![
+                KeyStep::Hold("shift".to_string()),
+                KeyStep::Hold("Shift".to_string()),
+            ],
+            "Step 2: Shift is already held",
+        ),
+        (
+            vec![KeyStep::Release("alt".to_string())],
+            "Step 1: alt is not held",
+        ),
+    ] {
+        let message = compile_key_steps(&steps).unwrap_err();
+        assert!(message.contains(error), "{:?}: {}", steps, message);
+    }
+}
+
+#[test]
+fn actions_are_sent_in_order_with_waits() {
+    let actions = compile_key_steps(&[
+        KeyStep::Chord("enter".to_string()),
+        KeyStep::Text("hi".to_string()),
+        KeyStep::WaitMs(30),
+        KeyStep::Chord("shift+enter".to_string()),
+    ])
+    .expect("compile");
+
+    let mut recorder = KeyRecorder::default();
+    let started = Instant::now();
+    run_key_actions(&mut recorder, &actions).expect("run");
+    assert!(started.elapsed() >= Duration::from_millis(30));
+    assert_eq!(
+        recorder.log(),
+        [
+            "Click Return",
+            "Text hi",
+            "Press Shift",
+            "Click Return",
+            "Release Shift",
+        ]
+    );
+}
+
+#[test]
+fn a_failed_action_releases_pressed_keys() {
+    let actions = compile_key_steps(&[
+        KeyStep::Hold("ctrl".to_string()),
+        KeyStep::Chord("shift+a".to_string()),
+    ])
+    .expect("compile");
+
+    // Fails on "Click a", with ctrl and shift down
+    let mut recorder = KeyRecorder::failing_at(2);
+    let error = run_key_actions(&mut recorder, &actions).unwrap_err();
+    assert_eq!(error, "display went away");
+    assert_eq!(
+        recorder.log(),
+        [
+            "Press Control",
+            "Press Shift",
+            "Release Shift",
+            "Release Control"
+        ]
+    );
+}
@@ -0,0 +1,157 @@
+//! Tests for parsing a whole chat log offline and aggregating its trades
+
+use dmg_meter_widget_lib::chat_watcher::templates::SHIPPED_TEMPLATES;
+use dmg_meter_widget_lib::chat_watcher::{
+    analyse_log, analyse_log_bytes, analyse_log_file, ChatLine, LogEncoding,
+};
+use std::path::Path;
+
+const LOG: &str = "\
+2,From shrackx (*shrack): Hi, I'm interested in your Frostburn listed for 2 wss
+2,Sent to shrackx (*shrack): yes, one sec
+2,From buyer (*buyer): Hi, I'm interested in your Shako listed for 1 ist
+2,From shrackalt (*shrack): Hi, I'm interested in your Frostburn listed for 3 wss
+
+4,buyer(buyer) joined our world. Diablo's minions grow stronger.
+2,From other (*other): Hi, I'm interested in your Frostburn listed for 1 ber + 1 jah
+";
+
+#[test]
+fn every_parsed_line_becomes_an_event() {
+    let analysis = analyse_log(LOG, &SHIPPED_TEMPLATES);
+    let lines: Vec<usize> = analysis.events.iter().map(|event| event.line).collect();
+    assert_eq!(lines, [1, 2, 3, 4, 6, 7]);
+
+    let first = &analysis.events[0];
+    assert!(matches!(first.chat_line, ChatLine::WhisperIn { .. }));
+    assert!(first.whisper.as_ref().expect("whisper").is_trade);
+    assert_eq!(
+        first.trade.as_ref().expect("trade").price.as_deref(),
+        Some("2 wss")
+    );
+
+    // Replies and joins are events without a trade
+    assert!(analysis.events[1].trade.is_none());
+    assert!(analysis.events[4].whisper.as_ref().expect("join").is_join);
+}
+
+#[test]
+fn line_counts() {
+    let stats = analyse_log(LOG, &SHIPPED_TEMPLATES).stats;
+    assert_eq!(stats.total_lines, 7);
+    assert_eq!(stats.parsed_lines, 6);
+    assert_eq!(stats.whispers_in, 4);
+    assert_eq!(stats.whispers_out, 1);
+    assert_eq!(stats.trades, 4);
+    assert_eq!(stats.incoming_trades, 4);
+}
+
+#[test]
+fn trades_per_item_and_buyer() {
+    let stats = analyse_log(LOG, &SHIPPED_TEMPLATES).stats;
+
+    let items: Vec<(&str, u64)> = stats
+        .trades_per_item
+        .iter()
+        .map(|entry| (entry.name.as_str(), entry.count))
+        .collect();
+    // Item names are resolved against the catalogue
+    assert_eq!(items, [("Frostburn", 3), ("Harlequin Crest", 1)]);
+
+    let buyers: Vec<(&str, u64)> = stats
+        .trades_per_buyer
+        .iter()
+        .map(|entry| (entry.name.as_str(), entry.count))
+        .collect();
+    // Alts count towards their account
+    assert_eq!(buyers, [("shrack", 2), ("buyer", 1), ("other", 1)]);
+}
+
+#[test]
+fn price_distribution_per_currency() {
+    let stats = analyse_log(LOG, &SHIPPED_TEMPLATES).stats;
+    let currencies: Vec<&str> = stats
+        .price_distribution
+        .iter()
+        .map(|price| price.currency.as_str())
+        .collect();
+    assert_eq!(currencies, ["wss", "ber", "ist", "jah"]);
+
+    let wss = &stats.price_distribution[0];
+    assert_eq!(wss.count, 2);
+    assert_eq!(wss.min, 2.0);
+    assert_eq!(wss.max, 3.0);
+    assert_eq!(wss.mean, 2.5);
+    assert_eq!(wss.median, 2.5);
+}
+
+#[test]
+fn stamped_lines_are_counted_per_hour() {
+    let log = "\
+[2024-03-01 14:05:11] 2,From a (*a): Hi, I'm interested in your Shako listed for 2 ist
+[14:30] 2,From b (*b): Hi, I'm interested in your Shako listed for 1 ist
+[09:15:00] 2,From c (*c): Hi, I'm interested in your Shako listed for 1 ist
+[09:20:00] 2,From c (*c): are you there?
+2,From d (*d): Hi, I'm interested in your Shako listed for 1 ist
+";
+    let analysis = analyse_log(log, &SHIPPED_TEMPLATES);
+    assert_eq!(analysis.events.len(), 5);
+    assert_eq!(analysis.events[0].hour, Some(14));
+    assert_eq!(analysis.events[4].hour, None);
+
+    let hours: Vec<(u8, u64)> = analysis
+        .stats
+        .trades_per_hour
+        .iter()
+        .map(|entry| (entry.hour, entry.count))
+        .collect();
+    assert_eq!(hours, [(9, 1), (14, 2)]);
+    assert_eq!(analysis.stats.untimed_trades, 1);
+}
+
+#[test]
+fn fixture_log_file() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/chat_log/whispers.log");
+    let analysis = analyse_log_file(&path, &SHIPPED_TEMPLATES, LogEncoding::Auto).expect("analyse");
+    assert_eq!(analysis.stats.total_lines, 18);
+    assert_eq!(analysis.stats.parsed_lines, 18);
+    assert_eq!(analysis.stats.trades, 9);
+
+    assert!(analyse_log_file(
+        Path::new("does/not/exist.log"),
+        &SHIPPED_TEMPLATES,
+        LogEncoding::Auto
+    )
+    .is_err());
+}
+
+#[test]
+fn lines_are_decoded_one_at_a_time() {
+    // A client switched to UTF-8 partway: one Windows-1251 line between UTF-8 ones
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/encoding/mixed.log");
+    let bytes = std::fs::read(&path).expect("read fixture");
+
+    for analysis in [
+        analyse_log_file(&path, &SHIPPED_TEMPLATES, LogEncoding::Auto).expect("analyse"),
+        analyse_log_bytes(&bytes, &SHIPPED_TEMPLATES, LogEncoding::Auto),
+    ] {
+        let senders: Vec<&str> = analysis
+            .events
+            .iter()
+            .map(|event| match &event.chat_line {
+                ChatLine::WhisperIn { player, .. } => player.character.as_str(),
+                other => panic!("expected a whisper, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(senders, ["Müller", "Вася", "Петя"]);
+        assert_eq!(analysis.stats.trades, 3);
+    }
+}
+
+#[test]
+fn empty_log() {
+    let analysis = analyse_log("", &SHIPPED_TEMPLATES);
+    assert!(analysis.events.is_empty());
+    assert_eq!(analysis.stats.total_lines, 0);
+    assert!(analysis.stats.price_distribution.is_empty());
+}
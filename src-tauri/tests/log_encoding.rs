@@ -0,0 +1,151 @@
+//! Tests for decoding logs written in legacy Windows code pages
+//!
+//! The `*.log` files in `tests/fixtures/encoding` hold the raw bytes a client with that code
+//! page writes; their names say which one.
+
+mod common;
+
+use common::{collecting_watcher, d2_dir, wait_for};
+use dmg_meter_widget_lib::chat_watcher::{
+    parse_chat_line, ChatLine, LineDecoder, LogEncoding, WatchMode,
+};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("encoding")
+        .join(name);
+    fs::read(path).expect("read fixture")
+}
+
+/// Decode a fixture line by line, the way the watcher reads it
+fn decode_lines(decoder: &mut LineDecoder, bytes: &[u8]) -> Vec<String> {
+    bytes
+        .split_inclusive(|byte| *byte == b'\n')
+        .map(|line| decoder.decode(line).trim_end().to_string())
+        .collect()
+}
+
+fn sender(line: &str) -> String {
+    match parse_chat_line(line) {
+        Some(ChatLine::WhisperIn { player, .. }) | Some(ChatLine::WhisperOut { player, .. }) => {
+            player.character
+        }
+        other => panic!("expected a whisper, got {:?}", other),
+    }
+}
+
+#[test]
+fn auto_detects_cyrillic() {
+    let mut decoder = LineDecoder::default();
+    let lines = decode_lines(&mut decoder, &fixture("windows1251.log"));
+    assert_eq!(lines[0], "2,From Вася (*vasya): Привет, продашь Шако?");
+    assert_eq!(lines[1], "2,To Вася (*vasya): Да, за 2 ist");
+    assert_eq!(sender(&lines[0]), "Вася");
+    assert_eq!(decoder.detected(), Some(LogEncoding::Windows1251));
+}
+
+#[test]
+fn auto_detects_western_european() {
+    let mut decoder = LineDecoder::default();
+    let lines = decode_lines(&mut decoder, &fixture("windows1252.log"));
+    assert_eq!(
+        lines[0],
+        "2,From Müller (*jörg): Hi, I'm interested in your Frostburn listed for 2 wss"
+    );
+    assert_eq!(sender(&lines[1]), "Ñoño");
+    assert_eq!(decoder.detected(), Some(LogEncoding::Windows1252));
+}
+
+#[test]
+fn auto_detects_central_european() {
+    let mut decoder = LineDecoder::default();
+    let lines = decode_lines(&mut decoder, &fixture("windows1250.log"));
+    assert_eq!(lines[0], "2,From Łukasz (*zolw): cześć, sprzedasz Shako?");
+    assert_eq!(sender(&lines[0]), "Łukasz");
+    assert_eq!(decoder.detected(), Some(LogEncoding::Windows1250));
+}
+
+#[test]
+fn utf8_logs_are_left_alone() {
+    let mut decoder = LineDecoder::default();
+    let lines = decode_lines(&mut decoder, &fixture("utf8.log"));
+    assert_eq!(lines[0], "2,From Вася (*vasya): Привет");
+    assert_eq!(lines[1], "2,From Müller (*jörg): hallo");
+    assert_eq!(decoder.detected(), None);
+}
+
+#[test]
+fn configured_code_page_is_used_as_is() {
+    let mut decoder = LineDecoder::new(LogEncoding::Windows1250);
+    let lines = decode_lines(&mut decoder, &fixture("windows1250.log"));
+    assert_eq!(lines[0], "2,From Łukasz (*zolw): cześć, sprzedasz Shako?");
+
+    // UTF-8 lines still pass through, e.g. after switching clients mid-log
+    let lines = decode_lines(&mut decoder, &fixture("utf8.log"));
+    assert_eq!(lines[1], "2,From Müller (*jörg): hallo");
+
+    // Forcing UTF-8 keeps the old lossy behaviour
+    let mut decoder = LineDecoder::new(LogEncoding::Utf8);
+    let lines = decode_lines(&mut decoder, &fixture("windows1251.log"));
+    assert!(lines[0].contains('\u{FFFD}'));
+}
+
+#[test]
+fn detection_sticks_for_the_rest_of_the_log() {
+    let mut decoder = LineDecoder::default();
+    decoder.decode(&fixture("windows1251.log"));
+    // On its own this line would be taken for Windows-1252
+    let line = b"2,From M\xfcller (*j\xf6rg): hallo";
+    assert_ne!(decoder.decode(line), "2,From Müller (*jörg): hallo");
+    assert_eq!(decoder.detected(), Some(LogEncoding::Windows1251));
+}
+
+#[test]
+fn encoding_config_round_trips() {
+    let dir = std::env::temp_dir().join(format!("pd2-trader-encoding-{}", std::process::id()));
+    let path = dir.join("log-encoding.json");
+    let _ = fs::remove_dir_all(&dir);
+
+    assert_eq!(LogEncoding::load_config(&path), Ok(LogEncoding::Auto));
+    LogEncoding::Windows1251.save_config(&path).expect("save");
+    assert_eq!(fs::read_to_string(&path).unwrap(), "\"windows1251\"");
+    assert_eq!(
+        LogEncoding::load_config(&path),
+        Ok(LogEncoding::Windows1251)
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn watcher_decodes_appended_lines() {
+    let dir = d2_dir("encoding-watch");
+    let (watcher, events) = collecting_watcher(&["chat-line"]);
+    watcher
+        .start_headless(
+            Some(dir.to_string_lossy().into_owned()),
+            WatchMode::Poll,
+            None,
+        )
+        .expect("start");
+
+    let log = dir.join("ProjectD2").join("pd2logs").join("pd2_chat.log");
+    let mut file = OpenOptions::new().append(true).open(&log).expect("log");
+    file.write_all(&fixture("windows1251.log")).expect("write");
+
+    let seen = wait_for(&events, 2);
+    let status = watcher.status();
+    watcher.stop().expect("stop");
+
+    assert_eq!(seen.len(), 2);
+    assert_eq!(seen[0].payload["player"]["character"], "Вася");
+    assert_eq!(seen[0].payload["message"], "Привет, продашь Шако?");
+    assert_eq!(status.detected_encoding, Some(LogEncoding::Windows1251));
+
+    let _ = fs::remove_dir_all(&dir);
+}
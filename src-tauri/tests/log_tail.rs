@@ -0,0 +1,77 @@
+//! Tests for following the chat log when the game truncates or replaces it
+
+mod common;
+
+use common::{collecting_watcher, d2_dir, wait_for};
+use dmg_meter_widget_lib::chat_watcher::{ChatWatcher, SinkEvent, WatchMode};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// A polling watcher on a fresh Diablo II directory, and the chat log it follows
+fn watch(name: &str) -> (PathBuf, ChatWatcher, Arc<Mutex<Vec<SinkEvent>>>, PathBuf) {
+    let dir = d2_dir(name);
+    let (watcher, events) = collecting_watcher(&["chat-line"]);
+    watcher
+        .start_headless(
+            Some(dir.to_string_lossy().into_owned()),
+            WatchMode::Poll,
+            None,
+        )
+        .expect("start");
+    let log = dir.join("ProjectD2").join("pd2logs").join("pd2_chat.log");
+    (dir, watcher, events, log)
+}
+
+fn append(log: &Path, line: &str) {
+    let mut file = OpenOptions::new().append(true).open(log).expect("log");
+    writeln!(file, "{}", line).expect("write");
+}
+
+fn messages(events: &[SinkEvent]) -> Vec<String> {
+    events
+        .iter()
+        .filter_map(|event| event.payload["message"].as_str().map(str::to_string))
+        .collect()
+}
+
+#[test]
+fn truncated_log_is_read_from_the_start() {
+    let (dir, watcher, events, log) = watch("tail-truncate");
+    append(&log, "2,From a (*a): a line before the game clears the log");
+    wait_for(&events, 1);
+
+    std::fs::write(&log, "2,From b (*b): fresh\n").expect("truncate");
+    let seen = wait_for(&events, 2);
+    watcher.stop().expect("stop");
+    assert_eq!(
+        messages(&seen),
+        ["a line before the game clears the log", "fresh"]
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn replaced_log_is_read_from_the_start() {
+    let (dir, watcher, events, log) = watch("tail-rotate");
+    append(&log, "2,From a (*a): one");
+    wait_for(&events, 1);
+
+    // The new file is longer than what was read, so only its identity shows it is new
+    std::fs::rename(&log, log.with_extension("log.1")).expect("rotate");
+    std::fs::write(
+        &log,
+        "2,From b (*b): a longer line written after rotation\n",
+    )
+    .expect("recreate");
+    let seen = wait_for(&events, 2);
+    watcher.stop().expect("stop");
+    assert_eq!(
+        messages(&seen),
+        ["one", "a longer line written after rotation"]
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
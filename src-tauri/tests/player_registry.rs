@@ -0,0 +1,150 @@
+//! Tests for learning account/character pairs from chat log lines
+
+use dmg_meter_widget_lib::chat_watcher::parse_chat_line;
+use dmg_meter_widget_lib::player_registry::PlayerRegistry;
+
+fn record(registry: &PlayerRegistry, line: &str, timestamp: u64) {
+    let chat_line = parse_chat_line(line).expect("chat line");
+    registry.record(&chat_line, timestamp).expect("record");
+}
+
+fn character_names(registry: &PlayerRegistry, account: &str) -> Vec<String> {
+    registry
+        .characters(account)
+        .expect("characters")
+        .into_iter()
+        .map(|known| known.character)
+        .collect()
+}
+
+#[test]
+fn whispers_and_joins_teach_characters() {
+    let registry = PlayerRegistry::in_memory().expect("registry");
+    record(&registry, "2,From shrackx (*shrack): hi", 1_000);
+    record(&registry, "2,Sent to Shrackb (*shrack): hello", 2_000);
+    record(
+        &registry,
+        "4,shrackalt(shrack) joined our world. Diablo's minions grow stronger.",
+        3_000,
+    );
+
+    assert_eq!(
+        character_names(&registry, "shrack"),
+        ["shrackalt", "Shrackb", "shrackx"]
+    );
+}
+
+#[test]
+fn first_and_last_seen_are_tracked() {
+    let registry = PlayerRegistry::in_memory().expect("registry");
+    record(&registry, "2,From shrackx (*shrack): hi", 1_000);
+    record(&registry, "2,From shrackx (*shrack): still there?", 5_000);
+    record(
+        &registry,
+        "4,shrackx(shrack) left our world. Diablo's minions weaken.",
+        9_000,
+    );
+
+    let known = registry.characters("shrack").expect("characters");
+    assert_eq!(known.len(), 1);
+    assert_eq!(known[0].first_seen, 1_000);
+    assert_eq!(known[0].last_seen, 9_000);
+    assert_eq!(known[0].times_seen, 3);
+}
+
+#[test]
+fn names_match_case_insensitively() {
+    let registry = PlayerRegistry::in_memory().expect("registry");
+    record(&registry, "2,From ShrackX (*Shrack): hi", 1_000);
+    record(&registry, "2,From shrackx (*shrack): hi", 2_000);
+
+    let known = registry.characters("SHRACK").expect("characters");
+    assert_eq!(known.len(), 1);
+    // The latest spelling wins
+    assert_eq!(known[0].account, "shrack");
+    assert_eq!(known[0].character, "shrackx");
+    assert_eq!(known[0].first_seen, 1_000);
+}
+
+#[test]
+fn replayed_lines_only_add_unknown_pairs() {
+    let registry = PlayerRegistry::in_memory().expect("registry");
+    record(&registry, "2,From shrackx (*shrack): hi", 1_000);
+
+    for line in [
+        "2,From shrackx (*shrack): hi",
+        "2,From shrackalt (*shrack): hello",
+    ] {
+        let chat_line = parse_chat_line(line).expect("chat line");
+        registry
+            .record_replayed(&chat_line, 5_000)
+            .expect("record replayed");
+    }
+
+    let known = registry.characters("shrack").expect("characters");
+    assert_eq!(known.len(), 2);
+    let shrackx = known
+        .iter()
+        .find(|k| k.character == "shrackx")
+        .expect("shrackx");
+    assert_eq!(shrackx.last_seen, 1_000);
+    assert_eq!(shrackx.times_seen, 1);
+    let alt = known
+        .iter()
+        .find(|k| k.character == "shrackalt")
+        .expect("shrackalt");
+    assert_eq!(alt.times_seen, 1);
+}
+
+#[test]
+fn lines_without_an_account_are_ignored() {
+    let registry = PlayerRegistry::in_memory().expect("registry");
+    record(&registry, "2,From lonewolf: hi", 1_000);
+    record(
+        &registry,
+        "2,From *pal: Your friend palchar has entered Project Diablo 2.",
+        1_000,
+    );
+    record(&registry, "0,Someone: hello world", 1_000);
+
+    assert!(registry
+        .characters("lonewolf")
+        .expect("characters")
+        .is_empty());
+    assert!(registry.accounts("lonewolf").expect("accounts").is_empty());
+    assert!(registry.accounts("palchar").expect("accounts").is_empty());
+}
+
+#[test]
+fn characters_can_be_looked_up_by_name() {
+    let registry = PlayerRegistry::in_memory().expect("registry");
+    record(&registry, "2,From tradechar (*first): hi", 1_000);
+    record(&registry, "2,From tradechar (*second): hi", 2_000);
+
+    let accounts: Vec<String> = registry
+        .accounts("TradeChar")
+        .expect("accounts")
+        .into_iter()
+        .map(|known| known.account)
+        .collect();
+    assert_eq!(accounts, ["second", "first"]);
+}
+
+#[test]
+fn registry_persists_across_reopen() {
+    let path = std::env::temp_dir().join(format!(
+        "pd2-trader-player-registry-{}.sqlite",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    {
+        let registry = PlayerRegistry::open(&path).expect("open");
+        record(&registry, "2,From shrackx (*shrack): hi", 1_000);
+    }
+    let registry = PlayerRegistry::open(&path).expect("reopen");
+    assert_eq!(character_names(&registry, "shrack"), ["shrackx"]);
+
+    drop(registry);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,292 @@
+//! Unit tests for trade price parsing
+
+use dmg_meter_widget_lib::chat_watcher::price::RUNES;
+use dmg_meter_widget_lib::chat_watcher::{parse_price, parse_trade_message, CurrencyKind, Price};
+
+/// (amount, currency, kind, level) for each component, in order
+fn components(price: &Price) -> Vec<(f64, &str, CurrencyKind, bool)> {
+    price
+        .components
+        .iter()
+        .map(|c| (c.amount, c.currency.as_str(), c.kind, c.level))
+        .collect()
+}
+
+fn parse(text: &str) -> Price {
+    parse_price(text).unwrap_or_else(|| panic!("{:?} should parse", text))
+}
+
+#[test]
+fn single_rune() {
+    for text in [
+        "1 ber",
+        "1 Ber",
+        "1 BER",
+        "1 ber rune",
+        "1 Ber Rune",
+        "ber",
+        "1ber",
+    ] {
+        assert_eq!(
+            components(&parse(text)),
+            [(1.0, "ber", CurrencyKind::Rune, false)],
+            "{:?}",
+            text
+        );
+    }
+}
+
+#[test]
+fn plural_and_possessive_runes() {
+    for text in ["3 ists", "3 ist runes", "3 ist's", "3x ist", "3 x ist"] {
+        assert_eq!(
+            components(&parse(text)),
+            [(3.0, "ist", CurrencyKind::Rune, false)],
+            "{:?}",
+            text
+        );
+    }
+}
+
+#[test]
+fn every_rune_is_recognised() {
+    for rune in RUNES {
+        let price = parse(&format!("2 {}", rune));
+        assert_eq!(
+            components(&price),
+            [(2.0, rune, CurrencyKind::Rune, false)],
+            "{}",
+            rune
+        );
+    }
+}
+
+#[test]
+fn worldstone_shards() {
+    for text in [
+        "2 wss",
+        "2wss",
+        "2 WSS",
+        "2 ws",
+        "2 shards",
+        "2 worldstone shards",
+        "2 Worldstone Shard",
+    ] {
+        assert_eq!(
+            components(&parse(text)),
+            [(2.0, "wss", CurrencyKind::Shard, false)],
+            "{:?}",
+            text
+        );
+    }
+}
+
+#[test]
+fn rune_classes() {
+    assert_eq!(
+        components(&parse("0.5 hr")),
+        [(0.5, "hr", CurrencyKind::RuneClass, false)]
+    );
+    assert_eq!(
+        components(&parse("2 hrs")),
+        [(2.0, "hr", CurrencyKind::RuneClass, false)]
+    );
+    assert_eq!(
+        components(&parse("3 mid runes")),
+        [(3.0, "mr", CurrencyKind::RuneClass, false)]
+    );
+}
+
+#[test]
+fn fractional_amounts() {
+    assert_eq!(parse("0.5 hr").components[0].amount, 0.5);
+    assert_eq!(parse(".5 hr").components[0].amount, 0.5);
+    assert_eq!(parse("1/2 ber").components[0].amount, 0.5);
+    assert_eq!(parse("1.25 wss").components[0].amount, 1.25);
+}
+
+#[test]
+fn invalid_fractions_are_dropped() {
+    assert!(parse_price("1/0 ber").is_none());
+    assert!(parse_price("1.2.3 ber").is_none());
+}
+
+#[test]
+fn level_abbreviations() {
+    for text in ["pul+", "Pul+", "pul-level", "pul level", "1 pul+"] {
+        assert_eq!(
+            components(&parse(text)),
+            [(1.0, "pul", CurrencyKind::Rune, true)],
+            "{:?}",
+            text
+        );
+    }
+    assert_eq!(
+        components(&parse("um-level")),
+        [(1.0, "um", CurrencyKind::Rune, true)]
+    );
+}
+
+#[test]
+fn multiple_components() {
+    let expected = [
+        (1.0, "ber", CurrencyKind::Rune, false),
+        (1.0, "jah", CurrencyKind::Rune, false),
+    ];
+    for text in [
+        "1 ber + 1 jah",
+        "1 ber+1 jah",
+        "1 ber, 1 jah",
+        "1 ber and 1 jah",
+        "1 ber & 1 jah",
+        "ber+jah",
+    ] {
+        assert_eq!(components(&parse(text)), expected, "{:?}", text);
+    }
+}
+
+#[test]
+fn mixed_currencies() {
+    let price = parse("3 ists, 1 gul + 2 wss");
+    assert_eq!(
+        components(&price),
+        [
+            (3.0, "ist", CurrencyKind::Rune, false),
+            (1.0, "gul", CurrencyKind::Rune, false),
+            (2.0, "wss", CurrencyKind::Shard, false),
+        ]
+    );
+    assert_eq!(price.amount_of("ist"), 3.0);
+    assert_eq!(price.amount_of("ber"), 0.0);
+}
+
+#[test]
+fn amounts_of_the_same_currency_add_up() {
+    assert_eq!(parse("2 ist + 1 ist").amount_of("ist"), 3.0);
+}
+
+#[test]
+fn level_marker_followed_by_another_component() {
+    assert_eq!(
+        components(&parse("pul+ + 2 wss")),
+        [
+            (1.0, "pul", CurrencyKind::Rune, true),
+            (2.0, "wss", CurrencyKind::Shard, false),
+        ]
+    );
+}
+
+#[test]
+fn obo_suffix() {
+    for text in [
+        "2 wss obo",
+        "2 wss OBO",
+        "2 wss o.b.o",
+        "2 wss or best offer",
+    ] {
+        let price = parse(text);
+        assert!(price.is_obo, "{:?}", text);
+        assert!(!price.is_offer, "{:?}", text);
+        assert_eq!(
+            components(&price),
+            [(2.0, "wss", CurrencyKind::Shard, false)],
+            "{:?}",
+            text
+        );
+    }
+}
+
+#[test]
+fn obo_markers_match_whole_words() {
+    for text in ["2 wss obo!", "2 wss, o.b.o.", "obo 2 wss"] {
+        assert!(parse(text).is_obo, "{:?}", text);
+    }
+    for text in ["2 wss hobo", "2 obol", "2 wss kobold"] {
+        assert!(
+            parse_price(text).is_none_or(|price| !price.is_obo),
+            "{:?}",
+            text
+        );
+    }
+}
+
+#[test]
+fn offer_markers() {
+    for text in ["offer", "offers", "make offer", "c/o", "Make an offer"] {
+        let price = parse(text);
+        assert!(price.is_offer, "{:?}", text);
+        assert!(price.components.is_empty(), "{:?}", text);
+    }
+
+    let price = parse("1 ber or offer");
+    assert!(price.is_offer);
+    assert_eq!(
+        components(&price),
+        [(1.0, "ber", CurrencyKind::Rune, false)]
+    );
+}
+
+#[test]
+fn offer_markers_match_whole_words() {
+    let price = parse("2 ist no offers");
+    assert!(!price.is_offer);
+    assert_eq!(
+        components(&price),
+        [(2.0, "ist", CurrencyKind::Rune, false)]
+    );
+
+    assert!(parse_price("offering 2 ist").is_none_or(|price| !price.is_offer));
+    assert!(parse_price("coffers").is_none_or(|price| !price.is_offer));
+}
+
+#[test]
+fn trailing_punctuation_is_ignored() {
+    assert_eq!(
+        components(&parse("2 wss.")),
+        [(2.0, "wss", CurrencyKind::Shard, false)]
+    );
+    assert_eq!(
+        components(&parse("1 jah!")),
+        [(1.0, "jah", CurrencyKind::Rune, false)]
+    );
+}
+
+#[test]
+fn unknown_currency_is_kept_verbatim() {
+    assert_eq!(
+        components(&parse("5 Perfect Skulls")),
+        [(5.0, "perfect skulls", CurrencyKind::Other, false)]
+    );
+}
+
+#[test]
+fn empty_text_has_no_price() {
+    for text in ["", "   ", ".", "+", ", ,", "5"] {
+        assert!(parse_price(text).is_none(), "{:?}", text);
+    }
+}
+
+#[test]
+fn trade_message_carries_raw_and_parsed_price() {
+    let trade = parse_trade_message(
+        "2,From shrackx (*shrack): Hi, I'm interested in your Shako listed for 0.5 hr. Still there?",
+    )
+    .expect("trade");
+
+    assert_eq!(trade.price.as_deref(), Some("0.5 hr"));
+    let parsed = trade.parsed_price.expect("parsed price");
+    assert_eq!(
+        components(&parsed),
+        [(0.5, "hr", CurrencyKind::RuneClass, false)]
+    );
+}
+
+#[test]
+fn trade_message_without_price_has_no_parsed_price() {
+    let trade =
+        parse_trade_message("2,From nolisting (*nolist): Hi, I'm interested in your Arachnid Mesh")
+            .expect("trade");
+
+    assert!(trade.price.is_none());
+    assert!(trade.parsed_price.is_none());
+}
@@ -0,0 +1,236 @@
+//! Tests for storing whispers in the trade history and querying them back
+
+use dmg_meter_widget_lib::chat_watcher::{parse_chat_line, parse_trade_message};
+use dmg_meter_widget_lib::trade_history::{
+    TradeDirection, TradeHistory, TradeHistoryKind, TradeHistoryQuery,
+};
+
+fn record(history: &TradeHistory, line: &str, timestamp: u64) {
+    let chat_line = parse_chat_line(line).expect("chat line");
+    history
+        .record(&chat_line, parse_trade_message(line).as_ref(), timestamp)
+        .expect("record");
+}
+
+/// A night of trading: two buyers, a reply, a join and an unrelated line
+fn history() -> TradeHistory {
+    let history = TradeHistory::in_memory().expect("history");
+    for (line, timestamp) in [
+        (
+            "2,From shrackx (*shrack): Hi, I'm interested in your Frostburn listed for 2 wss",
+            1_000,
+        ),
+        ("2,Sent to shrackx (*shrack): sure, inv", 2_000),
+        (
+            "4,buyer(buyer) joined our world. Diablo's minions grow stronger.",
+            3_000,
+        ),
+        (
+            "2,From buyer (*buyer): Hi, I'm interested in your Shako listed for 1 ist",
+            4_000,
+        ),
+        (
+            "2,From shrackalt (*shrack): Hi, I'm interested in your Frostburn listed for 3 wss",
+            5_000,
+        ),
+        ("0,Someone: hello world", 6_000),
+    ] {
+        record(&history, line, timestamp);
+    }
+    history
+}
+
+fn timestamps(history: &TradeHistory, query: TradeHistoryQuery) -> Vec<u64> {
+    history
+        .query(&query)
+        .expect("query")
+        .iter()
+        .map(|entry| entry.timestamp)
+        .collect()
+}
+
+#[test]
+fn whispers_and_joins_are_stored_newest_first() {
+    let entries = history()
+        .query(&TradeHistoryQuery::default())
+        .expect("query");
+    let stored: Vec<(u64, TradeHistoryKind, TradeDirection)> = entries
+        .iter()
+        .map(|entry| (entry.timestamp, entry.kind, entry.direction))
+        .collect();
+    assert_eq!(
+        stored,
+        [
+            (5_000, TradeHistoryKind::Trade, TradeDirection::Incoming),
+            (4_000, TradeHistoryKind::Trade, TradeDirection::Incoming),
+            (3_000, TradeHistoryKind::Join, TradeDirection::Incoming),
+            (2_000, TradeHistoryKind::Whisper, TradeDirection::Outgoing),
+            (1_000, TradeHistoryKind::Trade, TradeDirection::Incoming),
+        ]
+    );
+
+    let newest = &entries[0];
+    assert_eq!(newest.player_name, "shrack");
+    assert_eq!(newest.account_name.as_deref(), Some("shrack"));
+    assert_eq!(newest.character_name.as_deref(), Some("shrackalt"));
+    assert_eq!(newest.item_name.as_deref(), Some("Frostburn"));
+    assert_eq!(newest.price.as_deref(), Some("3 wss"));
+}
+
+#[test]
+fn filters_combine() {
+    let history = history();
+    let cases = [
+        (
+            TradeHistoryQuery {
+                player: Some("SHRACK".to_string()),
+                ..TradeHistoryQuery::default()
+            },
+            vec![5_000, 2_000, 1_000],
+        ),
+        (
+            TradeHistoryQuery {
+                player: Some("shrackalt".to_string()),
+                ..TradeHistoryQuery::default()
+            },
+            vec![5_000],
+        ),
+        (
+            TradeHistoryQuery {
+                item: Some("frost".to_string()),
+                ..TradeHistoryQuery::default()
+            },
+            vec![5_000, 1_000],
+        ),
+        (
+            TradeHistoryQuery {
+                from: Some(2_000),
+                to: Some(4_000),
+                ..TradeHistoryQuery::default()
+            },
+            vec![4_000, 3_000, 2_000],
+        ),
+        (
+            TradeHistoryQuery {
+                direction: Some(TradeDirection::Outgoing),
+                ..TradeHistoryQuery::default()
+            },
+            vec![2_000],
+        ),
+        (
+            TradeHistoryQuery {
+                kind: Some(TradeHistoryKind::Join),
+                ..TradeHistoryQuery::default()
+            },
+            vec![3_000],
+        ),
+        (
+            TradeHistoryQuery {
+                player: Some("shrack".to_string()),
+                item: Some("Frostburn".to_string()),
+                to: Some(4_000),
+                direction: Some(TradeDirection::Incoming),
+                kind: Some(TradeHistoryKind::Trade),
+                ..TradeHistoryQuery::default()
+            },
+            vec![1_000],
+        ),
+        (
+            TradeHistoryQuery {
+                player: Some("buyer".to_string()),
+                direction: Some(TradeDirection::Outgoing),
+                ..TradeHistoryQuery::default()
+            },
+            vec![],
+        ),
+    ];
+
+    for (query, expected) in cases {
+        assert_eq!(timestamps(&history, query.clone()), expected, "{:?}", query);
+    }
+}
+
+#[test]
+fn item_wildcards_match_literally() {
+    let history = TradeHistory::in_memory().expect("history");
+    for (item, timestamp) in [
+        ("40% Jewel", 1_000),
+        ("400 Jewel", 2_000),
+        ("Ist_Rune", 3_000),
+        ("IstXRune", 4_000),
+    ] {
+        record(
+            &history,
+            &format!("2,From buyer (*buyer): Hi, I'm interested in your {item} listed for 1 ist"),
+            timestamp,
+        );
+    }
+
+    let items = |item: &str| {
+        history
+            .query(&TradeHistoryQuery {
+                item: Some(item.to_string()),
+                ..TradeHistoryQuery::default()
+            })
+            .expect("query")
+            .into_iter()
+            .map(|entry| entry.item_name.expect("item"))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(items("40%"), ["40% Jewel"]);
+    assert_eq!(items("t_r"), ["Ist_Rune"]);
+    assert_eq!(items("\\"), Vec::<String>::new());
+}
+
+#[test]
+fn results_page_with_limit_and_offset() {
+    let history = history();
+    let page = |limit, offset| {
+        timestamps(
+            &history,
+            TradeHistoryQuery {
+                limit: Some(limit),
+                offset: Some(offset),
+                ..TradeHistoryQuery::default()
+            },
+        )
+    };
+    assert_eq!(page(2, 0), [5_000, 4_000]);
+    assert_eq!(page(2, 2), [3_000, 2_000]);
+    assert_eq!(page(2, 4), [1_000]);
+    assert_eq!(page(2, 6), Vec::<u64>::new());
+}
+
+#[test]
+fn count_recorded_matches_direction_sender_and_text() {
+    let history = history();
+    let line = "2,From shrackx (*shrack): Hi, I'm interested in your Frostburn listed for 2 wss";
+    record(&history, line, 7_000);
+
+    let count = |line: &str, since| {
+        history
+            .count_recorded(&parse_chat_line(line).expect("chat line"), since)
+            .expect("count")
+    };
+    assert_eq!(count(line, 0), 2);
+    assert_eq!(count(line, 1_001), 1);
+    assert_eq!(count(line, 7_001), 0);
+
+    // Same text the other way, or from someone else, is a different row
+    assert_eq!(
+        count(
+            "2,Sent to shrackx (*shrack): Hi, I'm interested in your Frostburn listed for 2 wss",
+            0
+        ),
+        0
+    );
+    assert_eq!(
+        count(
+            "2,From other (*other): Hi, I'm interested in your Frostburn listed for 2 wss",
+            0
+        ),
+        0
+    );
+    // Lines the history doesn't store are never counted
+    assert_eq!(count("0,Someone: hello world", 0), 0);
+}
@@ -0,0 +1,342 @@
+//! Tests for grouping whispers into trade conversation threads
+
+use dmg_meter_widget_lib::chat_watcher::threads::STALE_AFTER_MS;
+use dmg_meter_widget_lib::chat_watcher::{
+    parse_chat_line, parse_trade_message, ThreadState, TradeThread, TradeThreads,
+};
+
+const TRADE: &str = "Hi, I'm interested in your Frostburn listed for 2 wss";
+
+/// Feed a log line through the parsers and the threads, like the watcher does
+fn feed(threads: &mut TradeThreads, line: &str, now: u64) -> Vec<TradeThread> {
+    let chat_line = parse_chat_line(line).expect("chat line");
+    let trade = parse_trade_message(line);
+    threads.apply(&chat_line, trade.as_ref(), now)
+}
+
+fn single(updated: Vec<TradeThread>) -> TradeThread {
+    assert_eq!(updated.len(), 1, "{:?}", updated);
+    updated.into_iter().next().unwrap()
+}
+
+#[test]
+fn trade_whisper_opens_a_new_thread() {
+    let mut threads = TradeThreads::default();
+    let thread = single(feed(
+        &mut threads,
+        &format!("2,From shrackx (*shrack): {}", TRADE),
+        1_000,
+    ));
+
+    assert_eq!(thread.state, ThreadState::New);
+    assert_eq!(thread.player_name, "shrack");
+    assert_eq!(thread.character_name, "shrackx");
+    assert!(thread.is_incoming);
+    assert_eq!(thread.item_name.as_deref(), Some("Frostburn"));
+    assert_eq!(thread.price.as_deref(), Some("2 wss"));
+    assert_eq!(thread.started_at, 1_000);
+    assert_eq!(thread.last_activity, 1_000);
+    assert_eq!(thread.messages.len(), 1);
+    assert!(thread.messages[0].is_trade);
+}
+
+#[test]
+fn follow_ups_and_replies_join_the_open_thread() {
+    let mut threads = TradeThreads::default();
+    let opened = single(feed(
+        &mut threads,
+        &format!("2,From shrackx (*shrack): {}", TRADE),
+        1_000,
+    ));
+
+    let thread = single(feed(
+        &mut threads,
+        "2,From shrackx (*shrack): still there?",
+        2_000,
+    ));
+    assert_eq!(thread.id, opened.id);
+    assert_eq!(thread.state, ThreadState::New);
+    assert_eq!(thread.messages.len(), 2);
+
+    let thread = single(feed(
+        &mut threads,
+        "2,Sent to shrackx (*shrack): yes, one sec",
+        3_000,
+    ));
+    assert_eq!(thread.id, opened.id);
+    assert_eq!(thread.state, ThreadState::Replied);
+    assert_eq!(thread.last_activity, 3_000);
+    assert!(!thread.messages[2].is_incoming);
+}
+
+#[test]
+fn account_links_messages_from_alts() {
+    let mut threads = TradeThreads::default();
+    let opened = single(feed(
+        &mut threads,
+        &format!("2,From shrackx (*shrack): {}", TRADE),
+        1_000,
+    ));
+
+    let thread = single(feed(
+        &mut threads,
+        "2,From shrackalt (*Shrack): hello?",
+        2_000,
+    ));
+    assert_eq!(thread.id, opened.id);
+    assert_eq!(thread.character_name, "shrackalt");
+}
+
+#[test]
+fn character_is_the_key_without_an_account() {
+    let mut threads = TradeThreads::default();
+    let opened = single(feed(
+        &mut threads,
+        &format!("2,From lonewolf: {}", TRADE),
+        1_000,
+    ));
+    assert_eq!(opened.player_name, "lonewolf");
+
+    let thread = single(feed(&mut threads, "2,From lonewolf: hi?", 2_000));
+    assert_eq!(thread.id, opened.id);
+}
+
+#[test]
+fn whispers_without_a_trade_are_not_threaded() {
+    let mut threads = TradeThreads::default();
+    assert!(feed(&mut threads, "2,From buyer (*buyer): are you there?", 1_000).is_empty());
+    assert!(feed(&mut threads, "0,Someone: hello world", 1_000).is_empty());
+    assert!(threads.threads().is_empty());
+}
+
+#[test]
+fn invite_then_join_then_leave_completes_the_trade() {
+    let mut threads = TradeThreads::default();
+    feed(
+        &mut threads,
+        &format!("2,From shrackx (*shrack): {}", TRADE),
+        1_000,
+    );
+
+    let thread = single(feed(
+        &mut threads,
+        "2,Sent to shrackx (*shrack): Your offer has been accepted. Game: trade-1////123",
+        2_000,
+    ));
+    assert_eq!(thread.state, ThreadState::Invited);
+
+    let thread = single(feed(
+        &mut threads,
+        "4,shrackx(shrack) joined our world. Diablo's minions grow stronger.",
+        3_000,
+    ));
+    assert_eq!(thread.state, ThreadState::Invited);
+    assert_eq!(thread.last_activity, 3_000);
+
+    let thread = single(feed(
+        &mut threads,
+        "4,shrackx(shrack) left our world. Diablo's minions weaken.",
+        4_000,
+    ));
+    assert_eq!(thread.state, ThreadState::Completed);
+
+    // Closed threads take no more messages
+    assert!(feed(&mut threads, "2,From shrackx (*shrack): thanks!", 5_000).is_empty());
+}
+
+#[test]
+fn only_whole_word_invites_count() {
+    for reply in [
+        "let me check my inventory",
+        "that price is invalid",
+        "he joined already",
+        "see https://pd2.tools for prices",
+    ] {
+        let mut threads = TradeThreads::default();
+        feed(
+            &mut threads,
+            &format!("2,From shrackx (*shrack): {}", TRADE),
+            1_000,
+        );
+        let thread = single(feed(
+            &mut threads,
+            &format!("2,Sent to shrackx (*shrack): {}", reply),
+            2_000,
+        ));
+        assert_eq!(thread.state, ThreadState::Replied, "{:?}", reply);
+    }
+
+    for reply in [
+        "inv",
+        "Invite sent",
+        "join me",
+        "game:trade-2",
+        "trade-3//pw",
+    ] {
+        let mut threads = TradeThreads::default();
+        feed(
+            &mut threads,
+            &format!("2,From shrackx (*shrack): {}", TRADE),
+            1_000,
+        );
+        let thread = single(feed(
+            &mut threads,
+            &format!("2,Sent to shrackx (*shrack): {}", reply),
+            2_000,
+        ));
+        assert_eq!(thread.state, ThreadState::Invited, "{:?}", reply);
+    }
+}
+
+#[test]
+fn sold_reply_completes_the_thread() {
+    let mut threads = TradeThreads::default();
+    feed(
+        &mut threads,
+        &format!("2,From shrackx (*shrack): {}", TRADE),
+        1_000,
+    );
+    let thread = single(feed(
+        &mut threads,
+        "2,Sent to shrackx (*shrack): The item has been sold.",
+        2_000,
+    ));
+    assert_eq!(thread.state, ThreadState::Completed);
+}
+
+#[test]
+fn only_whole_word_closings_count() {
+    for (reply, state) in [
+        (
+            "2,Sent to shrackx (*shrack): sold, sorry",
+            ThreadState::Completed,
+        ),
+        (
+            "2,Sent to shrackx (*shrack): Declined.",
+            ThreadState::Completed,
+        ),
+        (
+            "2,Sent to shrackx (*shrack): ty for the trade!",
+            ThreadState::Completed,
+        ),
+        (
+            "2,Sent to shrackx (*shrack): my soldier is afk",
+            ThreadState::Replied,
+        ),
+        (
+            "2,Sent to shrackx (*shrack): it's unsold, still up",
+            ThreadState::Replied,
+        ),
+    ] {
+        let mut threads = TradeThreads::default();
+        feed(
+            &mut threads,
+            &format!("2,From shrackx (*shrack): {}", TRADE),
+            1_000,
+        );
+        let thread = single(feed(&mut threads, reply, 2_000));
+        assert_eq!(thread.state, state, "{:?}", reply);
+    }
+}
+
+#[test]
+fn repeated_trade_whispers_join_the_open_thread() {
+    let mut threads = TradeThreads::default();
+    let first = single(feed(
+        &mut threads,
+        &format!("2,From shrackx (*shrack): {}", TRADE),
+        1_000,
+    ));
+    feed(&mut threads, "2,Sent to shrackx (*shrack): one sec", 2_000);
+    let again = single(feed(
+        &mut threads,
+        "2,From shrackalt (*shrack): Hi, I'm interested in your frostburn listed for 3 wss",
+        3_000,
+    ));
+
+    assert_eq!(again.id, first.id);
+    assert_eq!(again.state, ThreadState::Replied);
+    assert_eq!(again.character_name, "shrackalt");
+    assert_eq!(again.price.as_deref(), Some("3 wss"));
+    assert_eq!(again.started_at, 1_000);
+    assert_eq!(again.last_activity, 3_000);
+    assert_eq!(again.messages.len(), 3);
+    assert_eq!(threads.threads().len(), 1);
+
+    // Once the thread is closed the same item starts over
+    feed(&mut threads, "2,Sent to shrackx (*shrack): sold", 4_000);
+    let next = single(feed(
+        &mut threads,
+        &format!("2,From shrackx (*shrack): {}", TRADE),
+        5_000,
+    ));
+    assert_ne!(next.id, first.id);
+    assert_eq!(next.state, ThreadState::New);
+}
+
+#[test]
+fn a_new_trade_whisper_starts_a_second_thread() {
+    let mut threads = TradeThreads::default();
+    let first = single(feed(
+        &mut threads,
+        &format!("2,From shrackx (*shrack): {}", TRADE),
+        1_000,
+    ));
+    let second = single(feed(
+        &mut threads,
+        "2,From shrackx (*shrack): Hi, I'm interested in your Shako listed for 1 ist",
+        2_000,
+    ));
+    assert_ne!(first.id, second.id);
+
+    // Follow-ups go to the most recent trade
+    let thread = single(feed(&mut threads, "2,From shrackx (*shrack): both?", 3_000));
+    assert_eq!(thread.id, second.id);
+
+    let all = threads.threads();
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].id, second.id);
+}
+
+#[test]
+fn outgoing_trade_whisper_opens_a_thread() {
+    let mut threads = TradeThreads::default();
+    let thread = single(feed(
+        &mut threads,
+        &format!("2,Sent to Shrackb (*shrack): {}", TRADE),
+        1_000,
+    ));
+    assert!(!thread.is_incoming);
+    assert_eq!(thread.state, ThreadState::New);
+}
+
+#[test]
+fn inactive_threads_go_stale() {
+    let mut threads = TradeThreads::default();
+    let opened = single(feed(
+        &mut threads,
+        &format!("2,From shrackx (*shrack): {}", TRADE),
+        1_000,
+    ));
+
+    assert!(threads.expire(1_000 + STALE_AFTER_MS - 1).is_empty());
+
+    // The next line reports the stale thread along with its own update
+    let updated = feed(
+        &mut threads,
+        &format!("2,From other (*other): {}", TRADE),
+        1_000 + STALE_AFTER_MS,
+    );
+    assert_eq!(updated.len(), 2);
+    assert_eq!(updated[0].id, opened.id);
+    assert_eq!(updated[0].state, ThreadState::Stale);
+    assert_eq!(updated[1].state, ThreadState::New);
+
+    // Stale threads take no more follow-ups
+    assert!(feed(
+        &mut threads,
+        "2,From shrackx (*shrack): hello?",
+        1_000 + STALE_AFTER_MS
+    )
+    .is_empty());
+}
@@ -0,0 +1,105 @@
+//! Tests for chat watcher errors and the chat-watcher-status events
+
+mod common;
+
+use common::{collecting_watcher, d2_dir, wait_for};
+use dmg_meter_widget_lib::chat_watcher::{ChatWatcherError, WatchHealth, WatchMode};
+use std::path::PathBuf;
+
+#[test]
+fn error_kinds_and_messages() {
+    let read = ChatWatcherError::Read {
+        path: PathBuf::from("pd2_chat.log"),
+        source: std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied"),
+    };
+    assert_eq!(read.kind(), "read");
+    assert_eq!(read.to_string(), "Failed to read pd2_chat.log: denied");
+
+    let lock = ChatWatcherError::LockPoisoned("chat watcher");
+    assert_eq!(lock.kind(), "lockPoisoned");
+    assert_eq!(String::from(lock), "Failed to lock chat watcher");
+
+    // Shown to the user as is, so the wording stays the same as before
+    assert_eq!(
+        ChatWatcherError::LogNotFound.to_string(),
+        "Could not find or create chat log file. Please check your Diablo II Directory settings."
+    );
+}
+
+#[test]
+fn start_and_stop_are_reported() {
+    let dir = d2_dir("status-start-stop");
+    let (watcher, events) = collecting_watcher(&["chat-watcher-status"]);
+    watcher
+        .start_headless(
+            Some(dir.to_string_lossy().into_owned()),
+            WatchMode::Poll,
+            None,
+        )
+        .expect("start");
+
+    let status = watcher.status();
+    assert_eq!(status.health, Some(WatchHealth::Healthy));
+    assert!(status.errors.is_empty());
+
+    watcher.stop().expect("stop");
+    let seen = wait_for(&events, 2);
+    assert_eq!(seen.len(), 2);
+    assert_eq!(seen[0].payload["health"], "started");
+    assert_eq!(seen[0].payload["backend"], "poll");
+    assert_eq!(seen[1].payload["health"], "stopped");
+    assert_eq!(seen[1].payload["reason"], "Stopped");
+    assert!(seen[1].payload["backend"].is_null());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn restarting_stops_the_previous_watch() {
+    let dir = d2_dir("status-restart");
+    let (watcher, events) = collecting_watcher(&["chat-watcher-status"]);
+    let d2_dir = Some(dir.to_string_lossy().into_owned());
+    watcher
+        .start_headless(d2_dir.clone(), WatchMode::Poll, None)
+        .expect("start");
+    watcher
+        .start_headless(d2_dir, WatchMode::Poll, None)
+        .expect("restart");
+    watcher.stop().expect("stop");
+
+    let seen = wait_for(&events, 4);
+    let health: Vec<&str> = seen
+        .iter()
+        .filter_map(|event| event.payload["health"].as_str())
+        .collect();
+    assert_eq!(health, ["started", "stopped", "started", "stopped"]);
+    assert_eq!(seen[1].payload["reason"], "Restarted");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn failing_to_start_reports_why() {
+    // A file where the Diablo II directory should be, so the logs directory can't be created
+    let dir = d2_dir("status-no-log");
+    let not_a_dir = dir.join("Diablo II");
+    std::fs::write(&not_a_dir, "").expect("write");
+
+    let (watcher, events) = collecting_watcher(&["chat-watcher-status"]);
+    let error = watcher
+        .start_headless(
+            Some(not_a_dir.to_string_lossy().into_owned()),
+            WatchMode::Poll,
+            None,
+        )
+        .unwrap_err();
+    assert!(matches!(error, ChatWatcherError::LogNotFound));
+    assert!(!watcher.status().running);
+
+    let seen = wait_for(&events, 1);
+    assert_eq!(seen.len(), 1);
+    assert_eq!(seen[0].payload["health"], "stopped");
+    assert_eq!(seen[0].payload["reason"], error.to_string());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,297 @@
+//! Tests for the whisper ignore list, rate limit, duplicate suppression and spam rules
+
+use dmg_meter_widget_lib::chat_watcher::{
+    parse_chat_line, SuppressReason, WhisperFilter, WhisperFilterConfig,
+};
+
+/// Rules off unless a test turns them on
+fn config() -> WhisperFilterConfig {
+    WhisperFilterConfig {
+        ignore: Vec::new(),
+        rate_limit: 0,
+        rate_limit_window_secs: 0,
+        duplicate_window_secs: 0,
+        spam_patterns: Vec::new(),
+    }
+}
+
+fn check(filter: &mut WhisperFilter, line: &str, now: u64) -> Option<SuppressReason> {
+    filter.check(&parse_chat_line(line).expect("chat line"), now)
+}
+
+#[test]
+fn exact_ignores_match_account_or_character() {
+    let mut filter = WhisperFilter::new(WhisperFilterConfig {
+        ignore: vec!["Shrack".to_string(), "lonewolf".to_string()],
+        ..config()
+    })
+    .expect("filter");
+
+    for line in [
+        "2,From shrackx (*shrack): hi",
+        "2,From lonewolf: hi",
+        "2,From LoneWolf (*other): hi",
+        "4,shrackx(shrack) joined our world. Diablo's minions grow stronger.",
+    ] {
+        assert_eq!(
+            check(&mut filter, line, 0),
+            Some(SuppressReason::Ignored),
+            "{:?}",
+            line
+        );
+    }
+    assert_eq!(
+        check(&mut filter, "2,From shrackxx (*shrackxx): hi", 0),
+        None
+    );
+}
+
+#[test]
+fn wildcard_ignores() {
+    let mut filter = WhisperFilter::new(WhisperFilterConfig {
+        ignore: vec!["*gold*".to_string(), "bot_*".to_string()],
+        ..config()
+    })
+    .expect("filter");
+
+    assert_eq!(
+        check(&mut filter, "2,From cheapgoldseller (*x): hi", 0),
+        Some(SuppressReason::Ignored)
+    );
+    assert_eq!(
+        check(&mut filter, "2,From someone (*BOT_42): hi", 0),
+        Some(SuppressReason::Ignored)
+    );
+    assert_eq!(check(&mut filter, "2,From robot_1 (*robot_1): hi", 0), None);
+}
+
+#[test]
+fn wildcard_patterns_are_otherwise_literal() {
+    let mut filter = WhisperFilter::new(WhisperFilterConfig {
+        ignore: vec!["a.b".to_string()],
+        ..config()
+    })
+    .expect("filter");
+
+    assert_eq!(check(&mut filter, "2,From axb: hi", 0), None);
+    assert_eq!(
+        check(&mut filter, "2,From a.b: hi", 0),
+        Some(SuppressReason::Ignored)
+    );
+}
+
+#[test]
+fn outgoing_whispers_are_never_filtered() {
+    let mut filter = WhisperFilter::new(WhisperFilterConfig {
+        ignore: vec!["*".to_string()],
+        ..config()
+    })
+    .expect("filter");
+    assert_eq!(
+        check(&mut filter, "2,Sent to shrackx (*shrack): hi", 0),
+        None
+    );
+}
+
+#[test]
+fn spam_patterns_match_message_text() {
+    let mut filter = WhisperFilter::new(WhisperFilterConfig {
+        spam_patterns: vec![
+            r"www\.\S+\.(com|net)".to_string(),
+            "cheap runes".to_string(),
+        ],
+        ..config()
+    })
+    .expect("filter");
+
+    assert_eq!(
+        check(&mut filter, "2,From x (*x): visit WWW.RUNES4U.COM now", 0),
+        Some(SuppressReason::Spam)
+    );
+    assert_eq!(
+        check(&mut filter, "2,From x (*x): CHEAP RUNES here", 0),
+        Some(SuppressReason::Spam)
+    );
+    assert_eq!(
+        check(&mut filter, "2,From x (*x): is the shako available?", 0),
+        None
+    );
+}
+
+#[test]
+fn invalid_spam_pattern_is_rejected() {
+    let error = WhisperFilter::new(WhisperFilterConfig {
+        spam_patterns: vec!["(unclosed".to_string()],
+        ..config()
+    })
+    .err()
+    .expect("error");
+    assert!(error.contains("(unclosed"), "{}", error);
+}
+
+#[test]
+fn duplicates_within_the_window_are_dropped() {
+    let mut filter = WhisperFilter::new(WhisperFilterConfig {
+        duplicate_window_secs: 30,
+        ..config()
+    })
+    .expect("filter");
+
+    let line = "2,From shrackx (*shrack): still there?";
+    assert_eq!(check(&mut filter, line, 0), None);
+    assert_eq!(
+        check(&mut filter, line, 10_000),
+        Some(SuppressReason::Duplicate)
+    );
+    // Other text and other senders are unaffected
+    assert_eq!(
+        check(&mut filter, "2,From shrackx (*shrack): hello?", 10_000),
+        None
+    );
+    assert_eq!(
+        check(&mut filter, "2,From other (*other): still there?", 10_000),
+        None
+    );
+    // The window restarts from the latest copy
+    assert_eq!(
+        check(&mut filter, line, 39_000),
+        Some(SuppressReason::Duplicate)
+    );
+    assert_eq!(check(&mut filter, line, 70_000), None);
+}
+
+#[test]
+fn rate_limit_per_sender() {
+    let mut filter = WhisperFilter::new(WhisperFilterConfig {
+        rate_limit: 3,
+        rate_limit_window_secs: 60,
+        ..config()
+    })
+    .expect("filter");
+
+    for i in 0..3 {
+        let line = format!("2,From shrackx (*shrack): message {}", i);
+        assert_eq!(check(&mut filter, &line, i * 1_000), None);
+    }
+    assert_eq!(
+        check(&mut filter, "2,From shrackx (*shrack): message 3", 3_000),
+        Some(SuppressReason::RateLimited)
+    );
+    // Alts on the same account share the limit
+    assert_eq!(
+        check(&mut filter, "2,From shrackalt (*shrack): message 4", 4_000),
+        Some(SuppressReason::RateLimited)
+    );
+    assert_eq!(check(&mut filter, "2,From other (*other): hi", 4_000), None);
+
+    // Once the earlier whispers leave the window the sender is let through again
+    assert_eq!(
+        check(&mut filter, "2,From shrackx (*shrack): later", 65_000),
+        None
+    );
+}
+
+#[test]
+fn default_filter_only_applies_the_ignore_list() {
+    let mut filter = WhisperFilter::new(WhisperFilterConfig {
+        ignore: vec!["shrack".to_string()],
+        ..WhisperFilterConfig::default()
+    })
+    .expect("filter");
+
+    for _ in 0..20 {
+        assert_eq!(
+            check(&mut filter, "2,From other (*other): same text", 0),
+            None
+        );
+    }
+    assert_eq!(
+        check(&mut filter, "2,From shrackx (*shrack): hi", 0),
+        Some(SuppressReason::Ignored)
+    );
+}
+
+#[test]
+fn replayed_lines_only_face_ignore_and_spam_rules() {
+    let mut filter = WhisperFilter::new(WhisperFilterConfig {
+        ignore: vec!["shrack".to_string()],
+        duplicate_window_secs: 30,
+        ..config()
+    })
+    .expect("filter");
+
+    let line = parse_chat_line("2,From other (*other): same text").expect("chat line");
+    assert_eq!(filter.check_replayed(&line), None);
+    assert_eq!(filter.check_replayed(&line), None);
+
+    let ignored = parse_chat_line("2,From shrackx (*shrack): hi").expect("chat line");
+    assert_eq!(
+        filter.check_replayed(&ignored),
+        Some(SuppressReason::Ignored)
+    );
+}
+
+#[test]
+fn suppressed_lines_are_counted_per_rule() {
+    let mut filter = WhisperFilter::new(WhisperFilterConfig {
+        ignore: vec!["shrack".to_string()],
+        duplicate_window_secs: 30,
+        spam_patterns: vec!["cheap".to_string()],
+        ..config()
+    })
+    .expect("filter");
+
+    check(&mut filter, "2,From shrackx (*shrack): hi", 0);
+    check(&mut filter, "2,From x (*x): cheap stuff", 0);
+    check(&mut filter, "2,From y (*y): hi", 0);
+    check(&mut filter, "2,From y (*y): hi", 1_000);
+    check(&mut filter, "2,From z (*z): hi", 1_000);
+
+    let counts = filter.counts();
+    assert_eq!(counts.ignored, 1);
+    assert_eq!(counts.spam, 1);
+    assert_eq!(counts.duplicate, 1);
+    assert_eq!(counts.rate_limited, 0);
+    assert_eq!(counts.total, 3);
+
+    // New rules keep the running counts
+    filter.replace(WhisperFilter::new(config()).expect("filter"));
+    assert_eq!(filter.counts().total, 3);
+    assert_eq!(
+        check(&mut filter, "2,From shrackx (*shrack): hi", 2_000),
+        None
+    );
+}
+
+#[test]
+fn config_round_trips_through_a_file() {
+    let path = std::env::temp_dir().join(format!(
+        "pd2-trader-whisper-filter-{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(
+        WhisperFilter::load_config(&path).expect("defaults"),
+        WhisperFilterConfig::default()
+    );
+
+    let config = WhisperFilterConfig {
+        ignore: vec!["*gold*".to_string()],
+        spam_patterns: vec!["cheap".to_string()],
+        ..WhisperFilterConfig::default()
+    };
+    WhisperFilter::new(config.clone())
+        .expect("filter")
+        .save_config(&path)
+        .expect("save");
+    assert_eq!(WhisperFilter::load_config(&path).expect("load"), config);
+
+    // Missing fields fall back to the defaults
+    std::fs::write(&path, r#"{"ignore": ["bot_*"]}"#).expect("write");
+    let loaded = WhisperFilter::load_config(&path).expect("load partial");
+    assert_eq!(loaded.ignore, ["bot_*"]);
+    assert_eq!(loaded.rate_limit, WhisperFilterConfig::default().rate_limit);
+
+    let _ = std::fs::remove_file(&path);
+}
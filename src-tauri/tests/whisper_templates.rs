@@ -0,0 +1,215 @@
+//! Tests for the shipped whisper templates and user-defined template sets
+
+use dmg_meter_widget_lib::chat_watcher::parser::{
+    trade_message_from_chat_line, whisper_from_chat_line,
+};
+use dmg_meter_widget_lib::chat_watcher::templates::SHIPPED_TEMPLATES;
+use dmg_meter_widget_lib::chat_watcher::{parse_chat_line, WhisperTemplate, WhisperTemplates};
+
+fn template(name: &str, pattern: &str) -> WhisperTemplate {
+    WhisperTemplate {
+        name: name.to_string(),
+        pattern: pattern.to_string(),
+    }
+}
+
+#[test]
+fn pd2_trade_template() {
+    let found = SHIPPED_TEMPLATES
+        .find("Hi, I'm interested in your Frostburn listed for 2 wss")
+        .expect("match");
+    assert_eq!(found.template, "pd2-trade");
+    assert!(found.at_start);
+    assert_eq!(found.item.as_deref(), Some("Frostburn"));
+    assert_eq!(found.price.as_deref(), Some("2 wss"));
+    assert_eq!(found.quantity, None);
+    assert_eq!(found.listing_id, None);
+}
+
+#[test]
+fn pd2_trade_template_price_stops_at_sentence_end() {
+    for (message, price) in [
+        (
+            "Hi, I'm interested in your Shako listed for 2 wss.",
+            "2 wss",
+        ),
+        (
+            "Hi, I'm interested in your Shako listed for 2 wss. Still there?",
+            "2 wss",
+        ),
+        (
+            "Hi, I'm interested in your Shako listed for 0.5 hr",
+            "0.5 hr",
+        ),
+        (
+            "Hi, I'm interested in your Shako listed for 1 ber + 1 jah",
+            "1 ber + 1 jah",
+        ),
+    ] {
+        let found = SHIPPED_TEMPLATES.find(message).expect("match");
+        assert_eq!(found.price.as_deref(), Some(price), "{:?}", message);
+    }
+}
+
+#[test]
+fn pd2_trade_template_quantity_and_listing_id() {
+    let found = SHIPPED_TEMPLATES
+        .find("Hi, I'm interested in your 3x Key of Terror listed for 1 ist. (listing #65f1c0ab)")
+        .expect("match");
+    assert_eq!(found.template, "pd2-trade");
+    assert_eq!(found.quantity, Some(3));
+    assert_eq!(found.item.as_deref(), Some("Key of Terror"));
+    assert_eq!(found.price.as_deref(), Some("1 ist"));
+    assert_eq!(found.listing_id.as_deref(), Some("65f1c0ab"));
+}
+
+#[test]
+fn pd2_trade_template_without_listing() {
+    let found = SHIPPED_TEMPLATES
+        .find("Hi, I'm interested in your Arachnid Mesh")
+        .expect("match");
+    assert_eq!(found.template, "pd2-trade");
+    assert_eq!(found.item, None);
+    assert_eq!(found.price, None);
+}
+
+#[test]
+fn pd2_trade_template_after_other_text() {
+    let found = SHIPPED_TEMPLATES
+        .find("wts? Hi, I'm interested in your Gheed's Fortune listed for 1 pul")
+        .expect("match");
+    assert!(!found.at_start);
+    assert_eq!(found.item.as_deref(), Some("Gheed's Fortune"));
+}
+
+#[test]
+fn offer_template() {
+    for (message, quantity, item, price) in [
+        (
+            "I'd like to offer 1 ber for your Harlequin Crest",
+            None,
+            "Harlequin Crest",
+            "1 ber",
+        ),
+        (
+            "Hi, I would like to offer 2 ist for your Shako.",
+            None,
+            "Shako",
+            "2 ist",
+        ),
+        (
+            "hey I can offer 3 wss for your 2x Frostburn!",
+            Some(2),
+            "Frostburn",
+            "3 wss",
+        ),
+    ] {
+        let found = SHIPPED_TEMPLATES.find(message).expect("match");
+        assert_eq!(found.template, "offer", "{:?}", message);
+        assert!(found.at_start, "{:?}", message);
+        assert_eq!(found.quantity, quantity, "{:?}", message);
+        assert_eq!(found.item.as_deref(), Some(item), "{:?}", message);
+        assert_eq!(found.price.as_deref(), Some(price), "{:?}", message);
+    }
+}
+
+#[test]
+fn wtb_template() {
+    for (message, quantity, item, price) in [
+        (
+            "WTB your Harlequin Crest for 1 ber",
+            None,
+            "Harlequin Crest",
+            "1 ber",
+        ),
+        ("wtb: Shako for 2 ist?", None, "Shako", "2 ist"),
+        (
+            "wtb 3x Key of Terror @ 1 ist",
+            Some(3),
+            "Key of Terror",
+            "1 ist",
+        ),
+    ] {
+        let found = SHIPPED_TEMPLATES.find(message).expect("match");
+        assert_eq!(found.template, "wtb", "{:?}", message);
+        assert_eq!(found.quantity, quantity, "{:?}", message);
+        assert_eq!(found.item.as_deref(), Some(item), "{:?}", message);
+        assert_eq!(found.price.as_deref(), Some(price), "{:?}", message);
+    }
+}
+
+#[test]
+fn ordinary_whispers_match_no_template() {
+    for message in [
+        "are you there?",
+        "yes, joining now",
+        "wtb",
+        "thanks for the trade!",
+    ] {
+        assert!(SHIPPED_TEMPLATES.find(message).is_none(), "{:?}", message);
+    }
+}
+
+#[test]
+fn invalid_pattern_is_rejected_with_template_name() {
+    let error = WhisperTemplates::new(vec![template("broken", "(unclosed")]).unwrap_err();
+    assert!(error.contains("broken"), "{}", error);
+}
+
+#[test]
+fn custom_templates_drive_both_parsers() {
+    let templates = WhisperTemplates::new(vec![template(
+        "german",
+        r"^Hallo, ich interessiere mich für dein (?P<item>.+?) für (?P<price>.+)$",
+    )])
+    .expect("compile");
+    let line = parse_chat_line(
+        "2,From kaeufer (*kaeufer): Hallo, ich interessiere mich für dein Shako für 1 ist",
+    )
+    .expect("line");
+
+    let whisper = whisper_from_chat_line(&line, &templates).expect("whisper");
+    assert!(whisper.is_trade);
+    assert_eq!(whisper.item_name.as_deref(), Some("Shako"));
+
+    let trade = trade_message_from_chat_line(&line, &templates).expect("trade");
+    assert_eq!(trade.template, "german");
+    assert_eq!(trade.item_name.as_deref(), Some("Shako"));
+    assert_eq!(trade.price.as_deref(), Some("1 ist"));
+
+    // The shipped wording is no longer recognised once replaced
+    let line = parse_chat_line(
+        "2,From shrackx (*shrack): Hi, I'm interested in your Frostburn listed for 2 wss",
+    )
+    .expect("line");
+    assert!(
+        !whisper_from_chat_line(&line, &templates)
+            .expect("whisper")
+            .is_trade
+    );
+    assert!(trade_message_from_chat_line(&line, &templates).is_none());
+}
+
+#[test]
+fn templates_round_trip_through_a_file() {
+    let path = std::env::temp_dir().join(format!(
+        "pd2-trader-whisper-templates-{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    // Nothing saved yet
+    let loaded = WhisperTemplates::load(&path).expect("load shipped");
+    assert_eq!(loaded.templates(), SHIPPED_TEMPLATES.templates());
+
+    let custom =
+        WhisperTemplates::new(vec![template("custom", r"^buy (?P<item>.+)$")]).expect("compile");
+    custom.save(&path).expect("save");
+    let loaded = WhisperTemplates::load(&path).expect("load saved");
+    assert_eq!(loaded.templates(), custom.templates());
+
+    std::fs::write(&path, r#"[{"name": "bad", "pattern": "("}]"#).expect("write");
+    assert!(WhisperTemplates::load(&path).is_err());
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -25,6 +25,7 @@ const chatFormSchema = z.object({
   acceptOfferMessageTemplate: z.string().optional(),
   rejectOfferMessageTemplate: z.string().optional(),
   soldOfferMessageTemplate: z.string().optional(),
+  offerMessagesInGame: z.boolean().optional(),
 });
 
 type ChatFormValues = z.infer<typeof chatFormSchema>;
@@ -48,6 +49,7 @@ export function ChatForm() {
         settings?.acceptOfferMessageTemplate || 'Your offer has been accepted. Game: {gameInfo}',
       rejectOfferMessageTemplate: settings?.rejectOfferMessageTemplate || 'Your offer has been rejected.',
       soldOfferMessageTemplate: settings?.soldOfferMessageTemplate || 'The item has been sold.',
+      offerMessagesInGame: settings?.offerMessagesInGame ?? false,
     },
   });
 
@@ -66,6 +68,7 @@ export function ChatForm() {
           settings.acceptOfferMessageTemplate || 'Your offer has been accepted. Game: {gameInfo}',
         rejectOfferMessageTemplate: settings.rejectOfferMessageTemplate || 'Your offer has been rejected.',
         soldOfferMessageTemplate: settings.soldOfferMessageTemplate || 'The item has been sold.',
+        offerMessagesInGame: settings.offerMessagesInGame ?? false,
       });
     }
   }, [settings, form]);
@@ -291,6 +294,25 @@ export function ChatForm() {
               </FormItem>
             )}
           />
+          <FormField
+            control={form.control}
+            name="offerMessagesInGame"
+            render={({ field }) => (
+              <FormItem>
+                <div className="flex flex-row items-center gap-2">
+                  <FormLabel>Send Offer Messages In Game</FormLabel>
+                  <FormControl>
+                    <Switch checked={field.value ?? false}
+                      onCheckedChange={field.onChange} />
+                  </FormControl>
+                </div>
+                <FormDescription>
+                  Type accept, reject and sold messages into the Diablo II chat instead of copying them. Falls back to
+                  copying when the game can't be focused.
+                </FormDescription>
+              </FormItem>
+            )}
+          />
           <FormField
             control={form.control}
             name="acceptOfferMessageTemplate"
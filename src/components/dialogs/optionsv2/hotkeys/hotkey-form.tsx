@@ -1,13 +1,24 @@
 import { useForm } from 'react-hook-form';
 import { zodResolver } from '@hookform/resolvers/zod';
 import { z } from 'zod';
-import { Form, FormControl, FormField, FormItem, FormLabel, FormMessage } from '@/components/ui/form';
+import {
+  Form,
+  FormControl,
+  FormDescription,
+  FormField,
+  FormItem,
+  FormLabel,
+  FormMessage,
+} from '@/components/ui/form';
 import React from 'react';
 import { Tabs, TabsList, TabsTrigger } from '@/components/ui/tabs';
 import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
+import { Switch } from '@/components/ui/switch';
 import { useOptions } from '@/hooks/useOptions';
 import { emit } from '@/lib/browser-events';
+import { isTauri, invoke } from '@tauri-apps/api/core';
+import { formatHotkey, HotkeyAction, HotkeyBinding, HotkeyProblem } from '@/hooks/useShortcuts';
 import { Loader2 } from 'lucide-react';
 import { ScrollArea } from '@/components/ui/scroll-area';
 
@@ -49,6 +60,7 @@ const hotkeyFormSchema = z
       .min(1, 'Enter a key')
       .max(1, 'Only one character allowed')
       .regex(/^[a-z0-9]$/i, 'Must be a letter or number'),
+    hotkeyInputCapture: z.boolean().optional(),
   })
   .refine((data) => !(data.hotkeyModifier === 'ctrl' && data.hotkeyKey?.toLowerCase() === 'c'), {
     message: 'Ctrl + C is not allowed (reserved system shortcut).',
@@ -57,6 +69,21 @@ const hotkeyFormSchema = z
 
 type HotkeyFormValues = z.infer<typeof hotkeyFormSchema>;
 
+type ModifierField = 'hotkeyModifier' | 'hotkeyModifierListItem' | 'hotkeyModifierSettings'
+  | 'hotkeyModifierCurrencyValuation' | 'hotkeyModifierChat' | 'hotkeyModifierOffers';
+type KeyField = 'hotkeyKey' | 'hotkeyKeyListItem' | 'hotkeyKeySettings'
+  | 'hotkeyKeyCurrencyValuation' | 'hotkeyKeyChat' | 'hotkeyKeyOffers';
+
+// Form fields of each hotkey, in the backend registry's terms
+const HOTKEY_FIELDS: [HotkeyAction, ModifierField, KeyField][] = [
+  ['priceCheck', 'hotkeyModifier', 'hotkeyKey'],
+  ['listItem', 'hotkeyModifierListItem', 'hotkeyKeyListItem'],
+  ['settings', 'hotkeyModifierSettings', 'hotkeyKeySettings'],
+  ['currencyValuation', 'hotkeyModifierCurrencyValuation', 'hotkeyKeyCurrencyValuation'],
+  ['chat', 'hotkeyModifierChat', 'hotkeyKeyChat'],
+  ['offers', 'hotkeyModifierOffers', 'hotkeyKeyOffers'],
+];
+
 export function HotkeyForm() {
   const { settings, isLoading, updateSettings } = useOptions();
   const [saving, setSaving] = React.useState(false);
@@ -76,6 +103,7 @@ export function HotkeyForm() {
       hotkeyKeyChat: 't',
       hotkeyModifierOffers: 'ctrl',
       hotkeyKeyOffers: 'b',
+      hotkeyInputCapture: false,
     },
   });
 
@@ -90,6 +118,20 @@ export function HotkeyForm() {
   }
 
   const onSubmit = async (values: HotkeyFormValues) => {
+    // Let the backend registry check for clashes with each other and with the game's keys
+    if (isTauri()) {
+      const bindings: HotkeyBinding[] = HOTKEY_FIELDS.map(([action, modifierField, keyField]) => ({
+        action,
+        sequence: formatHotkey(values[modifierField], values[keyField]),
+      }));
+      const problems = await invoke<HotkeyProblem[]>('check_hotkeys', { bindings });
+      for (const problem of problems) {
+        const fields = HOTKEY_FIELDS.find(([action]) => action === problem.action);
+        if (fields) form.setError(fields[2], { message: problem.message });
+      }
+      if (problems.length > 0) return;
+    }
+
     setSaving(true);
     await updateSettings(values);
     await new Promise((resolve) => setTimeout(resolve, 200)); // artificial delay
@@ -378,6 +420,25 @@ export function HotkeyForm() {
               )}
             />
           </div>
+          <FormField
+            control={form.control}
+            name="hotkeyInputCapture"
+            render={({ field }) => (
+              <FormItem className="mt-2">
+                <div className="flex flex-row items-center gap-2">
+                  <FormLabel>Key listener fallback</FormLabel>
+                  <FormControl>
+                    <Switch checked={field.value ?? false}
+                      onCheckedChange={field.onChange} />
+                  </FormControl>
+                </div>
+                <FormDescription>
+                  Use if hotkeys don't work, e.g. on Wayland or with the game running under Proton.
+                </FormDescription>
+                <FormMessage />
+              </FormItem>
+            )}
+          />
         </form>
       </ScrollArea>
       <Button
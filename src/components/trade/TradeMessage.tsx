@@ -24,6 +24,7 @@ import { Popover, PopoverContent, PopoverTrigger } from '@/components/ui/popover
 import { Input } from '@/components/ui/input';
 import { ScrollArea } from '@/components/ui/scroll-area';
 import { useClipboard } from '@/hooks/useClipboard';
+import { useGameChatSender } from '@/hooks/useKeySender';
 import { openUrl } from '@/lib/browser-opener';
 import { usePd2Website } from '@/hooks/pd2website/usePD2Website';
 import { emit, listen } from '@/lib/browser-events';
@@ -92,6 +93,25 @@ export const TradeMessage: React.FC<TradeMessageProps> = ({
   const [isProcessing, setIsProcessing] = useState(false);
   const historyCardRef = React.useRef<HTMLDivElement>(null);
   const { copy } = useClipboard();
+  const sendGameChat = useGameChatSender();
+  const [sentInGame, setSentInGame] = useState(false);
+  const whisperFeedback = sentInGame ? 'Sent in game!' : 'Copied to clipboard!';
+
+  // Copy the whisper command; with offerMessagesInGame, whisper in game and copy only if that fails
+  const whisper = async (accountName: string, message: string) => {
+    if (settings.offerMessagesInGame) {
+      try {
+        if (await sendGameChat(accountName, message)) {
+          setSentInGame(true);
+          return;
+        }
+      } catch (err) {
+        console.error('Failed to send game chat:', err);
+      }
+    }
+    await copy(`/w *${accountName} ${message}`);
+    setSentInGame(false);
+  };
 
   // Update timer every second
   useEffect(() => {
@@ -218,7 +238,7 @@ export const TradeMessage: React.FC<TradeMessageProps> = ({
       return;
     }
 
-    // For non-website offers, use the old clipboard method
+    // For non-website offers, copy or whisper the message
     if (!gameName) {
       return; // Don't proceed if game name is empty
     }
@@ -237,9 +257,8 @@ export const TradeMessage: React.FC<TradeMessageProps> = ({
       .replace(/{characterName}/g, characterName)
       .replace(/{itemName}/g, itemName)
       .replace(/{price}/g, price);
-    const acceptMessage = `/w *${accountName} ${message}`;
 
-    await copy(acceptMessage);
+    await whisper(accountName, message);
     setCopiedAction('accept');
     setTimeout(() => setCopiedAction(null), 2000);
     setAcceptPopoverOpen(false);
@@ -262,7 +281,7 @@ export const TradeMessage: React.FC<TradeMessageProps> = ({
       return;
     }
 
-    // For non-website offers, use the old clipboard method
+    // For non-website offers, copy or whisper the message
     const accountName = trade.accountName || '';
     const characterName = trade.characterName || '';
     const itemName = trade.itemName || '';
@@ -274,8 +293,7 @@ export const TradeMessage: React.FC<TradeMessageProps> = ({
       .replace(/{characterName}/g, characterName)
       .replace(/{itemName}/g, itemName)
       .replace(/{price}/g, price);
-    const rejectMessage = `/w *${accountName} ${message}`;
-    await copy(rejectMessage);
+    await whisper(accountName, message);
     setCopiedAction('reject');
     setTimeout(() => setCopiedAction(null), 2000);
   };
@@ -340,7 +358,7 @@ export const TradeMessage: React.FC<TradeMessageProps> = ({
   const isAccepted = trade.listingId && trade.acceptedOfferId === trade.id;
 
   const handleSold = async () => {
-    // Copy sold message to clipboard
+    // Copy or whisper the sold message
     const accountName = trade.accountName || '';
     const characterName = trade.characterName || '';
     const itemName = trade.itemName || '';
@@ -352,8 +370,7 @@ export const TradeMessage: React.FC<TradeMessageProps> = ({
       .replace(/{characterName}/g, characterName)
       .replace(/{itemName}/g, itemName)
       .replace(/{price}/g, price);
-    const soldMessage = `/w *${accountName} ${message}`;
-    await copy(soldMessage);
+    await whisper(accountName, message);
     setCopiedAction('sold');
     setTimeout(() => setCopiedAction(null), 2000);
   };
@@ -550,7 +567,7 @@ export const TradeMessage: React.FC<TradeMessageProps> = ({
                         </Button>
                       </TooltipTrigger>
                       <TooltipContent>
-                        <p>{copiedAction === 'sold' ? 'Copied to clipboard!' : 'Item sold'}</p>
+                        <p>{copiedAction === 'sold' ? whisperFeedback : 'Item sold'}</p>
                       </TooltipContent>
                     </Tooltip>
                   )}
@@ -679,7 +696,7 @@ export const TradeMessage: React.FC<TradeMessageProps> = ({
                               {copiedAction === 'accept'
                                 ? trade.listingId
                                   ? 'Offer accepted!'
-                                  : 'Copied to clipboard!'
+                                  : whisperFeedback
                                 : 'Accept'}
                             </p>
                           </TooltipContent>
@@ -723,7 +740,7 @@ export const TradeMessage: React.FC<TradeMessageProps> = ({
                               {copiedAction === 'reject'
                                 ? trade.listingId
                                   ? 'Offer rejected!'
-                                  : 'Copied to clipboard!'
+                                  : whisperFeedback
                                 : 'Reject'}
                             </p>
                           </TooltipContent>
@@ -24,3 +24,48 @@ export function useKeySender() {
     }
   }, []);
 }
+
+/** One step of a key sequence sent with `useKeySequenceSender` */
+export type KeyStep =
+  | { chord: string }
+  | { text: string }
+  | { wait_ms: number }
+  | { hold: string }
+  | { release: string };
+
+/**
+ * React hook that returns a function sending several steps as one uninterrupted sequence.
+ * Every step is validated before any key is sent; errors are thrown to the caller.
+ *
+ * Usage:
+ *   const sendKeys = useKeySequenceSender();
+ *   await sendKeys([{ chord: "Enter" }, { chord: "Ctrl+V" }, { wait_ms: 50 }, { chord: "Enter" }]);
+ */
+export function useKeySequenceSender() {
+  return useCallback(async (steps: KeyStep[]) => {
+    if (!isTauri()) {
+      console.warn('[KeySender] Key simulation not available in browser');
+      return;
+    }
+    await invoke('press_key_sequence', { steps });
+  }, []);
+}
+
+/**
+ * React hook that returns a function whispering `text` to `account` through the game's chat box.
+ * Diablo II is focused first and long messages are split into several whispers.
+ * Resolves to false in browser, where the caller should fall back to the clipboard.
+ *
+ * Usage:
+ *   const sendGameChat = useGameChatSender();
+ *   if (!(await sendGameChat("shrack", "Your offer has been accepted."))) await copy(...);
+ */
+export function useGameChatSender() {
+  return useCallback(async (account: string, text: string) => {
+    if (!isTauri()) {
+      return false;
+    }
+    await invoke('send_game_chat', { target: account, text });
+    return true;
+  }, []);
+}
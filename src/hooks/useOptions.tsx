@@ -25,6 +25,7 @@ export interface ISettings {
   hotkeyKeyChat: string;
   hotkeyModifierOffers: 'ctrl' | 'alt';
   hotkeyKeyOffers: string;
+  hotkeyInputCapture?: boolean; // Catch hotkeys with the backend key listener instead of global shortcuts
   fillStatValue?: number;
   whisperNotificationsEnabled?: boolean; // General/non-trade whispers
   diablo2Directory?: string;
@@ -38,6 +39,7 @@ export interface ISettings {
   acceptOfferMessageTemplate?: string; // Custom message template for accepting offers (without /w *{accountName})
   rejectOfferMessageTemplate?: string; // Custom message template for rejecting offers (without /w *{accountName})
   soldOfferMessageTemplate?: string; // Custom message template for sold items (without /w *{accountName})
+  offerMessagesInGame?: boolean; // Whisper accept/reject/sold messages in game instead of copying them
   windowTrackingEnabled?: boolean; // Dynamically track D2 window position/size
 }
 
@@ -65,6 +67,7 @@ const DEFAULT_SETTINGS: ISettings = {
   hotkeyKeyChat: 't',
   hotkeyModifierOffers: 'ctrl',
   hotkeyKeyOffers: 'b',
+  hotkeyInputCapture: false,
   fillStatValue: 5,
   whisperNotificationsEnabled: true,
   tradeNotificationsEnabled: true,
@@ -74,6 +77,7 @@ const DEFAULT_SETTINGS: ISettings = {
   acceptOfferMessageTemplate: 'Your offer has been accepted. Game: {gameInfo}',
   rejectOfferMessageTemplate: 'Your offer has been rejected.',
   soldOfferMessageTemplate: 'The item has been sold.',
+  offerMessagesInGame: false,
   windowTrackingEnabled: true,
 };
 
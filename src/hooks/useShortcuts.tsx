@@ -1,131 +1,112 @@
 import { useEffect, useRef, useMemo } from 'react';
 import { isTauri, invoke } from '@tauri-apps/api/core';
-import { register, unregister } from '@tauri-apps/plugin-global-shortcut';
-import { listen } from '@/lib/browser-events';
+import { emit, listen } from '@/lib/browser-events';
 import { useOptions } from './useOptions';
 
 type ShortcutHandler = () => void | Promise<void>;
 
+/** Actions the backend hotkey registry knows about */
+export type HotkeyAction = 'priceCheck' | 'listItem' | 'settings' | 'currencyValuation' | 'chat' | 'offers';
+
+export interface HotkeyBinding {
+  action: HotkeyAction;
+  sequence: string;
+}
+
+/** A binding the registry refused or couldn't register */
+export interface HotkeyProblem {
+  action: HotkeyAction;
+  sequence: string;
+  message: string;
+}
+
 interface ShortcutConfig {
+  action: HotkeyAction;
   modifier: 'ctrl' | 'alt';
   key: string;
-  handler: ShortcutHandler;
+  handler?: ShortcutHandler;
 }
 
-const formatShortcut = (modifier: 'ctrl' | 'alt', key: string): string => {
-  return `${modifier === 'ctrl' ? 'Control' : 'Alt'}+${key.toUpperCase()}`;
+// Key sequence in the backend grammar, e.g. "ctrl+d"
+export const formatHotkey = (modifier: 'ctrl' | 'alt', key: string): string => {
+  return `${modifier}+${key.toLowerCase()}`;
 };
 
-export const useShortcuts = (shortcuts: ShortcutConfig[]) => {
-  const registeredShortcuts = useRef<string[]>([]);
+interface InputCaptureStatus {
+  enabled: boolean;
+  running: boolean;
+  error: string | null;
+}
+
+/**
+ * Hands the bindings to the backend hotkey registry, which registers them only while
+ * Diablo II is focused, and runs the handler of each `hotkey-triggered` action.
+ * With `inputCapture`, keys come from the backend key listener instead of global shortcuts
+ * (e.g. on Wayland).
+ */
+export const useShortcuts = (shortcuts: ShortcutConfig[], inputCapture = false) => {
   const shortcutsRef = useRef<ShortcutConfig[]>(shortcuts);
-  const lastFocusState = useRef<boolean | null>(null);
-  const unlistenRef = useRef<(() => void) | null>(null);
-  const operationChain = useRef<Promise<void>>(Promise.resolve());
 
   // Keep shortcuts ref up to date
   useEffect(() => {
     shortcutsRef.current = shortcuts;
   }, [shortcuts]);
 
+  // Handlers change on every render; only send the bindings when they change
+  const bindingsJson = JSON.stringify(
+    shortcuts.map(({ action, modifier, key }): HotkeyBinding => ({
+      action,
+      sequence: formatHotkey(modifier, key),
+    })),
+  );
+
   useEffect(() => {
     if (!isTauri()) {
       // Global shortcuts not available in browser
       return;
     }
 
-    const unregisterAllShortcuts = async () => {
-      await Promise.all(registeredShortcuts.current.map((shortcut) => unregister(shortcut).catch(() => {})));
-      registeredShortcuts.current = [];
-    };
+    const bindings: HotkeyBinding[] = JSON.parse(bindingsJson);
+    invoke<HotkeyProblem[]>('set_hotkeys', { bindings })
+      .then((problems) => {
+        // The other hotkeys are registered; say which ones were left out
+        if (problems.length > 0) {
+          emit('toast-event', {
+            title: 'Some Hotkeys Were Not Set',
+            description: problems.map(({ sequence, message }) => `${sequence}: ${message}`).join('; '),
+            variant: 'warning',
+          });
+        }
+      })
+      .catch((err) => console.error('Failed to set hotkeys:', err));
+  }, [bindingsJson]);
 
-    // Serialize operations to prevent races between cleanup/setup/events
-    const scheduleOperation = (op: () => Promise<void>) => {
-      operationChain.current = operationChain.current
-        .then(op)
-        .catch((err) => console.error('Shortcut operation failed:', err));
-    };
+  useEffect(() => {
+    if (!isTauri()) {
+      return;
+    }
 
-    const registerShortcuts = async () => {
-      try {
-        // Unregister previous shortcuts first
-        await unregisterAllShortcuts();
-
-        // Register new shortcuts in parallel
-        await Promise.all(
-          shortcutsRef.current.map(async ({ modifier, key, handler }) => {
-            const shortcut = formatShortcut(modifier, key);
-            try {
-              await register(shortcut, (e) => {
-                if (e.state === 'Pressed') {
-                  handler();
-                }
-              });
-              registeredShortcuts.current.push(shortcut);
-            } catch (error: any) {
-              const msg = error ? error.toString().toLowerCase() : '';
-              if (msg.includes('already') || msg.includes('exists') || msg.includes('conflict')) {
-                registeredShortcuts.current.push(shortcut);
-              } else {
-                console.error(`Failed to register shortcut ${shortcut}:`, error);
-              }
-            }
-          }),
-        );
-      } catch (error) {
-        console.error('Failed to load global shortcut plugin:', error);
-      }
-    };
+    const unlistenPromise = listen<{ action: HotkeyAction }>('hotkey-triggered', ({ payload }) => {
+      const shortcut = shortcutsRef.current.find(({ action }) => action === payload.action);
+      shortcut?.handler?.();
+    });
 
-    const setup = async () => {
-      try {
-        // OP REQ: Always initialize assuming D2 is focused
-        // We schedule registration immediately.
-        scheduleOperation(async () => {
-          // Optimistically set focused
-          lastFocusState.current = true;
-          await registerShortcuts();
-        });
-
-        // 1. Check actual state to correct if needed
-        const isFocused = await invoke<boolean>('is_diablo_focused');
-
-        scheduleOperation(async () => {
-          // Update state based on reality
-          if (lastFocusState.current !== isFocused) {
-            lastFocusState.current = isFocused;
-            if (isFocused) await registerShortcuts();
-            else await unregisterAllShortcuts();
-          }
-        });
-
-        // 2. Listen for changes
-        unlistenRef.current = await listen<boolean>('diablo-focus-changed', async ({ payload: isFocused }) => {
-          scheduleOperation(async () => {
-            if (lastFocusState.current === isFocused) return;
-            lastFocusState.current = isFocused;
-
-            if (isFocused) await registerShortcuts();
-            else await unregisterAllShortcuts();
-          });
-        });
-      } catch (error) {
-        console.error('Failed to setup shortcut listener:', error);
-      }
+    return () => {
+      unlistenPromise.then((unlisten) => unlisten());
     };
+  }, []);
 
-    setup();
+  useEffect(() => {
+    if (!isTauri()) {
+      return;
+    }
 
-    return () => {
-      if (unlistenRef.current) {
-        unlistenRef.current();
-      }
-      // Cleanup: Unregister
-      scheduleOperation(async () => {
-        await unregisterAllShortcuts();
-      });
-    };
-  }, [shortcuts]);
+    invoke<InputCaptureStatus>('set_input_capture_enabled', { enabled: inputCapture })
+      .then((status) => {
+        if (status.error) console.error('Input capture failed:', status.error);
+      })
+      .catch((err) => console.error('Failed to set input capture:', err));
+  }, [inputCapture]);
 };
 
 export const useAppShortcuts = (
@@ -144,6 +125,7 @@ export const useAppShortcuts = (
 
     if (settings.hotkeyKey) {
       configs.push({
+        action: 'priceCheck',
         modifier: settings.hotkeyModifier,
         key: settings.hotkeyKey,
         handler: onItemSearch,
@@ -152,14 +134,25 @@ export const useAppShortcuts = (
 
     if (settings.hotkeyKeyListItem) {
       configs.push({
+        action: 'listItem',
         modifier: settings.hotkeyModifierListItem,
         key: settings.hotkeyKeyListItem,
         handler: onQuickList,
       });
     }
 
+    // Opening settings is handled by the tray, which listens for the action itself
+    if (settings.hotkeyKeySettings) {
+      configs.push({
+        action: 'settings',
+        modifier: settings.hotkeyModifierSettings ?? 'ctrl',
+        key: settings.hotkeyKeySettings,
+      });
+    }
+
     if (settings.hotkeyKeyCurrencyValuation) {
       configs.push({
+        action: 'currencyValuation',
         modifier: settings.hotkeyModifierCurrencyValuation,
         key: settings.hotkeyKeyCurrencyValuation,
         handler: onCurrencyValuation,
@@ -168,6 +161,7 @@ export const useAppShortcuts = (
 
     if (settings.hotkeyKeyChat && onChat) {
       configs.push({
+        action: 'chat',
         modifier: settings.hotkeyModifierChat,
         key: settings.hotkeyKeyChat,
         handler: onChat,
@@ -176,6 +170,7 @@ export const useAppShortcuts = (
 
     if (settings.hotkeyKeyOffers && onOffers) {
       configs.push({
+        action: 'offers',
         modifier: settings.hotkeyModifierOffers,
         key: settings.hotkeyKeyOffers,
         handler: onOffers,
@@ -189,6 +184,8 @@ export const useAppShortcuts = (
     settings.hotkeyKey,
     settings.hotkeyModifierListItem,
     settings.hotkeyKeyListItem,
+    settings.hotkeyModifierSettings,
+    settings.hotkeyKeySettings,
     settings.hotkeyModifierCurrencyValuation,
     settings.hotkeyKeyCurrencyValuation,
     settings.hotkeyModifierChat,
@@ -202,5 +199,5 @@ export const useAppShortcuts = (
     onOffers,
   ]);
 
-  useShortcuts(shortcuts);
+  useShortcuts(shortcuts, isLoading ? false : settings.hotkeyInputCapture ?? false);
 };
@@ -209,14 +209,6 @@ export const useSocketNotifications = ({
             return; // Skip announcements by default
           }
 
-          // Check if player is in ignore list
-          const ignoreList = settings?.whisperIgnoreList || [];
-          const isIgnored = ignoreList.some((ignoredPlayer) => normalizeName(ignoredPlayer) === whisperFromNormalized);
-
-          if (isIgnored) {
-            return; // Skip ignored players
-          }
-
           // Check if Diablo is focused
           const isDiabloFocused = await invoke<boolean>('is_diablo_focused');
 
@@ -284,7 +276,6 @@ export const useSocketNotifications = ({
     };
   }, [
     whisperNotificationsEnabled,
-    settings?.whisperIgnoreList,
     settings?.whisperAnnouncementsEnabled,
     settings?.whisperJoinNotificationsEnabled,
     settings?.whisperNotificationsEnabled,
@@ -1,9 +1,7 @@
 import React, { createContext, useContext, useEffect, useRef, useState } from 'react';
 import { isTauri } from '@tauri-apps/api/core';
-import { useOptions } from '@/hooks/useOptions';
 import { openCenteredWindow, attachWindowCloseHandler } from '@/lib/window';
 import { listen } from '@/lib/browser-events';
-import { isRegistered, register, unregister } from '@tauri-apps/plugin-global-shortcut';
 import { Menu } from '@tauri-apps/api/menu';
 import { TrayIcon, TrayIconEvent } from '@tauri-apps/api/tray';
 import { defaultWindowIcon } from '@tauri-apps/api/app';
@@ -24,9 +22,7 @@ export const useTray = () => useContext(TrayContext);
 export const TrayProvider: React.FC<{ children?: React.ReactNode }> = ({ children }) => {
   const [tray, setTray] = useState<any | null>(null);
   const [settingsWindow, setSettingsWindow] = useState<any | null>(null);
-  const { settings } = useOptions();
   const trayRef = useRef<any | null>(null);
-  const lastShortcutRef = useRef<string | null>(null);
   const settingsWinRef = useRef<any | null>(null);
   const [isSettingsOpen, setIsSettingsOpen] = useState(false);
   const isSettingsOpenRef = useRef(isSettingsOpen);
@@ -81,70 +77,24 @@ export const TrayProvider: React.FC<{ children?: React.ReactNode }> = ({ childre
     };
   }, []);
 
-  // Register global shortcut for opening settings (only when Diablo is focused)
+  // Open settings on its hotkey; the backend hotkey registry only fires it while Diablo is focused
   useEffect(() => {
-    if (!isTauri() || !settings?.hotkeyModifierSettings || !settings?.hotkeyKeySettings) return;
-
-    const shortcut = `${settings.hotkeyModifierSettings}+${settings.hotkeyKeySettings}`.toLowerCase();
-
-    const unregisterShortcut = async () => {
-      if (lastShortcutRef.current) {
-        try {
-          await unregister(lastShortcutRef.current);
-          lastShortcutRef.current = null;
-        } catch {
-          // Ignore errors
-        }
-      }
-    };
-
-    const registerShortcut = async () => {
-      try {
-        // Unregister previous shortcut first
-        await unregisterShortcut();
-
-        if (!(await isRegistered(shortcut))) {
-          await register(shortcut, (event) => {
-            if (event.state === 'Pressed') {
-              showSettingsWindow();
-            }
-          });
-          lastShortcutRef.current = shortcut;
-        }
-      } catch (err) {
-        console.error('Failed to register settings shortcut:', err);
-      }
-    };
-
-    // Listen for Diablo focus changes to register/unregister hotkey
-    let unlisten: (() => void) | null = null;
+    let unlisten: (() => void) | undefined;
 
-    listen<boolean>('diablo-focus-changed', async ({ payload: isFocused }) => {
-      if (isFocused) {
-        // Diablo gained focus - register hotkey
-        await registerShortcut();
-      } else {
-        // Diablo lost focus - unregister hotkey
-        await unregisterShortcut();
-      }
+    listen<{ action: string }>('hotkey-triggered', ({ payload }) => {
+      if (payload.action === 'settings') showSettingsWindow();
     })
       .then((off) => {
         unlisten = off;
       })
-      .catch((error) => {
-        console.error('Failed to listen for diablo-focus-changed event:', error);
+      .catch((err) => {
+        console.error('Failed to listen for hotkey-triggered event:', err);
       });
 
     return () => {
-      if (unlisten) {
-        unlisten();
-      }
-      // Unregister shortcut on cleanup
-      if (isTauri() && lastShortcutRef.current) {
-        unregisterShortcut().catch(console.error);
-      }
+      if (unlisten) unlisten();
     };
-  }, [settings?.hotkeyModifierSettings, settings?.hotkeyKeySettings]);
+  }, []);
 
   useEffect(() => {
     if (!isTauri()) {
@@ -9,7 +9,7 @@ import type { BrowserWindow } from '@/lib/window';
 import { useClipboard } from '@/hooks/useClipboard';
 import { TrayProvider, useTray } from '@/hooks/useTray';
 import { OptionsProvider, useOptions } from '@/hooks/useOptions';
-import { useKeySender } from '@/hooks/useKeySender';
+import { useKeySender, useKeySequenceSender } from '@/hooks/useKeySender';
 import { DialogProvider } from '@/hooks/useDialog';
 import { Pd2WebsiteProvider } from '@/hooks/pd2website/usePD2Website';
 import {
@@ -48,6 +48,7 @@ const LandingPage: React.FC = () => {
   const focusCheckIntervalRef = useRef<NodeJS.Timeout | null>(null);
   const { read } = useClipboard();
   const keyPress = useKeySender();
+  const sendKeys = useKeySequenceSender();
   const { settings, isLoading } = useOptions();
   const { settingsWindow } = useTray();
   const { isConnected } = useSocket({ settings });
@@ -113,11 +114,15 @@ const LandingPage: React.FC = () => {
 
   // Copy item from clipboard and validate
   const copyAndValidateItem = useCallback(async (): Promise<string | null> => {
-    await keyPress('ctrl+c');
-    await sleep(250);
+    // The wait runs on the input thread, so no other simulated key lands before the game copies
+    try {
+      await sendKeys([{ chord: 'ctrl+c' }, { wait_ms: 250 }]);
+    } catch (err) {
+      console.error('[LandingPage] Failed to copy item:', err);
+    }
     const raw = await read();
     return clipboardContainsValidItem(raw) ? raw : null;
-  }, [read, keyPress]);
+  }, [read, sendKeys]);
 
   // Open item search window
   const fireSearch = useCallback(async () => {
@@ -708,6 +713,22 @@ const LandingPage: React.FC = () => {
       }
     };
   }, [settings.whisperNotificationsEnabled, settings.tradeNotificationsEnabled, settings.diablo2Directory, isLoading]);
+
+  // The chat watcher drops whispers from ignored players, so keep its ignore list in sync with the settings
+  useEffect(() => {
+    if (!isTauri() || isLoading) return;
+
+    const syncIgnoreList = async () => {
+      try {
+        const filter = await invoke<Record<string, unknown>>('get_whisper_filter');
+        await invoke('set_whisper_filter', { config: { ...filter, ignore: settings.whisperIgnoreList ?? [] } });
+      } catch (error) {
+        console.error('Failed to update whisper filter:', error);
+      }
+    };
+
+    syncIgnoreList();
+  }, [settings.whisperIgnoreList, isLoading]);
   // Dynamic Window Tracking
   // Consolidated Dynamic Window Tracking & Focus Event Listener
   useEffect(() => {